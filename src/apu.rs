@@ -0,0 +1,905 @@
+// =========================================================================
+// [Sound Controller]
+// PSG (Sound 1~4) / Direct Sound (FIFO A/B)
+// SOUND1CNT_L~SOUNDBIAS, WAVE_RAM (0x04000060~0x0400009F), FIFO_A/B (0x040000A0~0x040000A7)
+// PSGはマスタクロックとの差分で進め、一定周期でステレオのサンプルを出力する
+// =========================================================================
+use common::*;
+use std::collections::VecDeque;
+
+// FIFOサイズ (32byte = 8word)
+const _FIFO_SIZE: usize = 32;
+// FIFOの残りがこのバイト数以下になったらDMAへ補充を要求する
+const _FIFO_REFILL: usize = 16;
+
+// SOUNDCNT_H
+const _SOUNDCNT_H_MASK: u16 = 0x770F;     // 読み出し可能ビット (リセットビットは書き込み専用)
+const _SOUNDCNT_H_TIMER_A: u16 = 0x0400;  // FIFO A タイマ選択 (0:Timer0, 1:Timer1)
+const _SOUNDCNT_H_RESET_A: u16 = 0x0800;  // FIFO A リセット
+const _SOUNDCNT_H_TIMER_B: u16 = 0x4000;  // FIFO B タイマ選択
+const _SOUNDCNT_H_RESET_B: u16 = 0x8000;  // FIFO B リセット
+
+// SOUNDCNT_X
+const _SOUNDCNT_X_ENABLE: u16 = 0x0080;   // サウンド回路 動作
+
+// xCNT_X: 再生開始 / 長さカウンタ有効
+const _SOUNDCNT_RESTART: u16 = 0x8000;
+const _SOUNDCNT_LENGTH: u16 = 0x4000;
+
+// レジスタ定義 |読み出しマスク|書き込みマスク| (0x04000060~0x04000089, 16bit単位)
+// SOUNDCNT_H/SOUNDCNT_X は個別に処理
+const _PSG_REGS: [(u16, u16); 21] = [
+    (0x007F, 0x007F), // SOUND1CNT_L
+    (0xFFC0, 0xFFFF), // SOUND1CNT_H
+    (0x4000, 0xC7FF), // SOUND1CNT_X
+    (0x0000, 0x0000),
+    (0xFFC0, 0xFFFF), // SOUND2CNT_L
+    (0x0000, 0x0000),
+    (0x4000, 0xC7FF), // SOUND2CNT_H
+    (0x0000, 0x0000),
+    (0x00E0, 0x00E0), // SOUND3CNT_L
+    (0xE000, 0xE0FF), // SOUND3CNT_H
+    (0x4000, 0xC7FF), // SOUND3CNT_X
+    (0x0000, 0x0000),
+    (0xFF00, 0xFF3F), // SOUND4CNT_L
+    (0x0000, 0x0000),
+    (0x40FF, 0xC0FF), // SOUND4CNT_H
+    (0x0000, 0x0000),
+    (0xFF77, 0xFF77), // SOUNDCNT_L
+    (0x0000, 0x0000), // SOUNDCNT_H
+    (0x0000, 0x0000), // SOUNDCNT_X
+    (0x0000, 0x0000),
+    (0xC3FE, 0xC3FE), // SOUNDBIAS
+];
+const _REG_SOUNDCNT_L: usize = 0x10;
+const _REG_SOUNDBIAS: usize = 0x14;
+
+// フレームシーケンサ (512Hz: 長さ256Hz, スイープ128Hz, エンベロープ64Hz)
+const _FRAME_SEQUENCER_CYCLES: u64 = 32768;
+// 出力サンプル周期 (32768Hz, SOUNDBIAS Bit[15:14]で倍率変更)
+const _SAMPLE_CYCLES: u64 = 512;
+// 出力バッファ (溢れた場合は古いサンプルから捨てる)
+const _SAMPLE_BUFFER_SIZE: usize = 8192;
+
+// 矩形波のデューティ比 (12.5%, 25%, 50%, 75%)
+const _DUTY: [[u8; 8]; 4] = [
+    [0, 0, 0, 0, 0, 0, 0, 1],
+    [1, 0, 0, 0, 0, 0, 0, 1],
+    [1, 0, 0, 0, 0, 1, 1, 1],
+    [0, 1, 1, 1, 1, 1, 1, 0],
+];
+// ノイズの分周比 (Bit[2:0])
+const _NOISE_DIVISOR: [u64; 8] = [8, 16, 32, 48, 64, 80, 96, 112];
+
+// 長さカウンタ (0でチャネル停止)
+#[derive(Clone, Copy)]
+struct Length {
+    counter: u16,
+    enabled: bool,
+}
+
+impl Length {
+    // 戻り値はカウンタ満了の有無
+    fn clock(&mut self) -> bool {
+        if !self.enabled || self.counter == 0 {
+            return false;
+        }
+        self.counter -= 1;
+        self.counter == 0
+    }
+}
+
+// 音量エンベロープ |Initial Volume(4)|Direction|Step(3)| (Bit[15:8])
+#[derive(Clone, Copy)]
+struct Envelope {
+    volume: u8,
+    step: u8,
+    increase: bool,
+    timer: u8,
+}
+
+impl Envelope {
+    fn new() -> Self {
+        Envelope { volume: 0, step: 0, increase: false, timer: 0 }
+    }
+
+    fn restart(&mut self, reg: u16) {
+        self.volume = (reg >> 12) as u8;
+        self.increase = (reg & 0x0800) != 0;
+        self.step = ((reg >> 8) & 7) as u8;
+        self.timer = self.step;
+    }
+
+    fn clock(&mut self) {
+        if self.step == 0 {
+            return;
+        }
+        self.timer -= 1;
+        if self.timer == 0 {
+            self.timer = self.step;
+            if self.increase && self.volume < 15 {
+                self.volume += 1;
+            } else if !self.increase && self.volume > 0 {
+                self.volume -= 1;
+            }
+        }
+    }
+
+    // 初期音量0かつ減少の場合はDACが停止する
+    fn is_dac_off(reg: u16) -> bool {
+        (reg & 0xF800) == 0
+    }
+}
+
+// 周波数スイープ (Sound 1) |Time(3)|Direction|Shift(3)|
+#[derive(Clone, Copy)]
+struct Sweep {
+    reg: u16,      // SOUND1CNT_L
+    timer: u8,
+    shadow: u16,   // 計算用の周波数
+    enabled: bool,
+}
+
+impl Sweep {
+    fn period(&self) -> u8 {
+        ((self.reg >> 4) & 7) as u8
+    }
+
+    fn shift(&self) -> u16 {
+        self.reg & 7
+    }
+
+    // 次の周波数 (2047を超えるとチャネル停止)
+    fn next_freq(&self) -> u16 {
+        let delta: u16 = self.shadow >> self.shift();
+        if (self.reg & 0x0008) != 0 { self.shadow - delta } else { self.shadow + delta }
+    }
+}
+
+// Sound 1/2: 矩形波
+#[derive(Clone, Copy)]
+struct Square {
+    enabled: bool,
+    freq: u16,     // 周波数 (131072/(2048-n) Hz)
+    duty: usize,
+    phase: usize,  // デューティ位置 (0~7)
+    timer: u64,    // 次のデューティ位置までのサイクル数
+    length: Length,
+    envelope: Envelope,
+    sweep: Sweep,
+}
+
+impl Square {
+    fn new() -> Self {
+        Square {
+            enabled: false,
+            freq: 0,
+            duty: 0,
+            phase: 0,
+            timer: 0,
+            length: Length { counter: 0, enabled: false },
+            envelope: Envelope::new(),
+            sweep: Sweep { reg: 0, timer: 0, shadow: 0, enabled: false },
+        }
+    }
+
+    fn restart(&mut self, envelope: u16) {
+        self.enabled = !Envelope::is_dac_off(envelope);
+        self.envelope.restart(envelope);
+        if self.length.counter == 0 {
+            self.length.counter = 64;
+        }
+        self.timer = (2048 - self.freq as u64) * 16;
+        // スイープ (シフト数が0以外なら開始時にオーバーフロー判定)
+        let sweep: &mut Sweep = &mut self.sweep;
+        sweep.shadow = self.freq;
+        sweep.timer = if sweep.period() == 0 { 8 } else { sweep.period() };
+        sweep.enabled = sweep.period() != 0 || sweep.shift() != 0;
+        if sweep.shift() != 0 && sweep.next_freq() > 2047 {
+            self.enabled = false;
+        }
+    }
+
+    fn tick(&mut self, mut cycles: u64) {
+        if !self.enabled {
+            return;
+        }
+        let period: u64 = (2048 - self.freq as u64) * 16;
+        while cycles >= self.timer {
+            cycles -= self.timer;
+            self.timer = period;
+            self.phase = (self.phase + 1) & 7;
+        }
+        self.timer -= cycles;
+    }
+
+    fn clock_sweep(&mut self) {
+        let sweep: &mut Sweep = &mut self.sweep;
+        sweep.timer = sweep.timer.saturating_sub(1);
+        if sweep.timer != 0 {
+            return;
+        }
+        sweep.timer = if sweep.period() == 0 { 8 } else { sweep.period() };
+        if !sweep.enabled || sweep.period() == 0 {
+            return;
+        }
+        let freq: u16 = sweep.next_freq();
+        if freq > 2047 {
+            self.enabled = false;
+        } else if sweep.shift() != 0 {
+            sweep.shadow = freq;
+            self.freq = freq;
+            if sweep.next_freq() > 2047 {
+                self.enabled = false;
+            }
+        }
+    }
+
+    fn output(&self) -> u8 {
+        if self.enabled && _DUTY[self.duty][self.phase] != 0 { self.envelope.volume } else { 0 }
+    }
+}
+
+// Sound 3: 波形メモリ (4bit×32サンプル×2バンク)
+struct Wave {
+    enabled: bool,
+    dac: bool,           // SOUND3CNT_L Bit7
+    two_banks: bool,     // 64サンプル (2バンク連続再生)
+    bank: usize,         // 再生バンク
+    volume: u16,         // SOUND3CNT_H Bit[15:13]
+    freq: u16,           // 周波数 (2097152/(2048-n) Hz)
+    position: usize,     // 再生位置 (0~63)
+    timer: u64,
+    length: Length,
+    ram: [u8; 32],
+}
+
+impl Wave {
+    fn new() -> Self {
+        Wave {
+            enabled: false,
+            dac: false,
+            two_banks: false,
+            bank: 0,
+            volume: 0,
+            freq: 0,
+            position: 0,
+            timer: 0,
+            length: Length { counter: 0, enabled: false },
+            ram: [0; 32],
+        }
+    }
+
+    fn restart(&mut self) {
+        self.enabled = self.dac;
+        if self.length.counter == 0 {
+            self.length.counter = 256;
+        }
+        self.position = 0;
+        self.timer = (2048 - self.freq as u64) * 8;
+    }
+
+    fn tick(&mut self, mut cycles: u64) {
+        if !self.enabled {
+            return;
+        }
+        let period: u64 = (2048 - self.freq as u64) * 8;
+        let samples: usize = if self.two_banks { 64 } else { 32 };
+        while cycles >= self.timer {
+            cycles -= self.timer;
+            self.timer = period;
+            self.position = (self.position + 1) % samples;
+        }
+        self.timer -= cycles;
+    }
+
+    fn output(&self) -> u8 {
+        if !self.enabled {
+            return 0;
+        }
+        // 上位ニブルが先
+        let bank: usize = (self.bank + self.position / 32) & 1;
+        let byte: u8 = self.ram[bank * 16 + (self.position % 32) / 2];
+        let sample: u8 = if (self.position & 1) == 0 { byte >> 4 } else { byte & 0x0F };
+        // 音量 (Bit15: 75%, Bit[14:13]: 0%, 100%, 50%, 25%)
+        if (self.volume & 0x8000) != 0 {
+            return sample * 3 / 4;
+        }
+        match (self.volume >> 13) & 3 {
+            0 => 0,
+            1 => sample,
+            2 => sample >> 1,
+            _ => sample >> 2,
+        }
+    }
+}
+
+// Sound 4: ノイズ (LFSR 15bit/7bit)
+struct Noise {
+    enabled: bool,
+    lfsr: u16,
+    narrow: bool,  // 7bit LFSR
+    period: u64,   // LFSR更新周期 (サイクル数)
+    timer: u64,
+    length: Length,
+    envelope: Envelope,
+}
+
+impl Noise {
+    fn new() -> Self {
+        Noise {
+            enabled: false,
+            lfsr: 0,
+            narrow: false,
+            period: 0,
+            timer: 0,
+            length: Length { counter: 0, enabled: false },
+            envelope: Envelope::new(),
+        }
+    }
+
+    // SOUND4CNT_H |Restart|Length|...|Shift(4)|Width|Ratio(3)|
+    fn set_frequency(&mut self, reg: u16) {
+        self.narrow = (reg & 0x0008) != 0;
+        self.period = (_NOISE_DIVISOR[(reg & 7) as usize] << ((reg >> 4) & 0x0F)) * 4;
+    }
+
+    fn restart(&mut self, envelope: u16) {
+        self.enabled = !Envelope::is_dac_off(envelope);
+        self.envelope.restart(envelope);
+        if self.length.counter == 0 {
+            self.length.counter = 64;
+        }
+        self.lfsr = if self.narrow { 0x7F } else { 0x7FFF };
+        self.timer = self.period;
+    }
+
+    fn tick(&mut self, mut cycles: u64) {
+        if !self.enabled {
+            return;
+        }
+        while cycles >= self.timer {
+            cycles -= self.timer;
+            self.timer = self.period;
+            let bit: u16 = (self.lfsr ^ (self.lfsr >> 1)) & 1;
+            self.lfsr = if self.narrow {
+                (self.lfsr >> 1) | (bit << 6)
+            } else {
+                (self.lfsr >> 1) | (bit << 14)
+            };
+        }
+        self.timer -= cycles;
+    }
+
+    fn output(&self) -> u8 {
+        if self.enabled && (self.lfsr & 1) == 0 { self.envelope.volume } else { 0 }
+    }
+}
+
+struct DirectSound {
+    fifo: VecDeque<i8>,
+    sample: i8,        // 現在の出力サンプル
+    dma_request: bool, // DMA補充要求
+}
+
+impl DirectSound {
+    fn new() -> Self {
+        DirectSound {
+            fifo: VecDeque::with_capacity(_FIFO_SIZE),
+            sample: 0,
+            dma_request: false,
+        }
+    }
+
+    fn push(&mut self, val: u8) {
+        // 満杯の場合は書き込みを無視
+        if self.fifo.len() < _FIFO_SIZE {
+            self.fifo.push_back(val as i8);
+        }
+    }
+
+    // タイマオーバーフローで1サンプル取り出す
+    fn pop(&mut self) {
+        if let Some(sample) = self.fifo.pop_front() {
+            self.sample = sample;
+        }
+        if self.fifo.len() <= _FIFO_REFILL {
+            self.dma_request = true;
+        }
+    }
+
+    fn reset(&mut self) {
+        self.fifo.clear();
+        self.sample = 0;
+    }
+}
+
+pub struct Apu {
+    regs: [u16; 21],           // SOUND1CNT_L~SOUNDBIAS (0x04000060~0x04000089)
+    soundcnt_h: u16,
+    square: [Square; 2],       // Sound 1/2
+    wave: Wave,                // Sound 3
+    noise: Noise,              // Sound 4
+    fifo: [DirectSound; 2],    // FIFO A/B
+    now: u64,                  // 最後に同期したマスタクロック
+    frame_timer: u64,          // 次のフレームシーケンサ更新までのサイクル数
+    frame_step: u8,
+    samples: VecDeque<(i16, i16)>, // 出力サンプル |Left|Right|
+}
+
+#[allow(dead_code)]
+impl Apu {
+    pub fn new() -> Self {
+        let mut regs: [u16; 21] = [0; 21];
+        regs[_REG_SOUNDBIAS] = 0x0200;
+        Apu {
+            regs,
+            soundcnt_h: 0,
+            square: [Square::new(); 2],
+            wave: Wave::new(),
+            noise: Noise::new(),
+            fifo: [DirectSound::new(), DirectSound::new()],
+            now: 0,
+            frame_timer: _FRAME_SEQUENCER_CYCLES,
+            frame_step: 0,
+            samples: VecDeque::with_capacity(_SAMPLE_BUFFER_SIZE),
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        (self.regs[0x12] & _SOUNDCNT_X_ENABLE) != 0
+    }
+
+    // 出力サンプル周期 (サイクル数)
+    pub fn sample_cycles(&self) -> u64 {
+        _SAMPLE_CYCLES >> (self.regs[_REG_SOUNDBIAS] >> 14)
+    }
+
+    // 現在の出力をサンプルバッファへ追加
+    pub fn push_sample(&mut self) {
+        if self.samples.len() >= _SAMPLE_BUFFER_SIZE {
+            self.samples.pop_front();
+        }
+        let sample: (i16, i16) = self.mix();
+        self.samples.push_back(sample);
+    }
+
+    // 出力済みサンプルを取り出す
+    pub fn take_samples(&mut self) -> Vec<(i16, i16)> {
+        self.samples.drain(..).collect()
+    }
+
+    // PSGの出力 (0~15) |Sound1|Sound2|Sound3|Sound4|
+    pub fn psg_output(&self) -> [u8; 4] {
+        [self.square[0].output(), self.square[1].output(), self.wave.output(), self.noise.output()]
+    }
+
+    // ステレオ合成 (SOUNDCNT_L: PSG音量/左右出力, SOUNDCNT_H: PSG比率/FIFO音量/左右出力)
+    fn mix(&self) -> (i16, i16) {
+        if !self.is_enabled() {
+            return (0, 0);
+        }
+        let cnt_l: u16 = self.regs[_REG_SOUNDCNT_L];
+        let psg: [u8; 4] = self.psg_output();
+        // PSG比率 (25%, 50%, 100%)
+        let psg_shift: u32 = match self.soundcnt_h & 3 {
+            0 => 2,
+            1 => 1,
+            _ => 0,
+        };
+        let bias: i32 = (self.regs[_REG_SOUNDBIAS] & 0x03FE) as i32;
+        // |Right|Left|
+        let mut out: [i32; 2] = [0; 2];
+        for (side, sample) in out.iter_mut().enumerate() {
+            let volume: i32 = ((cnt_l >> (side * 4)) & 7) as i32 + 1;
+            let psg_sum: i32 = (0..4)
+                .filter(|ch| (cnt_l & (1 << (8 + side * 4 + ch))) != 0)
+                .map(|ch| psg[ch] as i32)
+                .sum();
+            let mut mixed: i32 = (psg_sum * volume) >> psg_shift;
+            for n in 0..2 {
+                if (self.soundcnt_h & (1 << (8 + n * 4 + side))) != 0 {
+                    // FIFO音量 (50%, 100%)
+                    let scale: i32 = if (self.soundcnt_h & (4 << n)) != 0 { 4 } else { 2 };
+                    mixed += self.fifo[n].sample as i32 * scale;
+                }
+            }
+            *sample = ((mixed + bias).clamp(0, 0x3FF) - 0x200) << 6;
+        }
+        (out[1] as i16, out[0] as i16)
+    }
+
+    // フレームシーケンサ (長さ: 0,2,4,6, スイープ: 2,6, エンベロープ: 7)
+    fn clock_frame(&mut self) {
+        let step: u8 = self.frame_step;
+        self.frame_step = (step + 1) & 7;
+        if (step & 1) == 0 {
+            for square in self.square.iter_mut() {
+                if square.length.clock() {
+                    square.enabled = false;
+                }
+            }
+            if self.wave.length.clock() {
+                self.wave.enabled = false;
+            }
+            if self.noise.length.clock() {
+                self.noise.enabled = false;
+            }
+        }
+        if step == 2 || step == 6 {
+            self.square[0].clock_sweep();
+        }
+        if step == 7 {
+            self.square.iter_mut().for_each(|s| s.envelope.clock());
+            self.noise.envelope.clock();
+        }
+    }
+
+    // PSGを指定サイクル数進める
+    fn tick(&mut self, mut cycles: u64) {
+        while cycles > 0 {
+            let step: u64 = cycles.min(self.frame_timer);
+            self.square.iter_mut().for_each(|s| s.tick(step));
+            self.wave.tick(step);
+            self.noise.tick(step);
+            self.frame_timer -= step;
+            cycles -= step;
+            if self.frame_timer == 0 {
+                self.frame_timer = _FRAME_SEQUENCER_CYCLES;
+                self.clock_frame();
+            }
+        }
+    }
+
+    // タイマ0/1 オーバーフロー (選択しているFIFOからサンプルを取り出す)
+    pub fn timer_overflow(&mut self, timer: u8) {
+        let select: [u16; 2] = [_SOUNDCNT_H_TIMER_A, _SOUNDCNT_H_TIMER_B];
+        for (i, mask) in select.iter().enumerate() {
+            let selected: u8 = ((self.soundcnt_h & mask) != 0) as u8;
+            if selected == timer {
+                self.fifo[i].pop();
+            }
+        }
+    }
+
+    // FIFOのDMA補充要求 (取得後クリア, 0:FIFO A, 1:FIFO B)
+    pub fn take_fifo_request(&mut self, n: usize) -> bool {
+        std::mem::take(&mut self.fifo[n].dma_request)
+    }
+
+    // FIFOの現在の出力サンプル
+    pub fn fifo_sample(&self, n: usize) -> i8 {
+        self.fifo[n].sample
+    }
+
+    pub fn fifo_len(&self, n: usize) -> usize {
+        self.fifo[n].fifo.len()
+    }
+
+    fn write_soundcnt_h(&mut self, val: u16) {
+        if (val & _SOUNDCNT_H_RESET_A) != 0 {
+            self.fifo[0].reset();
+        }
+        if (val & _SOUNDCNT_H_RESET_B) != 0 {
+            self.fifo[1].reset();
+        }
+        self.soundcnt_h = val & _SOUNDCNT_H_MASK;
+    }
+
+    // SOUNDCNT_X (停止するとPSGのレジスタ/状態をリセット)
+    fn write_soundcnt_x(&mut self, val: u8) {
+        self.regs[0x12] = val as u16 & _SOUNDCNT_X_ENABLE;
+        if !self.is_enabled() {
+            self.regs[..=_REG_SOUNDCNT_L].fill(0);
+            let ram: [u8; 32] = self.wave.ram;
+            self.square = [Square::new(); 2];
+            self.wave = Wave::new();
+            self.wave.ram = ram;
+            self.noise = Noise::new();
+            self.frame_step = 0;
+        }
+    }
+
+    // PSGレジスタ書き込み (0x04000060~0x04000081)
+    fn write_psg(&mut self, offset: usize, val: u8) {
+        let index: usize = (offset - 0x060) / 2;
+        let shift: usize = (offset & 1) * 8;
+        let reg: u16 = ((self.regs[index] & !(0xFF << shift)) | ((val as u16) << shift)) & _PSG_REGS[index].1;
+        let low: bool = (offset & 1) == 0;
+        // 再生開始ビット (SOUNDxCNT_X Bit15) は書き込み専用
+        let is_control: bool = matches!(index, 0x02 | 0x06 | 0x0A | 0x0E);
+        let restart: bool = is_control && !low && (reg & _SOUNDCNT_RESTART) != 0;
+        self.regs[index] = if is_control { reg & !_SOUNDCNT_RESTART } else { reg };
+        match index {
+            0x00 => self.square[0].sweep.reg = reg,
+            // 長さ/デューティ/エンベロープ
+            0x01 | 0x04 => {
+                let square: &mut Square = &mut self.square[index / 4];
+                if low {
+                    square.length.counter = 64 - (reg & 0x3F);
+                }
+                square.duty = ((reg >> 6) & 3) as usize;
+                if Envelope::is_dac_off(reg) {
+                    square.enabled = false;
+                }
+            },
+            // 周波数/長さカウンタ有効/再生開始
+            0x02 | 0x06 => {
+                let n: usize = index / 4;
+                let square: &mut Square = &mut self.square[n];
+                square.freq = reg & 0x07FF;
+                square.length.enabled = (reg & _SOUNDCNT_LENGTH) != 0;
+                if restart {
+                    let envelope: u16 = self.regs[1 + n * 3];
+                    square.restart(envelope);
+                }
+            },
+            0x08 => {
+                self.wave.two_banks = (reg & 0x0020) != 0;
+                self.wave.bank = ((reg >> 6) & 1) as usize;
+                self.wave.dac = (reg & 0x0080) != 0;
+                if !self.wave.dac {
+                    self.wave.enabled = false;
+                }
+            },
+            0x09 => {
+                if low {
+                    self.wave.length.counter = 256 - (reg & 0xFF);
+                }
+                self.wave.volume = reg & 0xE000;
+            },
+            0x0A => {
+                self.wave.freq = reg & 0x07FF;
+                self.wave.length.enabled = (reg & _SOUNDCNT_LENGTH) != 0;
+                if restart {
+                    self.wave.restart();
+                }
+            },
+            0x0C => {
+                if low {
+                    self.noise.length.counter = 64 - (reg & 0x3F);
+                }
+                if Envelope::is_dac_off(reg) {
+                    self.noise.enabled = false;
+                }
+            },
+            0x0E => {
+                self.noise.set_frequency(reg);
+                self.noise.length.enabled = (reg & _SOUNDCNT_LENGTH) != 0;
+                if restart {
+                    let envelope: u16 = self.regs[0x0C];
+                    self.noise.restart(envelope);
+                }
+            },
+            _ => {},
+        }
+    }
+
+    // 波形メモリ (CPUからは再生していない側のバンクが見える)
+    fn wave_ram_index(&self, offset: usize) -> usize {
+        (1 - self.wave.bank) * 16 + (offset - 0x090)
+    }
+}
+
+impl IO for Apu {
+    fn read(&mut self, addr: u32) -> u8 {
+        let offset: usize = (addr & 0x3FF) as usize;
+        match offset {
+            0x082 => self.soundcnt_h as u8,
+            0x083 => (self.soundcnt_h >> 8) as u8,
+            // Bit[3:0]: 各チャネルの再生中フラグ
+            0x084 => {
+                let status: u8 = [self.square[0].enabled, self.square[1].enabled, self.wave.enabled, self.noise.enabled]
+                    .iter()
+                    .enumerate()
+                    .fold(0, |acc, (i, on)| acc | ((*on as u8) << i));
+                self.regs[0x12] as u8 | status
+            },
+            0x060..=0x089 => {
+                let index: usize = (offset - 0x060) / 2;
+                ((self.regs[index] & _PSG_REGS[index].0) >> ((offset & 1) * 8)) as u8
+            },
+            0x090..=0x09F => self.wave.ram[self.wave_ram_index(offset)],
+            // FIFOは書き込み専用
+            _ => 0,
+        }
+    }
+
+    fn write(&mut self, addr: u32, val: u8) {
+        let offset: usize = (addr & 0x3FF) as usize;
+        match offset {
+            0x082 => self.write_soundcnt_h((self.soundcnt_h & 0xFF00) | val as u16),
+            0x083 => self.write_soundcnt_h((self.soundcnt_h & 0x00FF) | ((val as u16) << 8)),
+            0x084 => self.write_soundcnt_x(val),
+            0x088 => self.regs[_REG_SOUNDBIAS] = ((self.regs[_REG_SOUNDBIAS] & 0xFF00) | val as u16) & 0xC3FE,
+            0x089 => self.regs[_REG_SOUNDBIAS] = ((self.regs[_REG_SOUNDBIAS] & 0x00FF) | ((val as u16) << 8)) & 0xC3FE,
+            // 停止中はPSGレジスタへ書き込み不可
+            0x060..=0x081 if self.is_enabled() => self.write_psg(offset, val),
+            0x090..=0x09F => {
+                let index: usize = self.wave_ram_index(offset);
+                self.wave.ram[index] = val;
+            },
+            0x0A0..=0x0A3 => self.fifo[0].push(val),
+            0x0A4..=0x0A7 => self.fifo[1].push(val),
+            _ => {},
+        }
+    }
+
+    // 現在時刻までPSGを進める
+    fn update(&mut self, _now: u64) {
+        let cycles: u64 = _now.saturating_sub(self.now);
+        self.now = _now;
+        if self.is_enabled() {
+            self.tick(cycles);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_direct_sound_fifo() {
+        let mut apu = Apu::new();
+        // FIFO A: Timer0, FIFO B: Timer1
+        apu.write(0x0400_0083, 0x40);
+        for i in 0..20u8 {
+            apu.write(0x0400_00A0 + (i as u32 & 3), i.wrapping_mul(0x10));
+        }
+        apu.write(0x0400_00A4, 0xFF);
+        assert_eq!(apu.fifo_len(0), 20);
+
+        apu.timer_overflow(0);
+        assert_eq!(apu.fifo_sample(0), 0);
+        apu.timer_overflow(0);
+        assert_eq!(apu.fifo_sample(0), 0x10);
+        assert_eq!(apu.fifo_sample(1), 0);
+        assert!(!apu.take_fifo_request(0));
+
+        apu.timer_overflow(0);
+        apu.timer_overflow(0);
+        assert!(apu.take_fifo_request(0));
+        assert!(!apu.take_fifo_request(0));
+
+        apu.timer_overflow(1);
+        assert_eq!(apu.fifo_sample(1), -1);
+        // リセット
+        apu.write(0x0400_0083, 0x48);
+        assert_eq!(apu.fifo_len(0), 0);
+        assert_eq!(apu.read(0x0400_0083), 0x40);
+    }
+
+    fn write16(apu: &mut Apu, addr: u32, val: u16) {
+        apu.write(addr, val as u8);
+        apu.write(addr + 1, (val >> 8) as u8);
+    }
+
+    // 停止中はPSGレジスタへ書き込めない
+    fn enabled_apu() -> Apu {
+        let mut apu = Apu::new();
+        write16(&mut apu, 0x0400_0060, 0x0077);
+        assert_eq!(apu.read(0x0400_0060), 0);
+        apu.write(0x0400_0084, 0x80);
+        apu
+    }
+
+    #[test]
+    fn test_square_channel() {
+        let mut apu = enabled_apu();
+        // Sound 2: デューティ50%, 音量15, 長さ 64-62=2, freq=2047 (1ステップ16サイクル)
+        write16(&mut apu, 0x0400_0068, 0xF0BE);
+        write16(&mut apu, 0x0400_006C, 0xC7FF);
+        assert_eq!(apu.read(0x0400_0084), 0x82);
+        assert_eq!(apu.read(0x0400_0069), 0xF0);
+        assert_eq!(apu.read(0x0400_006D), 0x40);
+        // デューティ位置 0:High, 1~4:Low, 5~7:High
+        assert_eq!(apu.psg_output()[1], 15);
+        apu.update(16);
+        assert_eq!(apu.psg_output()[1], 0);
+        apu.update(16 * 5);
+        assert_eq!(apu.psg_output()[1], 15);
+        // 長さカウンタ (256Hz×2で停止)
+        apu.update(_FRAME_SEQUENCER_CYCLES * 3);
+        assert_eq!(apu.read(0x0400_0084), 0x80);
+        assert_eq!(apu.psg_output()[1], 0);
+
+        // Sound 1: エンベロープ (初期音量2, 減少, ステップ1)
+        write16(&mut apu, 0x0400_0062, 0x2180);
+        write16(&mut apu, 0x0400_0064, 0x8700);
+        let start: u64 = apu.now;
+        apu.update(start + _FRAME_SEQUENCER_CYCLES * 8);
+        assert_eq!(apu.square[0].envelope.volume, 1);
+        apu.update(start + _FRAME_SEQUENCER_CYCLES * 16);
+        assert_eq!(apu.square[0].envelope.volume, 0);
+        assert!(apu.square[0].enabled);
+    }
+
+    #[test]
+    fn test_sweep() {
+        let mut apu = enabled_apu();
+        // 周期1, 増加, シフト1: 0x400 → 0x600 → オーバーフローで停止
+        write16(&mut apu, 0x0400_0060, 0x0011);
+        write16(&mut apu, 0x0400_0062, 0xF000);
+        write16(&mut apu, 0x0400_0064, 0x8400);
+        assert!(apu.square[0].enabled);
+        apu.update(_FRAME_SEQUENCER_CYCLES * 3);
+        assert_eq!(apu.square[0].freq, 0x600);
+        assert!(!apu.square[0].enabled);
+    }
+
+    #[test]
+    fn test_wave_channel() {
+        let mut apu = enabled_apu();
+        // バンク1を再生中はCPUからバンク0へ書き込む
+        apu.write(0x0400_0070, 0x40);
+        for i in 0..16 {
+            apu.write(0x0400_0090 + i, 0x1F);
+        }
+        assert_eq!(apu.wave.ram[0], 0x1F);
+        assert_eq!(apu.wave.ram[16], 0);
+        // バンク0を再生 (CPUはバンク1が見える)
+        apu.write(0x0400_0070, 0x00);
+        for i in 0..16 {
+            apu.write(0x0400_0090 + i, 0xA5);
+        }
+        apu.write(0x0400_0070, 0x80);
+        assert_eq!(apu.read(0x0400_0090), 0xA5);
+        // 音量100%, freq=2047 (1サンプル8サイクル)
+        write16(&mut apu, 0x0400_0072, 0x2000);
+        write16(&mut apu, 0x0400_0074, 0x87FF);
+        assert_eq!(apu.psg_output()[2], 0x1);
+        apu.update(8);
+        assert_eq!(apu.psg_output()[2], 0xF);
+        // 音量50%
+        write16(&mut apu, 0x0400_0072, 0x4000);
+        assert_eq!(apu.psg_output()[2], 0x7);
+        // DAC停止
+        apu.write(0x0400_0070, 0x00);
+        assert_eq!(apu.read(0x0400_0084), 0x80);
+    }
+
+    #[test]
+    fn test_noise_channel() {
+        let mut apu = enabled_apu();
+        // 音量15, 7bit LFSR, 分周比0 (32サイクル毎)
+        write16(&mut apu, 0x0400_0078, 0xF000);
+        write16(&mut apu, 0x0400_007C, 0x8008);
+        assert_eq!(apu.noise.lfsr, 0x7F);
+        apu.update(32);
+        assert_eq!(apu.noise.lfsr, 0x3F);
+        assert_eq!(apu.psg_output()[3], 0);
+        // 7bit LFSRは127ステップで1周
+        apu.update(32 * 128);
+        assert_eq!(apu.noise.lfsr, 0x3F);
+    }
+
+    #[test]
+    fn test_mixer() {
+        let mut apu = enabled_apu();
+        // Sound 2 (音量15, デューティ75%で位置1はHigh) を左のみ, 音量8, PSG比率100%
+        write16(&mut apu, 0x0400_0068, 0xF0C0);
+        write16(&mut apu, 0x0400_006C, 0x8000);
+        apu.update(16 * 2048);
+        assert_eq!(apu.psg_output()[1], 15);
+        write16(&mut apu, 0x0400_0080, 0x2077);
+        apu.write(0x0400_0082, 0x02);
+        apu.push_sample();
+        // FIFO A (100%) を右のみ
+        apu.write(0x0400_00A0, 0x10);
+        apu.write(0x0400_0082, 0x06);
+        apu.write(0x0400_0083, 0x01);
+        apu.timer_overflow(0);
+        apu.push_sample();
+        assert_eq!(apu.take_samples(), vec![(120 << 6, 0), (120 << 6, 64 << 6)]);
+        assert!(apu.take_samples().is_empty());
+
+        // 停止するとPSGレジスタをリセット
+        apu.write(0x0400_0084, 0x00);
+        assert_eq!(apu.read(0x0400_0081), 0);
+        assert_eq!(apu.read(0x0400_0084), 0);
+        apu.push_sample();
+        assert_eq!(apu.take_samples(), vec![(0, 0)]);
+        assert_eq!(apu.read(0x0400_0089), 0x02);
+    }
+}
@@ -1,12 +1,844 @@
 use cpu::*;
+use barrel_shifter::*;
+use bus::MemoryAccess;
+use once_cell::sync::Lazy;
 
-// TODO ARM7TDMI ARM命令のデコード
-#[allow(dead_code)]
-pub fn arm_op_decode(_cpu: &mut CPU, _op: u32) {
-    todo!("Plz! ARM OP Decode Func Impl!")
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ArmFormat {
+    DataProcessing,              // Data processing
+    PSRTransferMRS,              // PSR transfer (MRS)
+    PSRTransferMSR,              // PSR transfer (MSR)
+    Multiply,                    // Multiply and multiply-accumulate
+    MultiplyLong,                // Multiply long and multiply-accumulate long
+    SingleDataSwap,              // Single data swap
+    BranchExchange,              // Branch and exchange
+    HalfwordDataTransfer,        // Halfword and signed data transfer
+    SingleDataTransfer,          // Single data transfer
+    Undefined,                   // Undefined
+    BlockDataTransfer,           // Block data transfer
+    Branch,                      // Branch and branch with link
+    CoprocessorDataTransfer,     // Coprocessor data transfer
+    CoprocessorDataOperation,    // Coprocessor data operation
+    CoprocessorRegisterTransfer, // Coprocessor register transfer
+    SoftwareInterrupt,           // Software interrupt
 }
 
-// TODO ARM7TDMI ARM命令の実行
-pub fn arm_op_exec(_cpu: &mut CPU, _op: u32) {
-    todo!("Plz! ARM OP Exec Func Impl!")
-}
\ No newline at end of file
+#[derive(Debug, PartialEq)]
+pub enum ArmInstruction {
+    // データ処理
+    DataProcessing(u8, u8, u8, u8, u8, u16), // |I|OpCode|S|Rn|Rd|Operand2|
+
+    // PSR転送 (PSR -> レジスタ)
+    PSRTransferMRS(u8, u8), // |Ps|Rd|
+
+    // PSR転送 (レジスタ/即値 -> PSR)
+    PSRTransferMSR(u8, u8, u8, u16), // |I|Pd|Field|Source Operand|
+
+    // 乗算/積和
+    Multiply(u8, u8, u8, u8, u8, u8), // |A|S|Rd|Rn|Rs|Rm|
+
+    // 64bit乗算/積和
+    MultiplyLong(u8, u8, u8, u8, u8, u8, u8), // |U|A|S|RdHi|RdLo|Rs|Rm|
+
+    // スワップ
+    SingleDataSwap(u8, u8, u8, u8), // |B|Rn|Rd|Rm|
+
+    // 分岐交換
+    BranchExchange(u8), // |Rn|
+
+    // ハーフワード/符号付きデータ転送
+    HalfwordDataTransfer(u8, u8, u8, u8, u8, u8, u8, u8, u8), // |P|U|I|W|L|Rn|Rd|SH|Offset(Imm8/Rm)|
+
+    // シングルデータ転送
+    SingleDataTransfer(u8, u8, u8, u8, u8, u8, u8, u8, u16), // |I|P|U|B|W|L|Rn|Rd|Offset12|
+
+    // 未定義命令
+    Undefined(u32), // |Instruction|
+
+    // ブロックデータ転送
+    BlockDataTransfer(u8, u8, u8, u8, u8, u8, u16), // |P|U|S|W|L|Rn|Rlist|
+
+    // 分岐/リンク付き分岐
+    Branch(u8, i32), // |L|Offset24(符号拡張済み)|
+
+    // コプロセッサデータ転送
+    CoprocessorDataTransfer(u8, u8, u8, u8, u8, u8, u8, u8, u8), // |P|U|N|W|L|Rn|CRd|CP#|Offset8|
+
+    // コプロセッサデータ操作
+    CoprocessorDataOperation(u8, u8, u8, u8, u8, u8), // |CP Opc|CRn|CRd|CP#|CP|CRm|
+
+    // コプロセッサレジスタ転送
+    CoprocessorRegisterTransfer(u8, u8, u8, u8, u8, u8, u8), // |CP Opc|L|CRn|Rd|CP#|CP|CRm|
+
+    // ソフトウェア割り込み
+    SoftwareInterrupt(u32), // |Comment24|
+}
+
+fn bit(op: u32, n: u32) -> u8 {
+    ((op >> n) & 1) as u8
+}
+
+fn decode_data_processing(op: u32) -> ArmInstruction {
+    let i: u8         = bit(op, 25);
+    let opcode: u8    = ((op >> 21) & 0x0F) as u8;
+    let s: u8         = bit(op, 20);
+    let rn: u8        = ((op >> 16) & 0x0F) as u8;
+    let rd: u8        = ((op >> 12) & 0x0F) as u8;
+    let operand2: u16 = (op & 0x0FFF) as u16;
+    ArmInstruction::DataProcessing(i, opcode, s, rn, rd, operand2)
+}
+
+fn decode_psr_transfer_mrs(op: u32) -> ArmInstruction {
+    let ps: u8 = bit(op, 22);
+    let rd: u8 = ((op >> 12) & 0x0F) as u8;
+    ArmInstruction::PSRTransferMRS(ps, rd)
+}
+
+fn decode_psr_transfer_msr(op: u32) -> ArmInstruction {
+    let i: u8        = bit(op, 25);
+    let pd: u8       = bit(op, 22);
+    let field: u8    = ((op >> 16) & 0x0F) as u8;
+    let source: u16  = (op & 0x0FFF) as u16;
+    ArmInstruction::PSRTransferMSR(i, pd, field, source)
+}
+
+fn decode_multiply(op: u32) -> ArmInstruction {
+    let a: u8  = bit(op, 21);
+    let s: u8  = bit(op, 20);
+    let rd: u8 = ((op >> 16) & 0x0F) as u8;
+    let rn: u8 = ((op >> 12) & 0x0F) as u8;
+    let rs: u8 = ((op >> 8) & 0x0F) as u8;
+    let rm: u8 = (op & 0x0F) as u8;
+    ArmInstruction::Multiply(a, s, rd, rn, rs, rm)
+}
+
+fn decode_multiply_long(op: u32) -> ArmInstruction {
+    let u: u8    = bit(op, 22);
+    let a: u8    = bit(op, 21);
+    let s: u8    = bit(op, 20);
+    let rdhi: u8 = ((op >> 16) & 0x0F) as u8;
+    let rdlo: u8 = ((op >> 12) & 0x0F) as u8;
+    let rs: u8   = ((op >> 8) & 0x0F) as u8;
+    let rm: u8   = (op & 0x0F) as u8;
+    ArmInstruction::MultiplyLong(u, a, s, rdhi, rdlo, rs, rm)
+}
+
+fn decode_single_data_swap(op: u32) -> ArmInstruction {
+    let b: u8  = bit(op, 22);
+    let rn: u8 = ((op >> 16) & 0x0F) as u8;
+    let rd: u8 = ((op >> 12) & 0x0F) as u8;
+    let rm: u8 = (op & 0x0F) as u8;
+    ArmInstruction::SingleDataSwap(b, rn, rd, rm)
+}
+
+fn decode_branch_exchange(op: u32) -> ArmInstruction {
+    let rn: u8 = (op & 0x0F) as u8;
+    ArmInstruction::BranchExchange(rn)
+}
+
+fn decode_halfword_data_transfer(op: u32) -> ArmInstruction {
+    let p: u8  = bit(op, 24);
+    let u: u8  = bit(op, 23);
+    let i: u8  = bit(op, 22);
+    let w: u8  = bit(op, 21);
+    let l: u8  = bit(op, 20);
+    let rn: u8 = ((op >> 16) & 0x0F) as u8;
+    let rd: u8 = ((op >> 12) & 0x0F) as u8;
+    let sh: u8 = ((op >> 5) & 0x03) as u8;
+    // I=1: Offset = Hi(Bit[11:8]) << 4 | Lo(Bit[3:0]), I=0: Rm
+    let offset: u8 = if i != 0 {
+        ((((op >> 8) & 0x0F) << 4) | (op & 0x0F)) as u8
+    } else {
+        (op & 0x0F) as u8
+    };
+    ArmInstruction::HalfwordDataTransfer(p, u, i, w, l, rn, rd, sh, offset)
+}
+
+fn decode_single_data_transfer(op: u32) -> ArmInstruction {
+    let i: u8        = bit(op, 25);
+    let p: u8        = bit(op, 24);
+    let u: u8        = bit(op, 23);
+    let b: u8        = bit(op, 22);
+    let w: u8        = bit(op, 21);
+    let l: u8        = bit(op, 20);
+    let rn: u8       = ((op >> 16) & 0x0F) as u8;
+    let rd: u8       = ((op >> 12) & 0x0F) as u8;
+    let offset: u16  = (op & 0x0FFF) as u16;
+    ArmInstruction::SingleDataTransfer(i, p, u, b, w, l, rn, rd, offset)
+}
+
+fn decode_undefined(op: u32) -> ArmInstruction {
+    ArmInstruction::Undefined(op)
+}
+
+fn decode_block_data_transfer(op: u32) -> ArmInstruction {
+    let p: u8       = bit(op, 24);
+    let u: u8       = bit(op, 23);
+    let s: u8       = bit(op, 22);
+    let w: u8       = bit(op, 21);
+    let l: u8       = bit(op, 20);
+    let rn: u8      = ((op >> 16) & 0x0F) as u8;
+    let rlist: u16  = (op & 0xFFFF) as u16;
+    ArmInstruction::BlockDataTransfer(p, u, s, w, l, rn, rlist)
+}
+
+fn decode_branch(op: u32) -> ArmInstruction {
+    let l: u8 = bit(op, 24);
+    // Offset24を符号拡張
+    let offset: i32 = ((op << 8) as i32) >> 8;
+    ArmInstruction::Branch(l, offset)
+}
+
+fn decode_coprocessor_data_transfer(op: u32) -> ArmInstruction {
+    let p: u8      = bit(op, 24);
+    let u: u8      = bit(op, 23);
+    let n: u8      = bit(op, 22);
+    let w: u8      = bit(op, 21);
+    let l: u8      = bit(op, 20);
+    let rn: u8     = ((op >> 16) & 0x0F) as u8;
+    let crd: u8    = ((op >> 12) & 0x0F) as u8;
+    let cp_num: u8 = ((op >> 8) & 0x0F) as u8;
+    let offset: u8 = (op & 0xFF) as u8;
+    ArmInstruction::CoprocessorDataTransfer(p, u, n, w, l, rn, crd, cp_num, offset)
+}
+
+fn decode_coprocessor_data_operation(op: u32) -> ArmInstruction {
+    let cp_opc: u8 = ((op >> 20) & 0x0F) as u8;
+    let crn: u8    = ((op >> 16) & 0x0F) as u8;
+    let crd: u8    = ((op >> 12) & 0x0F) as u8;
+    let cp_num: u8 = ((op >> 8) & 0x0F) as u8;
+    let cp: u8     = ((op >> 5) & 0x07) as u8;
+    let crm: u8    = (op & 0x0F) as u8;
+    ArmInstruction::CoprocessorDataOperation(cp_opc, crn, crd, cp_num, cp, crm)
+}
+
+fn decode_coprocessor_register_transfer(op: u32) -> ArmInstruction {
+    let cp_opc: u8 = ((op >> 21) & 0x07) as u8;
+    let l: u8      = bit(op, 20);
+    let crn: u8    = ((op >> 16) & 0x0F) as u8;
+    let rd: u8     = ((op >> 12) & 0x0F) as u8;
+    let cp_num: u8 = ((op >> 8) & 0x0F) as u8;
+    let cp: u8     = ((op >> 5) & 0x07) as u8;
+    let crm: u8    = (op & 0x0F) as u8;
+    ArmInstruction::CoprocessorRegisterTransfer(cp_opc, l, crn, rd, cp_num, cp, crm)
+}
+
+fn decode_software_interrupt(op: u32) -> ArmInstruction {
+    ArmInstruction::SoftwareInterrupt(op & 0x00FF_FFFF)
+}
+
+// ARM命令のフォーマット判別パターン (Mask, Value, Format)
+// データ処理と乗算/スワップ/ハーフワード転送/PSR転送はエンコード空間が重なるので
+// 固定ビットの多い形式から順に並べる
+const ARM_FORMAT_PATTERNS: [(u32, u32, ArmFormat); 16] = [
+    (0x0FFF_FFF0, 0x012F_FF10, ArmFormat::BranchExchange),
+    (0x0FC0_00F0, 0x0000_0090, ArmFormat::Multiply),
+    (0x0F80_00F0, 0x0080_0090, ArmFormat::MultiplyLong),
+    (0x0FB0_0FF0, 0x0100_0090, ArmFormat::SingleDataSwap),
+    (0x0E00_0090, 0x0000_0090, ArmFormat::HalfwordDataTransfer),
+    (0x0FBF_0FFF, 0x010F_0000, ArmFormat::PSRTransferMRS),
+    (0x0DB0_F000, 0x0120_F000, ArmFormat::PSRTransferMSR),
+    (0x0C00_0000, 0x0000_0000, ArmFormat::DataProcessing),
+    (0x0E00_0010, 0x0600_0010, ArmFormat::Undefined),
+    (0x0C00_0000, 0x0400_0000, ArmFormat::SingleDataTransfer),
+    (0x0E00_0000, 0x0800_0000, ArmFormat::BlockDataTransfer),
+    (0x0E00_0000, 0x0A00_0000, ArmFormat::Branch),
+    (0x0E00_0000, 0x0C00_0000, ArmFormat::CoprocessorDataTransfer),
+    (0x0F00_0010, 0x0E00_0000, ArmFormat::CoprocessorDataOperation),
+    (0x0F00_0010, 0x0E00_0010, ArmFormat::CoprocessorRegisterTransfer),
+    (0x0F00_0000, 0x0F00_0000, ArmFormat::SoftwareInterrupt),
+];
+
+// ARM命令のフォーマット判別テーブルのインデックス (Bit[27:20], Bit[7:4])
+fn arm_lut_index(op: u32) -> usize {
+    (((op >> 16) & 0xFF0) | ((op >> 4) & 0x00F)) as usize
+}
+
+// ARM命令のフォーマット判別 (テーブルのインデックスに一致するパターン)
+// ※インデックス外のビット (Rn/Rd/Rs等) はパターンから除外して判定する
+fn arm_lut_format(index: usize) -> Option<ArmFormat> {
+    let op: u32 = (((index as u32) & 0xFF0) << 16) | (((index as u32) & 0x00F) << 4);
+    let index_mask: u32 = 0x0FF0_00F0;
+    ARM_FORMAT_PATTERNS.iter()
+        .find(|(mask, value, _)| (op & mask & index_mask) == (value & index_mask))
+        .map(|(_, _, format)| *format)
+}
+
+// ARM命令のフォーマット判別テーブル (どのパターンにも一致しない場合は未定義命令)
+static ARM_FORMAT_LUT: Lazy<[ArmFormat; 4096]> = Lazy::new(|| {
+    let mut lut: [ArmFormat; 4096] = [ArmFormat::Undefined; 4096];
+    for (i, entry) in lut.iter_mut().enumerate() {
+        *entry = arm_lut_format(i).unwrap_or(ArmFormat::Undefined);
+    }
+    lut
+});
+
+pub fn arm_format_decode(op: u32) -> (ArmFormat, ArmInstruction) {
+    let format: ArmFormat = ARM_FORMAT_LUT[arm_lut_index(op)];
+
+    // ARM命令のフォーマットに応じて命令をデコード
+    match format {
+        ArmFormat::DataProcessing              => (format, decode_data_processing(op)),
+        ArmFormat::PSRTransferMRS              => (format, decode_psr_transfer_mrs(op)),
+        ArmFormat::PSRTransferMSR              => (format, decode_psr_transfer_msr(op)),
+        ArmFormat::Multiply                    => (format, decode_multiply(op)),
+        ArmFormat::MultiplyLong                => (format, decode_multiply_long(op)),
+        ArmFormat::SingleDataSwap              => (format, decode_single_data_swap(op)),
+        ArmFormat::BranchExchange              => (format, decode_branch_exchange(op)),
+        ArmFormat::HalfwordDataTransfer        => (format, decode_halfword_data_transfer(op)),
+        ArmFormat::SingleDataTransfer          => (format, decode_single_data_transfer(op)),
+        ArmFormat::Undefined                   => (format, decode_undefined(op)),
+        ArmFormat::BlockDataTransfer           => (format, decode_block_data_transfer(op)),
+        ArmFormat::Branch                      => (format, decode_branch(op)),
+        ArmFormat::CoprocessorDataTransfer     => (format, decode_coprocessor_data_transfer(op)),
+        ArmFormat::CoprocessorDataOperation    => (format, decode_coprocessor_data_operation(op)),
+        ArmFormat::CoprocessorRegisterTransfer => (format, decode_coprocessor_register_transfer(op)),
+        ArmFormat::SoftwareInterrupt           => (format, decode_software_interrupt(op)),
+    }
+}
+
+// 即値シフトレジスタオフセット (LDR/STR)
+fn shift_offset(_cpu: &CPU, offset: u16) -> u32 {
+    let rm: u32 = _cpu.reg.reg((offset & 0x0F) as u8);
+    let shift: ShiftType = ShiftType::from_bits((offset >> 5) as u32);
+    let amount: u32 = ((offset >> 7) & 0x1F) as u32;
+    shift_imm(shift, rm, amount, _cpu.reg.cpsr.contains(PSR::C)).0
+}
+
+// データ処理のOperand2 (戻り値は (値, シフタキャリーアウト, レジスタ指定シフトか))
+fn operand2(_cpu: &CPU, i: u8, operand2: u16) -> (u32, bool, bool) {
+    let carry: bool = _cpu.reg.cpsr.contains(PSR::C);
+    if i != 0 {
+        // 即値: Imm8 ROR (Rotate*2)
+        let (val, c) = rotate_imm((operand2 & 0xFF) as u32, ((operand2 >> 8) & 0x0F) as u32, carry);
+        return (val, c, false);
+    }
+
+    let rm: u8 = (operand2 & 0x0F) as u8;
+    let shift: ShiftType = ShiftType::from_bits((operand2 >> 5) as u32);
+    if (operand2 & 0x10) != 0 {
+        // レジスタ指定シフト: 1I余分に掛かるのでR15は実行中命令+12
+        let rs: u32 = _cpu.reg.reg(((operand2 >> 8) & 0x0F) as u8);
+        let rm_val: u32 = if rm == 15 { _cpu.reg.reg(rm).wrapping_add(4) } else { _cpu.reg.reg(rm) };
+        let (val, c) = shift_reg(shift, rm_val, rs, carry);
+        (val, c, true)
+    } else {
+        let amount: u32 = ((operand2 >> 7) & 0x1F) as u32;
+        let (val, c) = shift_imm(shift, _cpu.reg.reg(rm), amount, carry);
+        (val, c, false)
+    }
+}
+
+// 乗算の内部サイクル数m (乗数Rsの上位ビットで早期終了)
+pub fn multiply_cycles(rs: u32, signed: bool) -> u32 {
+    let mask_hi = |shift: u32| -> bool {
+        let hi = rs >> shift;
+        hi == 0 || (signed && hi == (0xFFFF_FFFF >> shift))
+    };
+    if mask_hi(8) {
+        1
+    } else if mask_hi(16) {
+        2
+    } else if mask_hi(24) {
+        3
+    } else {
+        4
+    }
+}
+
+// LDR Rd, [addr] (非アライメント時は回転)
+fn load_word_rotated(_cpu: &mut CPU, addr: u32) -> u32 {
+    let val: u32 = _cpu.read_word(addr, MemoryAccess::NonSeq);
+    val.rotate_right((addr & 3) * 8)
+}
+
+fn exec_data_processing(_cpu: &mut CPU, _op: ArmInstruction) {
+    if let ArmInstruction::DataProcessing(i, opcode, s, rn, rd, operand2_bits) = _op {
+        trace!("DataProcessing - I: {}, OpCode: {}, S: {}, Rn: {}, Rd: {}, Operand2: {}", i, opcode, s, rn, rd, operand2_bits);
+        let (op2, shifter_carry, is_reg_shift) = operand2(_cpu, i, operand2_bits);
+        let rn_val: u32 = if rn == 15 && is_reg_shift { _cpu.reg.reg(rn).wrapping_add(4) } else { _cpu.reg.reg(rn) };
+        let carry: bool = _cpu.reg.cpsr.contains(PSR::C);
+        // Rd=R15かつS=1の場合はフラグを更新せず、SPSRをCPSRへ復帰する
+        let set_flags: bool = s != 0 && rd != 15;
+
+        let ret: Option<u32> = match opcode {
+            // AND
+            0x0 => { let ret = rn_val & op2; if set_flags { _cpu.psr_logic_update(ret, shifter_carry); } Some(ret) },
+            // EOR
+            0x1 => { let ret = rn_val ^ op2; if set_flags { _cpu.psr_logic_update(ret, shifter_carry); } Some(ret) },
+            // SUB
+            0x2 => Some(_cpu.alu_sub(rn_val, op2, true, set_flags)),
+            // RSB
+            0x3 => Some(_cpu.alu_sub(op2, rn_val, true, set_flags)),
+            // ADD
+            0x4 => Some(_cpu.alu_add(rn_val, op2, false, set_flags)),
+            // ADC
+            0x5 => Some(_cpu.alu_add(rn_val, op2, carry, set_flags)),
+            // SBC
+            0x6 => Some(_cpu.alu_sub(rn_val, op2, carry, set_flags)),
+            // RSC
+            0x7 => Some(_cpu.alu_sub(op2, rn_val, carry, set_flags)),
+            // TST
+            0x8 => { let ret = rn_val & op2; if set_flags { _cpu.psr_logic_update(ret, shifter_carry); } None },
+            // TEQ
+            0x9 => { let ret = rn_val ^ op2; if set_flags { _cpu.psr_logic_update(ret, shifter_carry); } None },
+            // CMP
+            0xA => { _cpu.alu_sub(rn_val, op2, true, set_flags); None },
+            // CMN
+            0xB => { _cpu.alu_add(rn_val, op2, false, set_flags); None },
+            // ORR
+            0xC => { let ret = rn_val | op2; if set_flags { _cpu.psr_logic_update(ret, shifter_carry); } Some(ret) },
+            // MOV
+            0xD => { let ret = op2; if set_flags { _cpu.psr_logic_update(ret, shifter_carry); } Some(ret) },
+            // BIC
+            0xE => { let ret = rn_val & !op2; if set_flags { _cpu.psr_logic_update(ret, shifter_carry); } Some(ret) },
+            // MVN
+            _ => { let ret = !op2; if set_flags { _cpu.psr_logic_update(ret, shifter_carry); } Some(ret) },
+        };
+
+        if rd == 15 && s != 0 {
+            _cpu.reg.restore_cpsr();
+        }
+        if let Some(ret) = ret {
+            // Rd=R15 はパイプラインフラッシュ (Cycle += 1S+1N)
+            _cpu.set_reg(rd, ret);
+        }
+
+        // Cycle += 1S (レジスタ指定シフト: +1I)
+        if is_reg_shift {
+            _cpu.idle(1);
+        }
+    }
+}
+
+fn exec_psr_transfer_mrs(_cpu: &mut CPU, _op: ArmInstruction) {
+    if let ArmInstruction::PSRTransferMRS(ps, rd) = _op {
+        trace!("PSRTransferMRS - Ps: {}, Rd: {}", ps, rd);
+        let psr: u32 = if ps != 0 { _cpu.reg.spsr().bits() } else { _cpu.reg.cpsr.bits() };
+        _cpu.set_reg(rd, psr); // Cycle += 1S
+    }
+}
+
+fn exec_psr_transfer_msr(_cpu: &mut CPU, _op: ArmInstruction) {
+    if let ArmInstruction::PSRTransferMSR(i, pd, field, source) = _op {
+        trace!("PSRTransferMSR - I: {}, Pd: {}, Field: {}, Source: {}", i, pd, field, source);
+        let val: u32 = if i != 0 {
+            rotate_imm((source & 0xFF) as u32, ((source >> 8) & 0x0F) as u32, false).0
+        } else {
+            _cpu.reg.reg((source & 0x0F) as u8)
+        };
+
+        // フィールドマスク (Bit19:f, Bit18:s, Bit17:x, Bit16:c)
+        let mut mask: u32 = 0;
+        for n in 0..4 {
+            if (field & (1 << n)) != 0 {
+                mask |= 0xFF << (n * 8);
+            }
+        }
+        if pd != 0 {
+            let psr: u32 = (_cpu.reg.spsr().bits() & !mask) | (val & mask);
+            _cpu.reg.set_spsr(PSR::from_bits_retain(psr));
+        } else {
+            // ユーザモードではフラグフィールドのみ書き換え可能
+            if _cpu.reg.mode().bits() == PSR::MODE_USER.bits() {
+                mask &= 0xFF00_0000;
+            }
+            // Tビット(ステート)はMSRでは変更できない (BXのみ)
+            mask &= !PSR::T.bits();
+            let psr: u32 = (_cpu.reg.cpsr.bits() & !mask) | (val & mask);
+            _cpu.reg.set_cpsr(PSR::from_bits_retain(psr));
+        } // Cycle += 1S
+    }
+}
+
+fn exec_multiply(_cpu: &mut CPU, _op: ArmInstruction) {
+    if let ArmInstruction::Multiply(a, s, rd, rn, rs, rm) = _op {
+        trace!("Multiply - A: {}, S: {}, Rd: {}, Rn: {}, Rs: {}, Rm: {}", a, s, rd, rn, rs, rm);
+        let rs_val: u32 = _cpu.reg.reg(rs);
+        let mut ret: u32 = _cpu.reg.reg(rm).wrapping_mul(rs_val);
+        let mut cycle: u32 = multiply_cycles(rs_val, true);
+        // MLA
+        if a != 0 {
+            ret = ret.wrapping_add(_cpu.reg.reg(rn));
+            cycle += 1;
+        }
+        _cpu.set_reg(rd, ret);
+        // S: N,Zのみ更新 (Cは破壊されるが意味を持たない)
+        if s != 0 {
+            let c = _cpu.reg.cpsr.contains(PSR::C);
+            let v = _cpu.reg.cpsr.contains(PSR::V);
+            _cpu.psr_op_update(ret, c, v);
+        }
+        _cpu.idle(cycle); // Cycle += 1S+mI (MLA: 1S+(m+1)I)
+    }
+}
+
+fn exec_multiply_long(_cpu: &mut CPU, _op: ArmInstruction) {
+    if let ArmInstruction::MultiplyLong(u, a, s, rdhi, rdlo, rs, rm) = _op {
+        trace!("MultiplyLong - U: {}, A: {}, S: {}, RdHi: {}, RdLo: {}, Rs: {}, Rm: {}", u, a, s, rdhi, rdlo, rs, rm);
+        let rs_val: u32 = _cpu.reg.reg(rs);
+        let rm_val: u32 = _cpu.reg.reg(rm);
+        // U=1: 符号付き(SMULL/SMLAL), U=0: 符号なし(UMULL/UMLAL)
+        let mut ret: u64 = if u != 0 {
+            ((rm_val as i32 as i64) * (rs_val as i32 as i64)) as u64
+        } else {
+            (rm_val as u64) * (rs_val as u64)
+        };
+        let mut cycle: u32 = multiply_cycles(rs_val, u != 0) + 1;
+        if a != 0 {
+            let acc: u64 = ((_cpu.reg.reg(rdhi) as u64) << 32) | (_cpu.reg.reg(rdlo) as u64);
+            ret = ret.wrapping_add(acc);
+            cycle += 1;
+        }
+        _cpu.set_reg(rdlo, ret as u32);
+        _cpu.set_reg(rdhi, (ret >> 32) as u32);
+        if s != 0 {
+            let c = _cpu.reg.cpsr.contains(PSR::C);
+            let v = _cpu.reg.cpsr.contains(PSR::V);
+            _cpu.psr_op_update((ret >> 32) as u32, c, v);
+            if ret == 0 {
+                _cpu.reg.cpsr.insert(PSR::Z);
+            } else {
+                _cpu.reg.cpsr.remove(PSR::Z);
+            }
+        }
+        _cpu.idle(cycle); // Cycle += 1S+(m+1)I (UMLAL/SMLAL: 1S+(m+2)I)
+    }
+}
+
+fn exec_single_data_swap(_cpu: &mut CPU, _op: ArmInstruction) {
+    if let ArmInstruction::SingleDataSwap(b, rn, rd, rm) = _op {
+        trace!("SingleDataSwap - B: {}, Rn: {}, Rd: {}, Rm: {}", b, rn, rd, rm);
+        let addr: u32 = _cpu.reg.reg(rn);
+        let src: u32 = _cpu.reg.reg(rm);
+        if b != 0 {
+            // SWPB
+            let tmp: u8 = _cpu.read_byte(addr, MemoryAccess::NonSeq);
+            _cpu.write_byte(addr, src as u8, MemoryAccess::NonSeq);
+            _cpu.set_reg(rd, tmp as u32);
+        } else {
+            // SWP
+            let tmp: u32 = load_word_rotated(_cpu, addr);
+            _cpu.write_word(addr, src, MemoryAccess::NonSeq);
+            _cpu.set_reg(rd, tmp);
+        }
+        _cpu.idle(1); // Cycle += 1S+2N+1I
+    }
+}
+
+fn exec_branch_exchange(_cpu: &mut CPU, _op: ArmInstruction) {
+    if let ArmInstruction::BranchExchange(rn) = _op {
+        trace!("BranchExchange - Rn: {}", rn);
+        let addr: u32 = _cpu.reg.reg(rn);
+        // Bit0でThumb/ARM切り替え
+        if (addr & 1) != 0 {
+            _cpu.reg.cpsr.insert(PSR::T);
+        } else {
+            _cpu.reg.cpsr.remove(PSR::T);
+        }
+        _cpu.set_pc(addr); // Cycle += 2S+1N
+    }
+}
+
+fn exec_halfword_data_transfer(_cpu: &mut CPU, _op: ArmInstruction) {
+    if let ArmInstruction::HalfwordDataTransfer(p, u, i, w, l, rn, rd, sh, offset) = _op {
+        trace!("HalfwordDataTransfer - P: {}, U: {}, I: {}, W: {}, L: {}, Rn: {}, Rd: {}, SH: {}, Offset: {}", p, u, i, w, l, rn, rd, sh, offset);
+        let base: u32 = _cpu.reg.reg(rn);
+        let offset: u32 = if i != 0 { offset as u32 } else { _cpu.reg.reg(offset) };
+        let offset_addr: u32 = if u != 0 { base.wrapping_add(offset) } else { base.wrapping_sub(offset) };
+        let addr: u32 = if p != 0 { offset_addr } else { base };
+
+        if l != 0 {
+            let val: u32 = match sh {
+                // LDRH (非アライメント時は回転)
+                0b01 => (_cpu.read_hword(addr, MemoryAccess::NonSeq) as u32).rotate_right((addr & 1) * 8),
+                // LDRSB
+                0b10 => _cpu.read_byte(addr, MemoryAccess::NonSeq) as i8 as i32 as u32,
+                // LDRSH (非アライメント時はLDRSB相当)
+                _ => if (addr & 1) != 0 {
+                    _cpu.read_byte(addr, MemoryAccess::NonSeq) as i8 as i32 as u32
+                } else {
+                    _cpu.read_hword(addr, MemoryAccess::NonSeq) as i16 as i32 as u32
+                },
+            };
+            // ポストインデックスは常にライトバック
+            if p == 0 || w != 0 {
+                _cpu.set_reg(rn, offset_addr);
+            }
+            _cpu.set_reg(rd, val);
+            _cpu.idle(1); // Cycle += 1S+1N+1I
+        } else {
+            // STRH (ストアするR15は実行中命令+12)
+            let val: u32 = if rd == 15 { _cpu.reg.reg(rd).wrapping_add(4) } else { _cpu.reg.reg(rd) };
+            if sh == 0b01 {
+                _cpu.write_hword(addr, val as u16, MemoryAccess::NonSeq);
+            }
+            if p == 0 || w != 0 {
+                _cpu.set_reg(rn, offset_addr);
+            }
+            // Cycle += 2N
+        }
+    }
+}
+
+fn exec_single_data_transfer(_cpu: &mut CPU, _op: ArmInstruction) {
+    if let ArmInstruction::SingleDataTransfer(i, p, u, b, w, l, rn, rd, offset) = _op {
+        trace!("SingleDataTransfer - I: {}, P: {}, U: {}, B: {}, W: {}, L: {}, Rn: {}, Rd: {}, Offset: {}", i, p, u, b, w, l, rn, rd, offset);
+        let base: u32 = _cpu.reg.reg(rn);
+        // I=1: シフトレジスタオフセット, I=0: 即値オフセット
+        let offset: u32 = if i != 0 { shift_offset(_cpu, offset) } else { offset as u32 };
+        let offset_addr: u32 = if u != 0 { base.wrapping_add(offset) } else { base.wrapping_sub(offset) };
+        let addr: u32 = if p != 0 { offset_addr } else { base };
+
+        if l != 0 {
+            // LDR/LDRB
+            let val: u32 = if b != 0 {
+                _cpu.read_byte(addr, MemoryAccess::NonSeq) as u32
+            } else {
+                load_word_rotated(_cpu, addr)
+            };
+            if p == 0 || w != 0 {
+                _cpu.set_reg(rn, offset_addr);
+            }
+            _cpu.set_reg(rd, val);
+            _cpu.idle(1); // Cycle += 1S+1N+1I (Rd=R15: 2S+2N+1I)
+        } else {
+            // STR/STRB (ストアするR15は実行中命令+12)
+            let val: u32 = if rd == 15 { _cpu.reg.reg(rd).wrapping_add(4) } else { _cpu.reg.reg(rd) };
+            if b != 0 {
+                _cpu.write_byte(addr, val as u8, MemoryAccess::NonSeq);
+            } else {
+                _cpu.write_word(addr, val, MemoryAccess::NonSeq);
+            }
+            if p == 0 || w != 0 {
+                _cpu.set_reg(rn, offset_addr);
+            }
+            // Cycle += 2N
+        }
+    }
+}
+
+fn exec_undefined(_cpu: &mut CPU, _op: ArmInstruction) {
+    if let ArmInstruction::Undefined(op) = _op {
+        trace!("Undefined - Instruction: {:#010X}", op);
+        _cpu.raise_exception(Exception::Undefined); // Cycle += 2S+1I+1N
+        _cpu.idle(1);
+    }
+}
+
+fn exec_block_data_transfer(_cpu: &mut CPU, _op: ArmInstruction) {
+    if let ArmInstruction::BlockDataTransfer(p, u, s, w, l, rn, rlist) = _op {
+        trace!("BlockDataTransfer - P: {}, U: {}, S: {}, W: {}, L: {}, Rn: {}, Rlist: {:#06X}", p, u, s, w, l, rn, rlist);
+        let base: u32 = _cpu.reg.reg(rn);
+        // Rlistが空の場合はR15のみ転送し、ベースは0x40変化する
+        let (rlist, size): (u16, u32) = if rlist == 0 {
+            (1 << 15, 0x40)
+        } else {
+            (rlist, rlist.count_ones() * 4)
+        };
+
+        // 最下位レジスタが最下位アドレスになるように開始アドレスを求める
+        let (mut addr, wb_addr): (u32, u32) = match (p, u) {
+            (0, 1) => (base, base.wrapping_add(size)),                                    // IA
+            (1, 1) => (base.wrapping_add(4), base.wrapping_add(size)),                    // IB
+            (0, _) => (base.wrapping_sub(size).wrapping_add(4), base.wrapping_sub(size)), // DA
+            (_, _) => (base.wrapping_sub(size), base.wrapping_sub(size)),                 // DB
+        };
+
+        // S=1: R15を含むLDMはSPSR復帰、それ以外はユーザバンクを転送
+        let is_pc_load: bool = l != 0 && (rlist & (1 << 15)) != 0;
+        let is_user_bank: bool = s != 0 && !is_pc_load;
+
+        let first: u8 = rlist.trailing_zeros() as u8;
+        let mut pc: u32 = 0;
+        for n in 0..16u8 {
+            if (rlist & (1 << n)) == 0 {
+                continue;
+            }
+            // 先頭のみN、以降はS
+            let access: MemoryAccess = if n == first { MemoryAccess::NonSeq } else { MemoryAccess::Seq };
+            if l != 0 {
+                let val: u32 = _cpu.read_word(addr, access);
+                if is_user_bank {
+                    _cpu.reg.set_user_reg(n, val);
+                } else if n == 15 {
+                    // 分岐はSPSR復帰後のステートで行う
+                    pc = val;
+                } else {
+                    _cpu.set_reg(n, val);
+                }
+            } else {
+                // ベースが先頭なら旧ベース、それ以外は更新後のベースをストア
+                let val: u32 = if n == rn && n != first && w != 0 {
+                    wb_addr
+                } else if n == 15 {
+                    _cpu.reg.reg(n).wrapping_add(4)
+                } else if is_user_bank {
+                    _cpu.reg.user_reg(n)
+                } else {
+                    _cpu.reg.reg(n)
+                };
+                _cpu.write_word(addr, val, access);
+            }
+            addr = addr.wrapping_add(4);
+        }
+
+        // LDMでベースがRlistに含まれる場合はライトバックしない
+        if w != 0 && !(l != 0 && (rlist & (1 << rn)) != 0) {
+            _cpu.set_reg(rn, wb_addr);
+        }
+
+        if is_pc_load {
+            if s != 0 {
+                _cpu.reg.restore_cpsr();
+            }
+            _cpu.set_pc(pc);
+        }
+
+        // Cycle += nS+1N+1I (STM: (n-1)S+2N)
+        if l != 0 {
+            _cpu.idle(1);
+        }
+    }
+}
+
+fn exec_branch(_cpu: &mut CPU, _op: ArmInstruction) {
+    if let ArmInstruction::Branch(l, offset) = _op {
+        trace!("Branch - L: {}, Offset: {}", l, offset);
+        let pc: u32 = _cpu.reg.reg(15);
+        // BL: LRに次の命令のアドレス
+        if l != 0 {
+            _cpu.set_reg(14, pc.wrapping_sub(4));
+        }
+        _cpu.set_pc(pc.wrapping_add((offset << 2) as u32)); // Cycle += 2S+1N
+    }
+}
+
+fn exec_coprocessor_data_transfer(_cpu: &mut CPU, _op: ArmInstruction) {
+    // GBAにコプロセッサは無いので未定義命令例外
+    if let ArmInstruction::CoprocessorDataTransfer(p, u, n, w, l, rn, crd, cp_num, offset) = _op {
+        trace!("CoprocessorDataTransfer - P: {}, U: {}, N: {}, W: {}, L: {}, Rn: {}, CRd: {}, CP#: {}, Offset: {}", p, u, n, w, l, rn, crd, cp_num, offset);
+        _cpu.raise_exception(Exception::Undefined); // Cycle += 2S+1I+1N
+        _cpu.idle(1);
+    }
+}
+
+fn exec_coprocessor_data_operation(_cpu: &mut CPU, _op: ArmInstruction) {
+    // GBAにコプロセッサは無いので未定義命令例外
+    if let ArmInstruction::CoprocessorDataOperation(cp_opc, crn, crd, cp_num, cp, crm) = _op {
+        trace!("CoprocessorDataOperation - CP Opc: {}, CRn: {}, CRd: {}, CP#: {}, CP: {}, CRm: {}", cp_opc, crn, crd, cp_num, cp, crm);
+        _cpu.raise_exception(Exception::Undefined); // Cycle += 2S+1I+1N
+        _cpu.idle(1);
+    }
+}
+
+fn exec_coprocessor_register_transfer(_cpu: &mut CPU, _op: ArmInstruction) {
+    // GBAにコプロセッサは無いので未定義命令例外
+    if let ArmInstruction::CoprocessorRegisterTransfer(cp_opc, l, crn, rd, cp_num, cp, crm) = _op {
+        trace!("CoprocessorRegisterTransfer - CP Opc: {}, L: {}, CRn: {}, Rd: {}, CP#: {}, CP: {}, CRm: {}", cp_opc, l, crn, rd, cp_num, cp, crm);
+        _cpu.raise_exception(Exception::Undefined); // Cycle += 2S+1I+1N
+        _cpu.idle(1);
+    }
+}
+
+fn exec_software_interrupt(_cpu: &mut CPU, _op: ArmInstruction) {
+    if let ArmInstruction::SoftwareInterrupt(comment) = _op {
+        trace!("SoftwareInterrupt - Comment: {:#08X}", comment);
+        // 機能番号はBit[23:16] (HLE時はBIOS処理を直接実行)
+        _cpu.software_interrupt((comment >> 16) as u8); // Cycle += 2S+1N
+    }
+}
+
+// ARM7TDMI ARM命令のデコード
+pub fn arm_op_decode(_cpu: &mut CPU, _op: u32) -> (ArmFormat, ArmInstruction) {
+    arm_format_decode(_op)
+}
+
+// ARM7TDMI ARM命令の実行
+pub fn arm_op_exec(_cpu: &mut CPU, _format: ArmFormat, _op_format: ArmInstruction) {
+    match _format {
+        ArmFormat::DataProcessing              => exec_data_processing(_cpu, _op_format),
+        ArmFormat::PSRTransferMRS              => exec_psr_transfer_mrs(_cpu, _op_format),
+        ArmFormat::PSRTransferMSR              => exec_psr_transfer_msr(_cpu, _op_format),
+        ArmFormat::Multiply                    => exec_multiply(_cpu, _op_format),
+        ArmFormat::MultiplyLong                => exec_multiply_long(_cpu, _op_format),
+        ArmFormat::SingleDataSwap              => exec_single_data_swap(_cpu, _op_format),
+        ArmFormat::BranchExchange              => exec_branch_exchange(_cpu, _op_format),
+        ArmFormat::HalfwordDataTransfer        => exec_halfword_data_transfer(_cpu, _op_format),
+        ArmFormat::SingleDataTransfer          => exec_single_data_transfer(_cpu, _op_format),
+        ArmFormat::Undefined                   => exec_undefined(_cpu, _op_format),
+        ArmFormat::BlockDataTransfer           => exec_block_data_transfer(_cpu, _op_format),
+        ArmFormat::Branch                      => exec_branch(_cpu, _op_format),
+        ArmFormat::CoprocessorDataTransfer     => exec_coprocessor_data_transfer(_cpu, _op_format),
+        ArmFormat::CoprocessorDataOperation    => exec_coprocessor_data_operation(_cpu, _op_format),
+        ArmFormat::CoprocessorRegisterTransfer => exec_coprocessor_register_transfer(_cpu, _op_format),
+        ArmFormat::SoftwareInterrupt           => exec_software_interrupt(_cpu, _op_format),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bios;
+
+    #[test]
+    fn test_arm_format_decode() {
+        // ADD R0, R1, #1
+        assert_eq!(arm_format_decode(0xE281_0001),
+            (ArmFormat::DataProcessing, ArmInstruction::DataProcessing(1, 0b0100, 0, 1, 0, 0x001)));
+        // MRS R0, CPSR
+        assert_eq!(arm_format_decode(0xE10F_0000).0, ArmFormat::PSRTransferMRS);
+        // MSR CPSR_fc, R0
+        assert_eq!(arm_format_decode(0xE129_F000).0, ArmFormat::PSRTransferMSR);
+        // MLA R0, R1, R2, R3
+        assert_eq!(arm_format_decode(0xE020_3291),
+            (ArmFormat::Multiply, ArmInstruction::Multiply(1, 0, 0, 3, 2, 1)));
+        // UMULL R0, R1, R2, R3
+        assert_eq!(arm_format_decode(0xE081_0392).0, ArmFormat::MultiplyLong);
+        // SWP R0, R1, [R2]
+        assert_eq!(arm_format_decode(0xE102_0091).0, ArmFormat::SingleDataSwap);
+        // BX R0
+        assert_eq!(arm_format_decode(0xE12F_FF10),
+            (ArmFormat::BranchExchange, ArmInstruction::BranchExchange(0)));
+        // LDRH R0, [R1, #0x12]
+        assert_eq!(arm_format_decode(0xE1D1_01B2),
+            (ArmFormat::HalfwordDataTransfer, ArmInstruction::HalfwordDataTransfer(1, 1, 1, 0, 1, 1, 0, 0b01, 0x12)));
+        // LDR R0, [R1, #4]
+        assert_eq!(arm_format_decode(0xE591_0004).0, ArmFormat::SingleDataTransfer);
+        // 未定義命令
+        assert_eq!(arm_format_decode(0xE600_0010).0, ArmFormat::Undefined);
+        // STMFD SP!, {R0-R3, LR}
+        assert_eq!(arm_format_decode(0xE92D_400F),
+            (ArmFormat::BlockDataTransfer, ArmInstruction::BlockDataTransfer(1, 0, 0, 1, 0, 13, 0x400F)));
+        // B -8 (自己ループ)
+        assert_eq!(arm_format_decode(0xEAFF_FFFE),
+            (ArmFormat::Branch, ArmInstruction::Branch(0, -2)));
+        // LDC / CDP / MRC
+        assert_eq!(arm_format_decode(0xED91_0100).0, ArmFormat::CoprocessorDataTransfer);
+        assert_eq!(arm_format_decode(0xEE01_0100).0, ArmFormat::CoprocessorDataOperation);
+        assert_eq!(arm_format_decode(0xEE11_0110).0, ArmFormat::CoprocessorRegisterTransfer);
+        // SWI 0x060000
+        assert_eq!(arm_format_decode(0xEF06_0000),
+            (ArmFormat::SoftwareInterrupt, ArmInstruction::SoftwareInterrupt(0x060000)));
+    }
+
+    #[test]
+    fn test_arm_format_lut_coverage() {
+        // 全インデックスがいずれかのパターンに一致する
+        for i in 0..4096 {
+            assert!(arm_lut_format(i).is_some(), "No ARM format pattern for index {:#05X}", i);
+        }
+    }
+
+    #[test]
+    fn test_msr_keeps_state() {
+        let mut cpu = CPU::new();
+        bios::skip_boot(&mut cpu);
+        // MSR CPSR_c, R0 (SVCモード + Tビット)
+        cpu.reg.r[0] = PSR::MODE_SVC.bits() | PSR::T.bits();
+        let (format, op) = arm_format_decode(0xE121_F000);
+        arm_op_exec(&mut cpu, format, op);
+        assert_eq!(cpu.reg.mode().bits(), PSR::MODE_SVC.bits());
+        assert!(!cpu.reg.cpsr.contains(PSR::T));
+    }
+}
@@ -0,0 +1,130 @@
+// =========================================================================
+// [Barrel Shifter]
+// ARM/Thumb共通のシフタ (戻り値は (結果, シフタキャリーアウト))
+// =========================================================================
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ShiftType {
+    LSL, // 論理左シフト
+    LSR, // 論理右シフト
+    ASR, // 算術右シフト
+    ROR, // 右ローテート
+}
+
+impl ShiftType {
+    // シフト種別 Bit[1:0] (ARM Bit[6:5], Thumb Format01 Op)
+    pub fn from_bits(bits: u32) -> Self {
+        match bits & 0b11 {
+            0b00 => ShiftType::LSL,
+            0b01 => ShiftType::LSR,
+            0b10 => ShiftType::ASR,
+            _ => ShiftType::ROR,
+        }
+    }
+}
+
+// LSL (シフト量0はキャリー維持、32以上は0)
+pub fn lsl(val: u32, amount: u32, carry: bool) -> (u32, bool) {
+    match amount {
+        0 => (val, carry),
+        1..=31 => (val << amount, ((val >> (32 - amount)) & 1) != 0),
+        32 => (0, (val & 1) != 0),
+        _ => (0, false),
+    }
+}
+
+// LSR
+pub fn lsr(val: u32, amount: u32, carry: bool) -> (u32, bool) {
+    match amount {
+        0 => (val, carry),
+        1..=31 => (val >> amount, ((val >> (amount - 1)) & 1) != 0),
+        32 => (0, (val >> 31) != 0),
+        _ => (0, false),
+    }
+}
+
+// ASR (32以上は符号ビットで埋める)
+pub fn asr(val: u32, amount: u32, carry: bool) -> (u32, bool) {
+    match amount {
+        0 => (val, carry),
+        1..=31 => (((val as i32) >> amount) as u32, ((val >> (amount - 1)) & 1) != 0),
+        _ => (((val as i32) >> 31) as u32, (val >> 31) != 0),
+    }
+}
+
+// ROR (32の倍数は値そのままでキャリーはBit31)
+pub fn ror(val: u32, amount: u32, carry: bool) -> (u32, bool) {
+    if amount == 0 {
+        return (val, carry);
+    }
+    let ret: u32 = val.rotate_right(amount & 31);
+    (ret, (ret >> 31) != 0)
+}
+
+// RRX (キャリーを含めた1bit右ローテート)
+pub fn rrx(val: u32, carry: bool) -> (u32, bool) {
+    (((carry as u32) << 31) | (val >> 1), (val & 1) != 0)
+}
+
+// 即値指定シフト (#0はLSL以外特殊: LSR#32, ASR#32, RRX)
+pub fn shift_imm(shift: ShiftType, val: u32, amount: u32, carry: bool) -> (u32, bool) {
+    match (shift, amount) {
+        (ShiftType::LSL, _) => lsl(val, amount, carry),
+        (ShiftType::LSR, 0) => lsr(val, 32, carry),
+        (ShiftType::LSR, _) => lsr(val, amount, carry),
+        (ShiftType::ASR, 0) => asr(val, 32, carry),
+        (ShiftType::ASR, _) => asr(val, amount, carry),
+        (ShiftType::ROR, 0) => rrx(val, carry),
+        (ShiftType::ROR, _) => ror(val, amount, carry),
+    }
+}
+
+// レジスタ指定シフト (シフト量はRsの下位8bit、0ならキャリー維持)
+pub fn shift_reg(shift: ShiftType, val: u32, amount: u32, carry: bool) -> (u32, bool) {
+    let amount: u32 = amount & 0xFF;
+    match shift {
+        ShiftType::LSL => lsl(val, amount, carry),
+        ShiftType::LSR => lsr(val, amount, carry),
+        ShiftType::ASR => asr(val, amount, carry),
+        ShiftType::ROR => ror(val, amount, carry),
+    }
+}
+
+// 即値ローテート (ARM データ処理/MSRの Imm8 ROR (Rotate*2))
+pub fn rotate_imm(imm8: u32, rotate: u32, carry: bool) -> (u32, bool) {
+    ror(imm8, rotate * 2, carry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shift_imm() {
+        assert_eq!(shift_imm(ShiftType::LSL, 0x8000_0001, 0, true), (0x8000_0001, true));
+        assert_eq!(shift_imm(ShiftType::LSL, 0x8000_0001, 1, false), (0x0000_0002, true));
+        assert_eq!(shift_imm(ShiftType::LSR, 0x8000_0000, 0, false), (0, true));
+        assert_eq!(shift_imm(ShiftType::ASR, 0x8000_0000, 0, false), (0xFFFF_FFFF, true));
+        assert_eq!(shift_imm(ShiftType::ROR, 0x0000_0001, 0, true), (0x8000_0000, true));
+        assert_eq!(shift_imm(ShiftType::ROR, 0x0000_0003, 1, false), (0x8000_0001, true));
+    }
+
+    #[test]
+    fn test_shift_reg() {
+        assert_eq!(shift_reg(ShiftType::LSL, 0x0000_0001, 0, true), (0x0000_0001, true));
+        assert_eq!(shift_reg(ShiftType::LSL, 0x0000_0001, 32, false), (0, true));
+        assert_eq!(shift_reg(ShiftType::LSL, 0x0000_0001, 33, true), (0, false));
+        assert_eq!(shift_reg(ShiftType::LSR, 0x8000_0000, 32, false), (0, true));
+        assert_eq!(shift_reg(ShiftType::LSR, 0x8000_0000, 33, true), (0, false));
+        assert_eq!(shift_reg(ShiftType::ASR, 0x8000_0000, 40, false), (0xFFFF_FFFF, true));
+        assert_eq!(shift_reg(ShiftType::ROR, 0x8000_0000, 32, false), (0x8000_0000, true));
+        // 下位8bitのみ有効
+        assert_eq!(shift_reg(ShiftType::LSL, 0x0000_0001, 0x101, false), (0x0000_0002, false));
+    }
+
+    #[test]
+    fn test_rotate_imm() {
+        assert_eq!(rotate_imm(0xFF, 4, false), (0xFF00_0000, true));
+        assert_eq!(rotate_imm(0x01, 0, true), (0x01, true));
+    }
+}
@@ -0,0 +1,688 @@
+// =========================================================================
+// [BIOS HLE]
+// BIOSイメージが無い場合のSWI高レベルエミュレーション
+// ※メモリアクセスはバスを直接叩く (サイクルは考慮しない)
+// =========================================================================
+use cpu::*;
+use common::_BIT_31;
+use bus::_BIOS_SIZE;
+use std::f64::consts::PI;
+
+// BIOSが使用するIWRAM上の領域
+const _BIOS_IF_FLAGS: u32 = 0x0300_7FF8;   // 割り込みチェックフラグ (IntrWait用)
+const _BIOS_RESET_FLAG: u32 = 0x0300_7FFA; // SoftReset 復帰先 (0:ROM, 0以外:EWRAM)
+
+// I/O レジスタ
+const _REG_DISPCNT: u32 = 0x0400_0000;
+const _REG_IME: u32 = 0x0400_0208;
+const _REG_HALTCNT: u32 = 0x0400_0301;
+
+// スタック初期値
+const _SP_SVC: u32 = 0x0300_7FE0;
+const _SP_IRQ: u32 = 0x0300_7FA0;
+const _SP_SYS: u32 = 0x0300_7F00;
+
+// HLE用の疑似BIOSイメージ
+// 0x00: Reset  -> ROM先頭へ分岐
+// 0x08: SWI    -> 呼ばれないが念のため復帰
+// 0x18: IRQ    -> [0x03007FFC] のユーザ割り込みハンドラを呼び出して復帰
+const _HLE_BIOS_CODE: [(u32, u32); 8] = [
+    (0x00, 0xE3A0_F302), // MOV   PC, #0x08000000
+    (0x08, 0xE1B0_F00E), // MOVS  PC, LR
+    (0x18, 0xE92D_500F), // STMFD SP!, {R0-R3, R12, LR}
+    (0x1C, 0xE3A0_0403), // MOV   R0, #0x03000000
+    (0x20, 0xE280_0902), // ADD   R0, R0, #0x8000
+    (0x24, 0xE28F_E000), // ADD   LR, PC, #0
+    (0x28, 0xE510_F004), // LDR   PC, [R0, #-4]
+    (0x2C, 0xE8BD_500F), // LDMFD SP!, {R0-R3, R12, LR}
+];
+const _HLE_BIOS_IRQ_RET: (u32, u32) = (0x30, 0xE25E_F004); // SUBS PC, LR, #4
+
+pub fn hle_bios_image() -> Vec<u8> {
+    let mut image: Vec<u8> = vec![0; _BIOS_SIZE];
+    for (addr, op) in _HLE_BIOS_CODE.iter().chain([_HLE_BIOS_IRQ_RET].iter()) {
+        let addr: usize = *addr as usize;
+        image[addr..addr + 4].copy_from_slice(&op.to_le_bytes());
+    }
+    image
+}
+
+// BIOS起動処理をスキップしてカートリッジのエントリポイントから開始
+pub fn skip_boot(_cpu: &mut CPU) {
+    boot(_cpu, 0x0800_0000);
+}
+
+// スタック/レジスタを初期化してSYSモードでaddrへ分岐
+fn boot(_cpu: &mut CPU, addr: u32) {
+    _cpu.reg.switch_mode(PSR::MODE_SVC);
+    _cpu.reg.set_reg(13, _SP_SVC);
+    _cpu.reg.set_reg(14, 0);
+    _cpu.reg.set_spsr(PSR::empty());
+    _cpu.reg.switch_mode(PSR::MODE_IRQ);
+    _cpu.reg.set_reg(13, _SP_IRQ);
+    _cpu.reg.set_reg(14, 0);
+    _cpu.reg.set_spsr(PSR::empty());
+    _cpu.reg.switch_mode(PSR::MODE_SYS);
+    _cpu.reg.set_reg(13, _SP_SYS);
+    _cpu.reg.set_reg(14, 0);
+    _cpu.reg.r = [0; 13];
+    _cpu.hle_intr_wait = false;
+
+    // SYSモード, ARMステート, IRQ/FIQ許可
+    _cpu.reg.cpsr = PSR::MODE_SYS;
+    _cpu.set_pc(addr);
+}
+
+// バスアクセス
+fn read8(_cpu: &mut CPU, addr: u32) -> u8 {
+    unsafe { _cpu.bus.read_byte(addr) }
+}
+
+fn read16(_cpu: &mut CPU, addr: u32) -> u16 {
+    unsafe { _cpu.bus.read_hword(addr & !1) }
+}
+
+fn read32(_cpu: &mut CPU, addr: u32) -> u32 {
+    unsafe { _cpu.bus.read_word(addr & !3) }
+}
+
+fn write8(_cpu: &mut CPU, addr: u32, val: u8) {
+    unsafe { _cpu.bus.write_byte(addr, val) }
+}
+
+fn write16(_cpu: &mut CPU, addr: u32, val: u16) {
+    unsafe { _cpu.bus.write_hword(addr & !1, val) }
+}
+
+fn write32(_cpu: &mut CPU, addr: u32, val: u32) {
+    unsafe { _cpu.bus.write_word(addr & !3, val) }
+}
+
+fn fill32(_cpu: &mut CPU, start: u32, end: u32) {
+    for addr in (start..end).step_by(4) {
+        write32(_cpu, addr, 0);
+    }
+}
+
+// SWI 高レベルエミュレーション (comment: 機能番号)
+pub fn swi(_cpu: &mut CPU, comment: u8) {
+    trace!("HLE SWI: {:#04X}", comment);
+    match comment {
+        0x00 => soft_reset(_cpu),
+        0x01 => register_ram_reset(_cpu),
+        0x02 => write8(_cpu, _REG_HALTCNT, 0x00),  // Halt
+        0x03 => write8(_cpu, _REG_HALTCNT, 0x80),  // Stop
+        0x04 => intr_wait(_cpu),
+        0x05 => {
+            // VBlankIntrWait (引数の設定は初回のみ, 再実行時はハンドラが立てたフラグを破棄しない)
+            if !_cpu.hle_intr_wait {
+                _cpu.reg.r[0] = 1;
+                _cpu.reg.r[1] = 1;
+            }
+            intr_wait(_cpu);
+        },
+        0x06 => div(_cpu, _cpu.reg.r[0] as i32, _cpu.reg.r[1] as i32),
+        0x07 => div(_cpu, _cpu.reg.r[1] as i32, _cpu.reg.r[0] as i32), // DivArm
+        0x08 => _cpu.reg.r[0] = (_cpu.reg.r[0] as f64).sqrt() as u32,
+        0x09 => {
+            let (ret, a, b) = arctan(_cpu.reg.r[0] as i32);
+            _cpu.reg.r[0] = ret as u32;
+            _cpu.reg.r[1] = a as u32;
+            _cpu.reg.r[3] = b as u32;
+        },
+        0x0A => _cpu.reg.r[0] = arctan2(_cpu.reg.r[0] as i32, _cpu.reg.r[1] as i32) as u16 as u32,
+        0x0B => cpu_set(_cpu),
+        0x0C => cpu_fast_set(_cpu),
+        0x0D => _cpu.reg.r[0] = 0xBAAE_187F, // GetBiosChecksum
+        0x0E => bg_affine_set(_cpu),
+        0x0F => obj_affine_set(_cpu),
+        0x10 => bit_unpack(_cpu),
+        0x11 => { let out = lz77_uncomp(_cpu, _cpu.reg.r[0]); write_wram(_cpu, &out); },
+        0x12 => { let out = lz77_uncomp(_cpu, _cpu.reg.r[0]); write_vram(_cpu, &out); },
+        0x13 => { let out = huff_uncomp(_cpu, _cpu.reg.r[0]); write_wram(_cpu, &out); },
+        0x14 => { let out = rl_uncomp(_cpu, _cpu.reg.r[0]); write_wram(_cpu, &out); },
+        0x15 => { let out = rl_uncomp(_cpu, _cpu.reg.r[0]); write_vram(_cpu, &out); },
+        0x1F => midi_key_to_freq(_cpu),
+        _ => warn!("Unsupported HLE SWI: {:#04X}", comment),
+    }
+}
+
+// SWI 0x00 SoftReset
+fn soft_reset(_cpu: &mut CPU) {
+    let flag: u8 = read8(_cpu, _BIOS_RESET_FLAG);
+    fill32(_cpu, 0x0300_7E00, 0x0300_8000);
+    boot(_cpu, if flag != 0 { 0x0200_0000 } else { 0x0800_0000 });
+}
+
+// SWI 0x01 RegisterRamReset
+fn register_ram_reset(_cpu: &mut CPU) {
+    let flags: u32 = _cpu.reg.r[0];
+    // 強制ブランク
+    write16(_cpu, _REG_DISPCNT, 0x0080);
+    if (flags & 0x01) != 0 { fill32(_cpu, 0x0200_0000, 0x0204_0000); } // EWRAM
+    if (flags & 0x02) != 0 { fill32(_cpu, 0x0300_0000, 0x0300_7E00); } // IWRAM (末尾0x200は除く)
+    if (flags & 0x04) != 0 { fill32(_cpu, 0x0500_0000, 0x0500_0400); } // Palette RAM
+    if (flags & 0x08) != 0 { fill32(_cpu, 0x0600_0000, 0x0601_8000); } // VRAM
+    if (flags & 0x10) != 0 { fill32(_cpu, 0x0700_0000, 0x0700_0400); } // OAM
+    if (flags & 0x20) != 0 { fill32(_cpu, 0x0400_0120, 0x0400_0160); } // SIO
+    if (flags & 0x40) != 0 { fill32(_cpu, 0x0400_0060, 0x0400_00B0); } // Sound
+    if (flags & 0x80) != 0 {
+        // その他のレジスタ (DISPCNTは除く)
+        fill32(_cpu, 0x0400_0004, 0x0400_0060);
+        fill32(_cpu, 0x0400_00B0, 0x0400_0120);
+        fill32(_cpu, 0x0400_0200, 0x0400_0210);
+    }
+}
+
+// SWI 0x04 IntrWait (R0:古いフラグを破棄, R1:待つ割り込み)
+// 条件を満たさない場合はHALTしてSWI命令から再実行する
+fn intr_wait(_cpu: &mut CPU) {
+    let flags: u16 = _cpu.reg.r[1] as u16;
+    write16(_cpu, _REG_IME, 1);
+
+    let mut if_flags: u16 = read16(_cpu, _BIOS_IF_FLAGS);
+    if _cpu.reg.r[0] != 0 {
+        if_flags &= !flags;
+        write16(_cpu, _BIOS_IF_FLAGS, if_flags);
+        _cpu.reg.r[0] = 0; // 再実行時は破棄しない
+    } else if (if_flags & flags) != 0 {
+        write16(_cpu, _BIOS_IF_FLAGS, if_flags & !flags);
+        _cpu.hle_intr_wait = false;
+        return;
+    }
+
+    write8(_cpu, _REG_HALTCNT, 0x00);
+    _cpu.hle_intr_wait = true;
+    // 実行中命令 = PC-8 (Thumb:PC-4)
+    let width: u32 = if _cpu.reg.cpsr.contains(PSR::T) { 2 } else { 4 };
+    let pc: u32 = _cpu.reg.pc.wrapping_sub(width * 2);
+    _cpu.set_pc(pc);
+}
+
+// SWI 0x06 Div (R0:商, R1:余り, R3:商の絶対値)
+fn div(_cpu: &mut CPU, num: i32, denom: i32) {
+    if denom == 0 {
+        // 実機は無限ループになる
+        warn!("HLE SWI Div: divide by zero");
+        _cpu.reg.r[0] = if num < 0 { u32::MAX } else { 1 };
+        _cpu.reg.r[1] = num as u32;
+        _cpu.reg.r[3] = 1;
+        return;
+    }
+    let quot: i32 = num.wrapping_div(denom);
+    _cpu.reg.r[0] = quot as u32;
+    _cpu.reg.r[1] = num.wrapping_rem(denom) as u32;
+    _cpu.reg.r[3] = quot.unsigned_abs();
+}
+
+// SWI 0x09 ArcTan (戻り値は (結果, R1, R3))
+fn arctan(i: i32) -> (i32, i32, i32) {
+    let a: i32 = -(i.wrapping_mul(i) >> 14);
+    let mut b: i32 = ((0xA9 * a) >> 14) + 0x390;
+    b = (b.wrapping_mul(a) >> 14) + 0x91C;
+    b = (b.wrapping_mul(a) >> 14) + 0xFB6;
+    b = (b.wrapping_mul(a) >> 14) + 0x16AA;
+    b = (b.wrapping_mul(a) >> 14) + 0x2081;
+    b = (b.wrapping_mul(a) >> 14) + 0x3651;
+    b = (b.wrapping_mul(a) >> 14) + 0xA2F9;
+    (i.wrapping_mul(b) >> 16, a, b)
+}
+
+// SWI 0x0A ArcTan2 (0x0000~0xFFFF で 0~2π)
+fn arctan2(x: i32, y: i32) -> i32 {
+    if y == 0 {
+        return if x >= 0 { 0 } else { 0x8000 };
+    }
+    if x == 0 {
+        return if y >= 0 { 0x4000 } else { 0xC000 };
+    }
+    let atan_yx = || arctan((y << 14) / x).0;
+    let atan_xy = || arctan((x << 14) / y).0;
+    if y >= 0 {
+        if x >= 0 {
+            if x >= y {
+                return atan_yx();
+            }
+        } else if -x >= y {
+            return atan_yx() + 0x8000;
+        }
+        0x4000 - atan_xy()
+    } else {
+        if x <= 0 {
+            if -x > -y {
+                return atan_yx() + 0x8000;
+            }
+        } else if x >= -y {
+            return atan_yx() + 0x10000;
+        }
+        0xC000 - atan_xy()
+    }
+}
+
+// SWI 0x0B CpuSet (R0:転送元, R1:転送先, R2:Bit[20:0]:数, Bit24:フィル, Bit26:32bit)
+fn cpu_set(_cpu: &mut CPU) {
+    let (src, dst, ctrl) = (_cpu.reg.r[0], _cpu.reg.r[1], _cpu.reg.r[2]);
+    let count: u32 = ctrl & 0x1F_FFFF;
+    let fill: bool = (ctrl & (1 << 24)) != 0;
+    if (ctrl & (1 << 26)) != 0 {
+        let fill_val: u32 = read32(_cpu, src);
+        for i in 0..count {
+            let val: u32 = if fill { fill_val } else { read32(_cpu, src.wrapping_add(i * 4)) };
+            write32(_cpu, dst.wrapping_add(i * 4), val);
+        }
+    } else {
+        let fill_val: u16 = read16(_cpu, src);
+        for i in 0..count {
+            let val: u16 = if fill { fill_val } else { read16(_cpu, src.wrapping_add(i * 2)) };
+            write16(_cpu, dst.wrapping_add(i * 2), val);
+        }
+    }
+}
+
+// SWI 0x0C CpuFastSet (32bit単位, 数は8の倍数に切り上げ)
+fn cpu_fast_set(_cpu: &mut CPU) {
+    let (src, dst, ctrl) = (_cpu.reg.r[0], _cpu.reg.r[1], _cpu.reg.r[2]);
+    let count: u32 = ((ctrl & 0x1F_FFFF) + 7) & !7;
+    let fill: bool = (ctrl & (1 << 24)) != 0;
+    let fill_val: u32 = read32(_cpu, src);
+    for i in 0..count {
+        let val: u32 = if fill { fill_val } else { read32(_cpu, src.wrapping_add(i * 4)) };
+        write32(_cpu, dst.wrapping_add(i * 4), val);
+    }
+}
+
+// 回転角 (上位8bitのみ有効) -> ラジアン
+fn affine_theta(angle: u16) -> f64 {
+    ((angle >> 8) as f64) / 128.0 * PI
+}
+
+// SWI 0x0E BgAffineSet (R0:入力(20byte), R1:出力(16byte), R2:数)
+fn bg_affine_set(_cpu: &mut CPU) {
+    let (mut src, mut dst, count) = (_cpu.reg.r[0], _cpu.reg.r[1], _cpu.reg.r[2]);
+    for _ in 0..count {
+        let ox: f64 = (read32(_cpu, src) as i32 as f64) / 256.0;
+        let oy: f64 = (read32(_cpu, src + 4) as i32 as f64) / 256.0;
+        let cx: f64 = read16(_cpu, src + 8) as i16 as f64;
+        let cy: f64 = read16(_cpu, src + 10) as i16 as f64;
+        let sx: f64 = (read16(_cpu, src + 12) as i16 as f64) / 256.0;
+        let sy: f64 = (read16(_cpu, src + 14) as i16 as f64) / 256.0;
+        let theta: f64 = affine_theta(read16(_cpu, src + 16));
+        src += 20;
+
+        let (sin, cos) = theta.sin_cos();
+        let (pa, pb, pc, pd) = (cos * sx, -sin * sx, sin * sy, cos * sy);
+        let rx: f64 = ox - (pa * cx + pb * cy);
+        let ry: f64 = oy - (pc * cx + pd * cy);
+        write16(_cpu, dst, (pa * 256.0) as i16 as u16);
+        write16(_cpu, dst + 2, (pb * 256.0) as i16 as u16);
+        write16(_cpu, dst + 4, (pc * 256.0) as i16 as u16);
+        write16(_cpu, dst + 6, (pd * 256.0) as i16 as u16);
+        write32(_cpu, dst + 8, (rx * 256.0) as i32 as u32);
+        write32(_cpu, dst + 12, (ry * 256.0) as i32 as u32);
+        dst += 16;
+    }
+}
+
+// SWI 0x0F ObjAffineSet (R0:入力(8byte), R1:出力, R2:数, R3:出力間隔)
+fn obj_affine_set(_cpu: &mut CPU) {
+    let (mut src, mut dst, count, diff) = (_cpu.reg.r[0], _cpu.reg.r[1], _cpu.reg.r[2], _cpu.reg.r[3]);
+    for _ in 0..count {
+        let sx: f64 = (read16(_cpu, src) as i16 as f64) / 256.0;
+        let sy: f64 = (read16(_cpu, src + 2) as i16 as f64) / 256.0;
+        let theta: f64 = affine_theta(read16(_cpu, src + 4));
+        src += 8;
+
+        let (sin, cos) = theta.sin_cos();
+        let params: [f64; 4] = [cos * sx, -sin * sx, sin * sy, cos * sy];
+        for param in params.iter() {
+            write16(_cpu, dst, (param * 256.0) as i16 as u16);
+            dst = dst.wrapping_add(diff);
+        }
+    }
+}
+
+// SWI 0x10 BitUnPack (R2: |Len(16)|SrcWidth(8)|DstWidth(8)|Offset(31)+ZeroFlag(1)|)
+fn bit_unpack(_cpu: &mut CPU) {
+    let (src, mut dst, info) = (_cpu.reg.r[0], _cpu.reg.r[1], _cpu.reg.r[2]);
+    let len: u32 = read16(_cpu, info) as u32;
+    let src_width: u32 = read8(_cpu, info + 2) as u32;
+    let dst_width: u32 = read8(_cpu, info + 3) as u32;
+    let data: u32 = read32(_cpu, info + 4);
+    let offset: u32 = data & 0x7FFF_FFFF;
+    let zero_flag: bool = (data & _BIT_31) != 0;
+    if !matches!(src_width, 1 | 2 | 4 | 8) || !matches!(dst_width, 1 | 2 | 4 | 8 | 16 | 32) {
+        warn!("HLE SWI BitUnPack: invalid width {} -> {}", src_width, dst_width);
+        return;
+    }
+
+    let src_mask: u32 = (1 << src_width) - 1;
+    let mut out: u32 = 0;
+    let mut out_bits: u32 = 0;
+    for i in 0..len {
+        let byte: u32 = read8(_cpu, src.wrapping_add(i)) as u32;
+        for shift in (0..8).step_by(src_width as usize) {
+            let mut val: u32 = (byte >> shift) & src_mask;
+            if val != 0 || zero_flag {
+                val = val.wrapping_add(offset);
+            }
+            out |= val << out_bits;
+            out_bits += dst_width;
+            if out_bits >= 32 {
+                write32(_cpu, dst, out);
+                dst = dst.wrapping_add(4);
+                out = 0;
+                out_bits = 0;
+            }
+        }
+    }
+}
+
+// 圧縮データヘッダ (戻り値は展開後サイズ)
+fn uncomp_size(_cpu: &mut CPU, src: u32) -> usize {
+    (read32(_cpu, src) >> 8) as usize
+}
+
+// SWI 0x11/0x12 LZ77UnComp
+fn lz77_uncomp(_cpu: &mut CPU, src: u32) -> Vec<u8> {
+    let size: usize = uncomp_size(_cpu, src);
+    let mut out: Vec<u8> = Vec::with_capacity(size);
+    let mut addr: u32 = src + 4;
+    while out.len() < size {
+        let flags: u8 = read8(_cpu, addr);
+        addr += 1;
+        for bit in (0..8).rev() {
+            if out.len() >= size {
+                break;
+            }
+            if (flags >> bit) & 1 == 0 {
+                out.push(read8(_cpu, addr));
+                addr += 1;
+            } else {
+                let b0: usize = read8(_cpu, addr) as usize;
+                let b1: usize = read8(_cpu, addr + 1) as usize;
+                addr += 2;
+                let len: usize = (b0 >> 4) + 3;
+                let disp: usize = (((b0 & 0x0F) << 8) | b1) + 1;
+                for _ in 0..len {
+                    let val: u8 = if disp <= out.len() { out[out.len() - disp] } else { 0 };
+                    out.push(val);
+                }
+            }
+        }
+    }
+    out.truncate(size);
+    out
+}
+
+// SWI 0x13 HuffUnComp
+fn huff_uncomp(_cpu: &mut CPU, src: u32) -> Vec<u8> {
+    let header: u32 = read32(_cpu, src);
+    let size: usize = (header >> 8) as usize;
+    let data_bits: u32 = match header & 0x0F { 4 => 4, _ => 8 };
+    let tree_size: u32 = (read8(_cpu, src + 4) as u32 + 1) * 2;
+    let root: u32 = src + 5;
+    let mut addr: u32 = src + 4 + tree_size;
+
+    let mut out: Vec<u8> = Vec::with_capacity(size);
+    let mut node_addr: u32 = root;
+    let mut node: u8 = read8(_cpu, root);
+    let mut word: u32 = 0;
+    let mut word_bits: u32 = 0;
+    while out.len() < size {
+        let stream: u32 = read32(_cpu, addr);
+        addr += 4;
+        for bit in (0..32).rev() {
+            let dir: u32 = (stream >> bit) & 1;
+            let next: u32 = (node_addr & !1) + ((node & 0x3F) as u32) * 2 + 2 + dir;
+            let is_leaf: bool = (node & (0x80 >> dir)) != 0;
+            if !is_leaf {
+                node_addr = next;
+                node = read8(_cpu, next);
+                continue;
+            }
+            // 葉: データを32bit単位で出力
+            word |= (read8(_cpu, next) as u32) << word_bits;
+            word_bits += data_bits;
+            if word_bits == 32 {
+                out.extend_from_slice(&word.to_le_bytes());
+                word = 0;
+                word_bits = 0;
+            }
+            node_addr = root;
+            node = read8(_cpu, root);
+            if out.len() >= size {
+                break;
+            }
+        }
+    }
+    out.truncate(size);
+    out
+}
+
+// SWI 0x14/0x15 RLUnComp
+fn rl_uncomp(_cpu: &mut CPU, src: u32) -> Vec<u8> {
+    let size: usize = uncomp_size(_cpu, src);
+    let mut out: Vec<u8> = Vec::with_capacity(size);
+    let mut addr: u32 = src + 4;
+    while out.len() < size {
+        let flag: u8 = read8(_cpu, addr);
+        addr += 1;
+        if (flag & 0x80) != 0 {
+            let len: usize = ((flag & 0x7F) as usize) + 3;
+            let val: u8 = read8(_cpu, addr);
+            addr += 1;
+            out.extend(std::iter::repeat_n(val, len));
+        } else {
+            let len: u32 = ((flag & 0x7F) as u32) + 1;
+            for i in 0..len {
+                out.push(read8(_cpu, addr + i));
+            }
+            addr += len;
+        }
+    }
+    out.truncate(size);
+    out
+}
+
+// 展開データの書き込み (WRAM: 8bit単位)
+fn write_wram(_cpu: &mut CPU, data: &[u8]) {
+    let dst: u32 = _cpu.reg.r[1];
+    for (i, val) in data.iter().enumerate() {
+        write8(_cpu, dst.wrapping_add(i as u32), *val);
+    }
+}
+
+// 展開データの書き込み (VRAM: 16bit単位)
+fn write_vram(_cpu: &mut CPU, data: &[u8]) {
+    let dst: u32 = _cpu.reg.r[1];
+    for (i, pair) in data.chunks(2).enumerate() {
+        let val: u16 = pair[0] as u16 | ((*pair.get(1).unwrap_or(&0) as u16) << 8);
+        write16(_cpu, dst.wrapping_add((i * 2) as u32), val);
+    }
+}
+
+// SWI 0x1F MidiKey2Freq (R0:WaveData, R1:MIDIキー, R2:微調整)
+fn midi_key_to_freq(_cpu: &mut CPU) {
+    let freq: f64 = read32(_cpu, _cpu.reg.r[0] + 4) as f64;
+    let key: f64 = (_cpu.reg.r[1] as f64) + (_cpu.reg.r[2] as f64) / 256.0;
+    _cpu.reg.r[0] = (freq / 2f64.powf((180.0 - key) / 12.0)) as u32;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bus::PowerMode;
+    use interrupt::Interrupt;
+
+    #[test]
+    fn test_hle_math() {
+        let mut cpu = CPU::new();
+        cpu.reg.r[0] = (-7i32) as u32;
+        cpu.reg.r[1] = 2;
+        swi(&mut cpu, 0x06);
+        assert_eq!(cpu.reg.r[0] as i32, -3);
+        assert_eq!(cpu.reg.r[1] as i32, -1);
+        assert_eq!(cpu.reg.r[3], 3);
+
+        cpu.reg.r[0] = 1_000_000;
+        swi(&mut cpu, 0x08);
+        assert_eq!(cpu.reg.r[0], 1000);
+
+        assert_eq!(arctan2(1, 0), 0);
+        assert_eq!(arctan2(0, 1), 0x4000);
+        assert_eq!(arctan2(-1, 0), 0x8000);
+    }
+
+    #[test]
+    fn test_hle_uncomp() {
+        let mut cpu = CPU::new();
+        // LZ77: "ABCABCABC" (リテラル3 + 参照(距離3,長さ6))
+        let lz77: [u8; 10] = [0x10, 0x09, 0x00, 0x00, 0x10, b'A', b'B', b'C', 0x30, 0x02];
+        // RL: 'X'x4 + "YZ"
+        let rl: [u8; 10] = [0x30, 0x06, 0x00, 0x00, 0x81, b'X', 0x01, b'Y', b'Z', 0x00];
+        for (i, val) in lz77.iter().enumerate() {
+            write8(&mut cpu, 0x0200_0000 + i as u32, *val);
+        }
+        for (i, val) in rl.iter().enumerate() {
+            write8(&mut cpu, 0x0200_0010 + i as u32, *val);
+        }
+        assert_eq!(lz77_uncomp(&mut cpu, 0x0200_0000), b"ABCABCABC".to_vec());
+        assert_eq!(rl_uncomp(&mut cpu, 0x0200_0010), b"XXXXYZ".to_vec());
+        // Huffman(8bit): A=0, B=1 で "ABAB"
+        write32(&mut cpu, 0x0200_0020, 0x0000_0428);
+        write32(&mut cpu, 0x0200_0024, 0x4241_C001);
+        write32(&mut cpu, 0x0200_0028, 0x5000_0000);
+        assert_eq!(huff_uncomp(&mut cpu, 0x0200_0020), b"ABAB".to_vec());
+
+        // CpuSet (16bit フィル)
+        write16(&mut cpu, 0x0200_0100, 0xBEEF);
+        cpu.reg.r[0] = 0x0200_0100;
+        cpu.reg.r[1] = 0x0300_0000;
+        cpu.reg.r[2] = (1 << 24) | 4;
+        swi(&mut cpu, 0x0B);
+        assert_eq!(read32(&mut cpu, 0x0300_0004), 0xBEEF_BEEF);
+        assert_eq!(read16(&mut cpu, 0x0300_0008), 0);
+    }
+
+    #[test]
+    fn test_skip_boot() {
+        let mut cpu = CPU::new();
+        skip_boot(&mut cpu);
+        assert_eq!(cpu.reg.mode().bits(), PSR::MODE_SYS.bits());
+        assert_eq!(cpu.reg.reg(13), _SP_SYS);
+        cpu.reg.switch_mode(PSR::MODE_IRQ);
+        assert_eq!(cpu.reg.reg(13), _SP_IRQ);
+        cpu.reg.switch_mode(PSR::MODE_SVC);
+        assert_eq!(cpu.reg.reg(13), _SP_SVC);
+    }
+
+    #[test]
+    fn test_hle_intr_wait() {
+        let mut cpu = CPU::new();
+        skip_boot(&mut cpu);
+        cpu.reg.pc = 0x0800_0108; // SWI命令 = 0x08000100
+        write16(&mut cpu, 0x0400_0200, 0x0001); // IE = VBlank
+
+        // VBlankIntrWait: 古いフラグを破棄してHALTし、SWI命令へ戻る
+        write16(&mut cpu, _BIOS_IF_FLAGS, 0x0001);
+        swi(&mut cpu, 0x05);
+        assert_eq!(read16(&mut cpu, _BIOS_IF_FLAGS), 0);
+        assert_eq!(cpu.bus.power_mode(), PowerMode::Halt);
+        assert_eq!(cpu.reg.pc, 0x0800_0100);
+
+        // 割り込みで解除され、ハンドラがフラグを立てた後の再実行で復帰する
+        cpu.bus.interrupt.request(Interrupt::VBlank);
+        assert!(!cpu.bus.is_halted());
+        write16(&mut cpu, _BIOS_IF_FLAGS, 0x0001);
+        cpu.reg.pc = 0x0800_0108;
+        swi(&mut cpu, 0x05);
+        assert_eq!(read16(&mut cpu, _BIOS_IF_FLAGS), 0);
+        assert_eq!(cpu.bus.power_mode(), PowerMode::Running);
+        assert_eq!(cpu.reg.pc, 0x0800_0108);
+        assert!(!cpu.hle_intr_wait);
+
+        // IntrWait (R0=0): 待つ割り込みが既に立っていれば即座に復帰
+        write16(&mut cpu, _BIOS_IF_FLAGS, 0x0005);
+        cpu.reg.r[0] = 0;
+        cpu.reg.r[1] = 0x0004;
+        swi(&mut cpu, 0x04);
+        assert_eq!(read16(&mut cpu, _BIOS_IF_FLAGS), 0x0001);
+        assert_eq!(cpu.bus.power_mode(), PowerMode::Running);
+        assert_eq!(cpu.reg.pc, 0x0800_0108);
+    }
+
+    #[test]
+    fn test_hle_halt_stop() {
+        let mut cpu = CPU::new();
+        skip_boot(&mut cpu);
+        write16(&mut cpu, 0x0400_0200, 0x0001); // IE = VBlank
+        swi(&mut cpu, 0x02);
+        assert_eq!(cpu.bus.power_mode(), PowerMode::Halt);
+        cpu.bus.interrupt.request(Interrupt::VBlank);
+        assert!(!cpu.bus.is_halted());
+        swi(&mut cpu, 0x03);
+        assert_eq!(cpu.bus.power_mode(), PowerMode::Stop);
+    }
+
+    #[test]
+    fn test_hle_transform() {
+        let mut cpu = CPU::new();
+
+        // CpuFastSet (数は8ワード単位に切り上げ)
+        for i in 0..9 {
+            write32(&mut cpu, 0x0200_0000 + i * 4, i + 1);
+        }
+        cpu.reg.r[0] = 0x0200_0000;
+        cpu.reg.r[1] = 0x0300_0100;
+        cpu.reg.r[2] = 1;
+        swi(&mut cpu, 0x0C);
+        assert_eq!(read32(&mut cpu, 0x0300_011C), 8);
+        assert_eq!(read32(&mut cpu, 0x0300_0120), 0);
+
+        // BgAffineSet (回転なし, 等倍: 中心(4,2)を画面(16,8)へ)
+        write32(&mut cpu, 0x0200_0100, 16 << 8);
+        write32(&mut cpu, 0x0200_0104, 8 << 8);
+        write32(&mut cpu, 0x0200_0108, 0x0002_0004);
+        write32(&mut cpu, 0x0200_010C, 0x0100_0100);
+        write16(&mut cpu, 0x0200_0110, 0);
+        cpu.reg.r[0] = 0x0200_0100;
+        cpu.reg.r[1] = 0x0300_0200;
+        cpu.reg.r[2] = 1;
+        swi(&mut cpu, 0x0E);
+        assert_eq!(read32(&mut cpu, 0x0300_0200), 0x0000_0100);
+        assert_eq!(read32(&mut cpu, 0x0300_0204), 0x0100_0000);
+        assert_eq!(read32(&mut cpu, 0x0300_0208), 12 << 8);
+        assert_eq!(read32(&mut cpu, 0x0300_020C), 6 << 8);
+
+        // ObjAffineSet (90度回転, X方向2倍, OAM間隔8byte)
+        write32(&mut cpu, 0x0200_0200, 0x0100_0200);
+        write16(&mut cpu, 0x0200_0204, 0x4000);
+        cpu.reg.r[0] = 0x0200_0200;
+        cpu.reg.r[1] = 0x0300_0300;
+        cpu.reg.r[2] = 1;
+        cpu.reg.r[3] = 8;
+        swi(&mut cpu, 0x0F);
+        assert_eq!(read16(&mut cpu, 0x0300_0300), 0);
+        assert_eq!(read16(&mut cpu, 0x0300_0308), 0xFE00);
+        assert_eq!(read16(&mut cpu, 0x0300_0310), 0x0100);
+        assert_eq!(read16(&mut cpu, 0x0300_0318), 0);
+
+        // BitUnPack (2bit -> 8bit, 0以外にオフセット0x10を加算)
+        write8(&mut cpu, 0x0200_0300, 0x1B);
+        write32(&mut cpu, 0x0200_0304, 0x0802_0001);
+        write32(&mut cpu, 0x0200_0308, 0x10);
+        cpu.reg.r[0] = 0x0200_0300;
+        cpu.reg.r[1] = 0x0300_0400;
+        cpu.reg.r[2] = 0x0200_0304;
+        swi(&mut cpu, 0x10);
+        assert_eq!(read32(&mut cpu, 0x0300_0400), 0x0011_1213);
+
+        // MidiKey2Freq (key=168 は基準(180)の1オクターブ下)
+        write32(&mut cpu, 0x0200_0404, 0x1000_0000);
+        cpu.reg.r[0] = 0x0200_0400;
+        cpu.reg.r[1] = 168;
+        cpu.reg.r[2] = 0;
+        swi(&mut cpu, 0x1F);
+        assert_eq!(cpu.reg.r[0], 0x0800_0000);
+    }
+}
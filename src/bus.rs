@@ -1,5 +1,16 @@
 use common::*;
+use scheduler::*;
+use cartridge::*;
+use io::*;
+use interrupt::*;
+use timer::*;
+use apu::*;
+use dma::*;
+use keypad::*;
+use ppu::*;
 
+// BIOS
+pub const _BIOS_SIZE: usize = _MEM_SIZE_16K;
 // EWRAM(External Work RAM)
 const _EWRAM_SIZE: usize = _MEM_SIZE_256K;
 // IRAM(Internal Work RAM)
@@ -11,27 +22,266 @@ const _VRAM_SIZE: usize = 96 * 1024;
 // OAM
 const _OAM_SIZE: usize = _MEM_SIZE_1K;
 
+// WAITCNT ウェイトステート設定値 (N:第1アクセス, S:第2アクセス)
+const _WAIT_N: [u32; 4] = [4, 3, 2, 8];
+const _WAIT_S_WS0: [u32; 2] = [2, 1];
+const _WAIT_S_WS1: [u32; 2] = [4, 1];
+const _WAIT_S_WS2: [u32; 2] = [8, 1];
+
+// バスアクセスの種類
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum MemoryAccess {
+    NonSeq, // 非シーケンシャル(N)
+    Seq,    // シーケンシャル(S)
+}
+
+// 低消費電力モード (HALTCNT)
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum PowerMode {
+    Running, // 通常動作
+    Halt,    // CPU停止 (IE & IF != 0 で解除)
+    Stop,    // CPU/クロック停止 (Keypad/Serial/GamePak割り込みで解除)
+}
+
 #[allow(dead_code)]
 pub struct Bus {
+    bios: Vec<u8>,
+    bios_exec: bool,             // BIOS領域の命令を実行中か (BIOS読み出し保護)
+    bios_latch: u32,             // 最後にフェッチしたBIOSの命令
     ewram: [u8; _EWRAM_SIZE],
     iram: [u8; _IRAM_SIZE],
+    io: IoRegisters,             // I/Oレジスタ (専用デバイスを持たないもの)
+    pub interrupt: InterruptController, // IE/IF/IME
+    pub timers: Timers,          // TM0~TM3
+    pub apu: Apu,                // Sound
+    pub dma: Dma,                // DMA0~DMA3
+    pub keypad: Keypad,          // KEYINPUT/KEYCNT
+    pub ppu: Ppu,                // LCD
     pram: [u8; _PRAM_SIZE],
     vram: [u8; _VRAM_SIZE],
     oam: [u8; _OAM_SIZE],
+    cartridge: Cartridge,
+    waitcnt: u16,                // WAITCNT (0x04000204)
+    power: PowerMode,            // HALTCNT (0x04000301)
+    cycle_table: [[u32; 4]; 16], // 領域(Addr Bit[27:24])毎のアクセスサイクル |N16|S16|N32|S32|
+    pub scheduler: Scheduler,    // マスタクロック/イベントキュー
 }
 
 #[allow(dead_code)]
 impl Bus {
     pub fn new() -> Self {
-        Bus {
+        let mut bus = Bus {
+            bios: Vec::new(),
+            bios_exec: false,
+            bios_latch: 0,
             ewram: [0; _EWRAM_SIZE],
             iram: [0; _IRAM_SIZE],
+            io: IoRegisters::new(),
+            interrupt: InterruptController::new(),
+            timers: Timers::new(),
+            apu: Apu::new(),
+            dma: Dma::new(),
+            keypad: Keypad::new(),
+            ppu: Ppu::new(),
             pram: [0; _PRAM_SIZE],
             vram: [0; _VRAM_SIZE],
             oam: [0; _OAM_SIZE],
+            cartridge: Cartridge::empty(),
+            waitcnt: 0,
+            power: PowerMode::Running,
+            cycle_table: Bus::make_cycle_table(0),
+            scheduler: Scheduler::new(),
+        };
+        // ライン0のHDrawから開始
+        bus.scheduler.schedule(EventType::HBlank, _HDRAW_CYCLES);
+        let sample_cycles: u64 = bus.apu.sample_cycles();
+        bus.scheduler.schedule(EventType::ApuSample, sample_cycles);
+        bus
+    }
+
+    // 領域毎のアクセスサイクル表を作成 (16bitバス領域の32bitアクセスは2回分)
+    fn make_cycle_table(waitcnt: u16) -> [[u32; 4]; 16] {
+        let mut table: [[u32; 4]; 16] = [[1, 1, 1, 1]; 16];
+        // EWRAM (16bitバス, 2ウェイト)
+        table[0x2] = [3, 3, 6, 6];
+        // Palette RAM / VRAM (16bitバス)
+        table[0x5] = [1, 1, 2, 2];
+        table[0x6] = [1, 1, 2, 2];
+
+        // Game Pak ROM WS0/WS1/WS2 (16bitバス, 32bitアクセスはN+S)
+        let ws: u32 = waitcnt as u32;
+        let rom: [(u32, u32); 3] = [
+            (_WAIT_N[((ws >> 2) & 3) as usize], _WAIT_S_WS0[((ws >> 4) & 1) as usize]),
+            (_WAIT_N[((ws >> 5) & 3) as usize], _WAIT_S_WS1[((ws >> 7) & 1) as usize]),
+            (_WAIT_N[((ws >> 8) & 3) as usize], _WAIT_S_WS2[((ws >> 10) & 1) as usize]),
+        ];
+        for (i, (n, s)) in rom.iter().enumerate() {
+            let cycles: [u32; 4] = [n + 1, s + 1, (n + 1) + (s + 1), (s + 1) * 2];
+            table[0x8 + i * 2] = cycles;
+            table[0x9 + i * 2] = cycles;
+        }
+
+        // Game Pak SRAM (8bitバス)
+        let sram: u32 = _WAIT_N[(ws & 3) as usize] + 1;
+        table[0xE] = [sram; 4];
+        table[0xF] = [sram; 4];
+        table
+    }
+
+    // WAITCNT 書き込み (ウェイトステート再計算)
+    pub fn set_waitcnt(&mut self, val: u16) {
+        self.waitcnt = val;
+        self.cycle_table = Bus::make_cycle_table(val);
+    }
+
+    pub fn waitcnt(&self) -> u16 {
+        self.waitcnt
+    }
+
+    pub fn power_mode(&self) -> PowerMode {
+        self.power
+    }
+
+    // HALT/STOP中か (解除条件が成立していれば通常動作へ戻る)
+    pub fn is_halted(&mut self) -> bool {
+        let wake: bool = match self.power {
+            PowerMode::Running => return false,
+            PowerMode::Halt => self.interrupt.is_requested(),
+            PowerMode::Stop => self.interrupt.is_wake_from_stop(),
+        };
+        if wake {
+            trace!("Wake from {:?}", self.power);
+            self.power = PowerMode::Running;
+        }
+        !wake
+    }
+
+    // アクセスサイクル数 (Width: 1,2,4byte)
+    pub fn access_cycles(&self, addr: u32, width: u32, access: MemoryAccess) -> u32 {
+        let cycles: &[u32; 4] = &self.cycle_table[((addr >> 24) & 0x0F) as usize];
+        match (width, access) {
+            (4, MemoryAccess::NonSeq) => cycles[2],
+            (4, MemoryAccess::Seq) => cycles[3],
+            (_, MemoryAccess::NonSeq) => cycles[0],
+            (_, MemoryAccess::Seq) => cycles[1],
+        }
+    }
+
+    // BIOSイメージの読み込み (16KB)
+    pub fn load_bios(&mut self, image: &[u8]) -> std::io::Result<()> {
+        if image.len() != _BIOS_SIZE {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("BIOS image must be {} bytes (got {})", _BIOS_SIZE, image.len()),
+            ));
+        }
+        self.bios = image.to_vec();
+        Ok(())
+    }
+
+    pub fn has_bios(&self) -> bool {
+        !self.bios.is_empty()
+    }
+
+    // カートリッジ挿入
+    pub fn load_cartridge(&mut self, cartridge: Cartridge) {
+        self.cartridge = cartridge;
+    }
+
+    pub fn cartridge(&self) -> &Cartridge {
+        &self.cartridge
+    }
+
+    // 命令フェッチアドレスの通知 (BIOS読み出し保護の判定用)
+    // BIOS内のフェッチのみラッチを更新する (データ読み出しでは更新しない)
+    pub fn set_fetch_addr(&mut self, addr: u32) {
+        self.bios_exec = addr < _BIOS_SIZE as u32;
+        if self.bios_exec && self.has_bios() {
+            self.bios_latch = self.bios_word(addr);
         }
     }
 
+    // BIOSのワード読み出し (ワード境界)
+    fn bios_word(&self, addr: u32) -> u32 {
+        let idx: usize = (addr & 0x3FFC) as usize;
+        u32::from_le_bytes([self.bios[idx], self.bios[idx + 1], self.bios[idx + 2], self.bios[idx + 3]])
+    }
+
+    // BIOS読み出し (BIOS内実行中以外は最後にフェッチしたBIOS命令を返す)
+    // 戻り値はアドレスのバイト位置に合わせてシフト済み
+    fn read_bios(&mut self, addr: u32) -> u32 {
+        let val: u32 = if self.bios_exec && self.has_bios() { self.bios_word(addr) } else { self.bios_latch };
+        val >> ((addr & 3) * 8)
+    }
+
+    // I/Oレジスタ読み出し (担当デバイスへ振り分け)
+    fn io_read(&mut self, addr: u32) -> u8 {
+        match addr & 0x3FF {
+            0x000..=0x055 => self.ppu.read(addr),
+            0x060..=0x0A7 => {
+                self.apu.update(self.scheduler.now());
+                self.apu.read(addr)
+            },
+            0x0B0..=0x0DF => self.dma.read(addr),
+            0x130..=0x133 => self.keypad.read(addr),
+            0x100..=0x10F => {
+                self.timers.update(self.scheduler.now());
+                self.timers.read(addr)
+            },
+            0x200..=0x203 | 0x208..=0x20B => self.interrupt.read(addr),
+            // WAITCNT (Bit15: Game Pak Type は常に0)
+            0x204..=0x205 => (self.waitcnt >> ((addr & 1) * 8)) as u8,
+            _ => self.io.read(addr),
+        }
+    }
+
+    // I/Oレジスタ書き込み (担当デバイスへ振り分け)
+    fn io_write(&mut self, addr: u32, val: u8) {
+        match addr & 0x3FF {
+            0x000..=0x055 => self.ppu.write(addr, val),
+            0x060..=0x0A7 => {
+                self.apu.update(self.scheduler.now());
+                self.apu.write(addr, val);
+            },
+            0x0B0..=0x0DF => {
+                self.dma.write(addr, val);
+                self.schedule_dma();
+            },
+            0x130..=0x133 => {
+                self.keypad.write(addr, val);
+                self.check_keypad_irq();
+            },
+            0x100..=0x10F => {
+                self.timers.update(self.scheduler.now());
+                self.timers.write(addr, val);
+                self.schedule_timers();
+            },
+            0x200..=0x203 | 0x208..=0x20B => self.interrupt.write(addr, val),
+            0x204..=0x205 => {
+                let shift: u32 = (addr & 1) * 8;
+                let waitcnt: u16 = (self.waitcnt & !(0xFF << shift)) | ((val as u16) << shift);
+                self.set_waitcnt(waitcnt & 0x5FFF);
+            },
+            // HALTCNT (Bit7: 0=Halt, 1=Stop)
+            0x301 => {
+                self.power = if (val & 0x80) != 0 { PowerMode::Stop } else { PowerMode::Halt };
+                trace!("HALTCNT: {:?}", self.power);
+            },
+            _ => self.io.write(addr, val),
+        }
+    }
+
+    // VRAMのオフセット (96KBを128KB単位でミラー, 上位32KBはOBJ領域のミラー)
+    fn vram_offset(addr: u32) -> usize {
+        let offset: usize = (addr & 0x1FFFF) as usize;
+        if offset >= _VRAM_SIZE { offset - 0x8000 } else { offset }
+    }
+
+    // VRAMのOBJ領域の先頭 (ビットマップモードはBG領域が広い)
+    fn vram_obj_base(&self) -> usize {
+        if (self.ppu.dispcnt() & 7) >= 3 { 0x14000 } else { 0x10000 }
+    }
+
     fn read_u8(&mut self, ptr: *const u8) -> u8 {
         unsafe { *ptr }
     }
@@ -75,229 +325,653 @@ impl Bus {
     pub unsafe fn read_byte(&mut self, addr: u32) -> u8 {
         match addr {
             // BIOS
-            0x00000000..=0x00003FFF => todo!("BIOS Read"),
-            // EWRAM(External Work RAM)
-            0x02000000..=0x0203FFFF => {
+            0x00000000..=0x00003FFF => self.read_bios(addr) as u8,
+            // EWRAM(External Work RAM, 256KB毎のミラー)
+            0x02000000..=0x02FFFFFF => {
                 let ptr = self.ewram.as_mut_ptr().add((addr & 0x3FFFF) as usize);
                 self.read_u8(ptr)
             },
-            // IRAM(Internal Work RAM)
-            0x03000000..=0x03007FFF => {
+            // IRAM(Internal Work RAM, 32KB毎のミラー)
+            0x03000000..=0x03FFFFFF => {
                 let ptr = self.iram.as_mut_ptr().add((addr & 0x7FFF) as usize);
                 self.read_u8(ptr)
             },
             // I/O
-            0x04000000..=0x040003FF => todo!("I/O Reg Read"),
-            // Palette RAM
-            0x05000000..=0x050003FF => {
+            0x04000000..=0x040003FF => self.io_read(addr),
+            // Palette RAM (1KB毎のミラー)
+            0x05000000..=0x05FFFFFF => {
                 let ptr = self.pram.as_mut_ptr().add((addr & 0x03FF) as usize);
                 self.read_u8(ptr)
             },
-            // VRAM
-            0x06000000..=0x06017FFF => {
-                let ptr = self.vram.as_mut_ptr().add((addr & 0x17FFF) as usize);
+            // VRAM (128KB毎のミラー)
+            0x06000000..=0x06FFFFFF => {
+                let ptr = self.vram.as_mut_ptr().add(Bus::vram_offset(addr));
                 self.read_u8(ptr)
             },
-            // OAM
-            0x07000000..=0x070003FF => {
+            // OAM (1KB毎のミラー)
+            0x07000000..=0x07FFFFFF => {
                 let ptr = self.oam.as_mut_ptr().add((addr & 0x03FF) as usize);
                 self.read_u8(ptr)
             },
-            // Game Pak ROM/Flash
-            0x08000000..=0x09FFFFFF => todo!("Game Pak ROM/Flash Read"),
-            // Game Pak ROM/Flash Image 1
-            0x0A000000..=0x0BFFFFFF => todo!("Game Pak ROM/Flash Image 1 Read"),
-            // Game Pak ROM/Flash Image 2
-            0x0C000000..=0x0DFFFFFF => todo!("Game Pak ROM/Flash Image 2 Read"),
-            // Game Pak RAM
-            0x0E000000..=0x0E00FFFF => todo!("Game Pak RAM Read"),
-            _ => panic!("[ERR] Invalid 8bit Bus Read Addr ${:#08X}", addr),
+            // Game Pak ROM/Flash (Wait State 0/1/2 のミラー)
+            0x08000000..=0x0DFFFFFF => self.cartridge.read_byte(addr),
+            // Game Pak RAM (バックアップ未実装のため未接続扱い)
+            0x0E000000..=0x0E00FFFF => 0,
+            // 未使用領域 (オープンバスは未実装のため0を返す)
+            _ => {
+                trace!("Unmapped 8bit Bus Read Addr ${:#08X}", addr);
+                0
+            },
         }
     }
 
     pub unsafe fn read_hword(&mut self, addr: u32) -> u16 {
         match addr {
             // BIOS
-            0x00000000..=0x00003FFF => todo!("BIOS Read"),
-            // EWRAM(External Work RAM)
-            0x02000000..=0x0203FFFF => {
+            0x00000000..=0x00003FFF => self.read_bios(addr) as u16,
+            // EWRAM(External Work RAM, 256KB毎のミラー)
+            0x02000000..=0x02FFFFFF => {
                 let ptr = self.ewram.as_mut_ptr().add((addr & 0x3FFFF) as usize);
                 self.read_u16(ptr)
             },
-            // IRAM(Internal Work RAM)
-            0x03000000..=0x03007FFF => {
+            // IRAM(Internal Work RAM, 32KB毎のミラー)
+            0x03000000..=0x03FFFFFF => {
                 let ptr = self.iram.as_mut_ptr().add((addr & 0x7FFF) as usize);
                 self.read_u16(ptr)
             },
             // I/O
-            0x04000000..=0x040003FF => todo!("I/O Reg Read"),
-            // Palette RAM
-            0x05000000..=0x050003FF => {
+            0x04000000..=0x040003FF => {
+                let addr: u32 = addr & !1;
+                (self.io_read(addr) as u16) | ((self.io_read(addr + 1) as u16) << 8)
+            },
+            // Palette RAM (1KB毎のミラー)
+            0x05000000..=0x05FFFFFF => {
                 let ptr = self.pram.as_mut_ptr().add((addr & 0x03FF) as usize);
                 self.read_u16(ptr)
             },
-            // VRAM
-            0x06000000..=0x06017FFF => {
-                let ptr = self.vram.as_mut_ptr().add((addr & 0x17FFF) as usize);
+            // VRAM (128KB毎のミラー)
+            0x06000000..=0x06FFFFFF => {
+                let ptr = self.vram.as_mut_ptr().add(Bus::vram_offset(addr));
                 self.read_u16(ptr)
             },
-            // OAM
-            0x07000000..=0x070003FF => {
+            // OAM (1KB毎のミラー)
+            0x07000000..=0x07FFFFFF => {
                 let ptr = self.oam.as_mut_ptr().add((addr & 0x03FF) as usize);
                 self.read_u16(ptr)
             },
-            // Game Pak ROM/Flash
-            0x08000000..=0x09FFFFFF => todo!("Game Pak ROM/Flash Read"),
-            // Game Pak ROM/Flash Image 1
-            0x0A000000..=0x0BFFFFFF => todo!("Game Pak ROM/Flash Image 1 Read"),
-            // Game Pak ROM/Flash Image 2
-            0x0C000000..=0x0DFFFFFF => todo!("Game Pak ROM/Flash Image 2 Read"),
-            // Game Pak RAM
-            0x0E000000..=0x0E00FFFF => todo!("Game Pak RAM Read"),
-            _ => panic!("[ERR] Invalid 16bit Bus Read Addr ${:#08X}", addr),
+            // Game Pak ROM/Flash (Wait State 0/1/2 のミラー)
+            0x08000000..=0x0DFFFFFF => self.cartridge.read_hword(addr),
+            // Game Pak RAM (バックアップ未実装のため未接続扱い)
+            0x0E000000..=0x0E00FFFF => 0,
+            // 未使用領域 (オープンバスは未実装のため0を返す)
+            _ => {
+                trace!("Unmapped 16bit Bus Read Addr ${:#08X}", addr);
+                0
+            },
         }
     }
 
     pub unsafe fn read_word(&mut self, addr: u32) -> u32 {
         match addr {
             // BIOS
-            0x00000000..=0x00003FFF => todo!("BIOS Read"),
-            // EWRAM(External Work RAM)
-            0x02000000..=0x0203FFFF => {
+            0x00000000..=0x00003FFF => self.read_bios(addr),
+            // EWRAM(External Work RAM, 256KB毎のミラー)
+            0x02000000..=0x02FFFFFF => {
                 let ptr = self.ewram.as_mut_ptr().add((addr & 0x3FFFF) as usize);
                 self.read_u32(ptr)
             },
-            // IRAM(Internal Work RAM)
-            0x03000000..=0x03007FFF => {
+            // IRAM(Internal Work RAM, 32KB毎のミラー)
+            0x03000000..=0x03FFFFFF => {
                 let ptr = self.iram.as_mut_ptr().add((addr & 0x7FFF) as usize);
                 self.read_u32(ptr)
             },
             // I/O
-            0x04000000..=0x040003FF => todo!("I/O Reg Read"),
-            // Palette RAM
-            0x05000000..=0x050003FF => {
+            0x04000000..=0x040003FF => {
+                let addr: u32 = addr & !3;
+                (0..4).fold(0, |acc, i| acc | ((self.io_read(addr + i) as u32) << (i * 8)))
+            },
+            // Palette RAM (1KB毎のミラー)
+            0x05000000..=0x05FFFFFF => {
                 let ptr = self.pram.as_mut_ptr().add((addr & 0x03FF) as usize);
                 self.read_u32(ptr)
             },
-            // VRAM
-            0x06000000..=0x06017FFF => {
-                let ptr = self.vram.as_mut_ptr().add((addr & 0x17FFF) as usize);
+            // VRAM (128KB毎のミラー)
+            0x06000000..=0x06FFFFFF => {
+                let ptr = self.vram.as_mut_ptr().add(Bus::vram_offset(addr));
                 self.read_u32(ptr)
             },
-            // OAM
-            0x07000000..=0x070003FF => {
+            // OAM (1KB毎のミラー)
+            0x07000000..=0x07FFFFFF => {
                 let ptr = self.oam.as_mut_ptr().add((addr & 0x03FF) as usize);
                 self.read_u32(ptr)
             },
-            // Game Pak ROM/Flash
-            0x08000000..=0x09FFFFFF => todo!("Game Pak ROM/Flash Read"),
-            // Game Pak ROM/Flash Image 1
-            0x0A000000..=0x0BFFFFFF => todo!("Game Pak ROM/Flash Image 1 Read"),
-            // Game Pak ROM/Flash Image 2
-            0x0C000000..=0x0DFFFFFF => todo!("Game Pak ROM/Flash Image 2 Read"),
-            _ => panic!("[ERR] Invalid 32bit Bus Read Addr ${:#08X}", addr),
+            // Game Pak ROM/Flash (Wait State 0/1/2 のミラー)
+            0x08000000..=0x0DFFFFFF => self.cartridge.read_word(addr),
+            // Game Pak RAM (バックアップ未実装のため未接続扱い)
+            0x0E000000..=0x0E00FFFF => 0,
+            // 未使用領域 (オープンバスは未実装のため0を返す)
+            _ => {
+                trace!("Unmapped 32bit Bus Read Addr ${:#08X}", addr);
+                0
+            },
         }
     }
 
     pub unsafe fn write_byte(&mut self, addr: u32, val: u8) {
         match addr {
-            // EWRAM(External Work RAM)
-            0x02000000..=0x0203FFFF => {
+            // BIOS (書き込み不可)
+            0x00000000..=0x00003FFF => {},
+            // EWRAM(External Work RAM, 256KB毎のミラー)
+            0x02000000..=0x02FFFFFF => {
                 let ptr = self.ewram.as_mut_ptr().add((addr & 0x3FFFF) as usize);
                 self.write_u8(ptr, val);
             },
-            // IRAM(Internal Work RAM)
-            0x03000000..=0x03007FFF => {
+            // IRAM(Internal Work RAM, 32KB毎のミラー)
+            0x03000000..=0x03FFFFFF => {
                 let ptr = self.iram.as_mut_ptr().add((addr & 0x7FFF) as usize);
                 self.write_u8(ptr, val);
             },
             // I/O
-            0x04000000..=0x040003FF => todo!("I/O Reg Write"),
-            // Palette RAM
-            0x05000000..=0x050003FF => {
-                let ptr = self.pram.as_mut_ptr().add((addr & 0x03FF) as usize);
-                self.write_u8(ptr, val);
+            0x04000000..=0x040003FF => self.io_write(addr, val),
+            // Palette RAM (1KB毎のミラー)
+            // 8bit書き込みはハーフワードの上位/下位両方へ書き込まれる
+            0x05000000..=0x05FFFFFF => {
+                let ptr = self.pram.as_mut_ptr().add((addr & 0x03FE) as usize);
+                self.write_u16(ptr, (val as u16) * 0x0101);
             },
-            // VRAM
-            0x06000000..=0x06017FFF => {
-                let ptr = self.vram.as_mut_ptr().add((addr & 0x17FFF) as usize);
-                self.write_u8(ptr, val);
-            },
-            // OAM
-            0x07000000..=0x070003FF => {
-                let ptr = self.oam.as_mut_ptr().add((addr & 0x03FF) as usize);
-                self.write_u8(ptr, val);
+            // VRAM (128KB毎のミラー)
+            // BG領域はPalette RAMと同様に両バイトへ書き込み, OBJ領域への8bit書き込みは無視
+            0x06000000..=0x06FFFFFF => {
+                let offset: usize = Bus::vram_offset(addr);
+                if offset < self.vram_obj_base() {
+                    let ptr = self.vram.as_mut_ptr().add(offset & !1);
+                    self.write_u16(ptr, (val as u16) * 0x0101);
+                }
             },
-            // Game Pak ROM/Flash
-            0x08000000..=0x09FFFFFF => todo!("Game Pak ROM/Flash Write"),
-            // Game Pak ROM/Flash Image 1
-            0x0A000000..=0x0BFFFFFF => todo!("Game Pak ROM/Flash Image 1 Write"),
-            // Game Pak ROM/Flash Image 2
-            0x0C000000..=0x0DFFFFFF => todo!("Game Pak ROM/Flash Image 2 Write"),
-            // Game Pak RAM
-            0x0E000000..=0x0E00FFFF => todo!("Game Pak RAM Write"),
-            _ => panic!("[ERR] Invalid 8bit Bus Write Addr ${:#08X}", addr),
+            // OAM (8bit書き込みは無視)
+            0x07000000..=0x07FFFFFF => {},
+            // Game Pak ROM/Flash (書き込み不可)
+            0x08000000..=0x0DFFFFFF => {},
+            // Game Pak RAM (バックアップ未実装のため無視)
+            0x0E000000..=0x0E00FFFF => {},
+            // 未使用領域 (書き込みは無視)
+            _ => trace!("Unmapped 8bit Bus Write Addr ${:#08X}", addr),
         }
     }
 
     pub unsafe fn write_hword(&mut self, addr: u32, val: u16) {
         match addr {
-            // EWRAM(External Work RAM)
-            0x02000000..=0x0203FFFF => {
+            // BIOS (書き込み不可)
+            0x00000000..=0x00003FFF => {},
+            // EWRAM(External Work RAM, 256KB毎のミラー)
+            0x02000000..=0x02FFFFFF => {
                 let ptr = self.ewram.as_mut_ptr().add((addr & 0x3FFFF) as usize);
                 self.write_u16(ptr, val);
             },
-            // IRAM(Internal Work RAM)
-            0x03000000..=0x03007FFF => {
+            // IRAM(Internal Work RAM, 32KB毎のミラー)
+            0x03000000..=0x03FFFFFF => {
                 let ptr = self.iram.as_mut_ptr().add((addr & 0x7FFF) as usize);
                 self.write_u16(ptr, val);
             },
             // I/O
-            0x04000000..=0x040003FF => todo!("I/O Reg Write"),
-            // Palette RAM
-            0x05000000..=0x050003FF => {
+            0x04000000..=0x040003FF => {
+                let addr: u32 = addr & !1;
+                self.io_write(addr, val as u8);
+                self.io_write(addr + 1, (val >> 8) as u8);
+            },
+            // Palette RAM (1KB毎のミラー)
+            0x05000000..=0x05FFFFFF => {
                 let ptr = self.pram.as_mut_ptr().add((addr & 0x03FF) as usize);
                 self.write_u16(ptr, val);
             },
-            // VRAM
-            0x06000000..=0x06017FFF => {
-                let ptr = self.vram.as_mut_ptr().add((addr & 0x17FFF) as usize);
+            // VRAM (128KB毎のミラー)
+            0x06000000..=0x06FFFFFF => {
+                let ptr = self.vram.as_mut_ptr().add(Bus::vram_offset(addr));
                 self.write_u16(ptr, val);
             },
-            // OAM
-            0x07000000..=0x070003FF => {
+            // OAM (1KB毎のミラー)
+            0x07000000..=0x07FFFFFF => {
                 let ptr = self.oam.as_mut_ptr().add((addr & 0x03FF) as usize);
                 self.write_u16(ptr, val);
             },
-            // Game Pak ROM/Flash
-            0x08000000..=0x09FFFFFF => todo!("Game Pak ROM/Flash Write"),
-            // Game Pak ROM/Flash Image 1
-            0x0A000000..=0x0BFFFFFF => todo!("Game Pak ROM/Flash Image 1 Write"),
-            // Game Pak ROM/Flash Image 2
-            0x0C000000..=0x0DFFFFFF => todo!("Game Pak ROM/Flash Image 2 Write"),
-            _ => panic!("[ERR] Invalid 16bit Bus Write Addr ${:#08X}", addr),
+            // Game Pak ROM/Flash (書き込み不可)
+            0x08000000..=0x0DFFFFFF => {},
+            // Game Pak RAM (バックアップ未実装のため無視)
+            0x0E000000..=0x0E00FFFF => {},
+            // 未使用領域 (書き込みは無視)
+            _ => trace!("Unmapped 16bit Bus Write Addr ${:#08X}", addr),
         }
     }
 
     pub unsafe fn write_word(&mut self, addr: u32, val: u32) {
         match addr {
-            // IRAM(Internal Work RAM)
-            0x03000000..=0x03007FFF => {
+            // BIOS (書き込み不可)
+            0x00000000..=0x00003FFF => {},
+            // EWRAM(External Work RAM, 256KB毎のミラー)
+            0x02000000..=0x02FFFFFF => {
+                let ptr = self.ewram.as_mut_ptr().add((addr & 0x3FFFF) as usize);
+                self.write_u32(ptr, val);
+            },
+            // IRAM(Internal Work RAM, 32KB毎のミラー)
+            0x03000000..=0x03FFFFFF => {
                 let ptr = self.iram.as_mut_ptr().add((addr & 0x7FFF) as usize);
                 self.write_u32(ptr, val);
             },
             // I/O
-            0x04000000..=0x040003FF => todo!("I/O Reg Write"),
-            // OAM
-            0x07000000..=0x070003FF => {
+            0x04000000..=0x040003FF => {
+                let addr: u32 = addr & !3;
+                for i in 0..4 {
+                    self.io_write(addr + i, (val >> (i * 8)) as u8);
+                }
+            },
+            // Palette RAM (1KB毎のミラー)
+            0x05000000..=0x05FFFFFF => {
+                let ptr = self.pram.as_mut_ptr().add((addr & 0x03FF) as usize);
+                self.write_u32(ptr, val);
+            },
+            // VRAM (128KB毎のミラー)
+            0x06000000..=0x06FFFFFF => {
+                let ptr = self.vram.as_mut_ptr().add(Bus::vram_offset(addr));
+                self.write_u32(ptr, val);
+            },
+            // OAM (1KB毎のミラー)
+            0x07000000..=0x07FFFFFF => {
                 let ptr = self.oam.as_mut_ptr().add((addr & 0x03FF) as usize);
                 self.write_u32(ptr, val);
             },
-            _ => panic!("[ERR] Invalid 32bit Bus Write Addr ${:#08X}", addr),
+            // Game Pak ROM/Flash (書き込み不可)
+            0x08000000..=0x0DFFFFFF => {},
+            // Game Pak RAM (バックアップ未実装のため無視)
+            0x0E000000..=0x0E00FFFF => {},
+            // 未使用領域 (書き込みは無視)
+            _ => trace!("Unmapped 32bit Bus Write Addr ${:#08X}", addr),
+        }
+    }
+
+    // 現在時刻までに発生したイベントを処理
+    pub fn update(&mut self) {
+        while let Some((event, time)) = self.scheduler.pop_due() {
+            trace!("Event: {:?} @ {} (now: {})", event, time, self.scheduler.now());
+            match event {
+                EventType::TimerOverflow(n) => self.timer_overflow(n as usize, time),
+                EventType::DmaTransfer(n) => self.run_dma(n as usize),
+                EventType::HBlank => self.ppu_hblank(time),
+                EventType::HDraw => self.ppu_hdraw(time),
+                EventType::ApuSample => self.apu_sample(time),
+                // TODO シリアル通信
+                EventType::SerialTransfer => {},
+            }
         }
     }
 
-    pub fn update(&mut self, _tick: u32) {
-        // TODO
+    // サウンド出力 (PSGを進めて1サンプル出力)
+    fn apu_sample(&mut self, time: u64) {
+        self.apu.update(time);
+        self.apu.push_sample();
+        self.scheduler.schedule_at(EventType::ApuSample, time + self.apu.sample_cycles());
+    }
+
+    // HBlank開始 (表示ラインの描画, HBlank DMA)
+    fn ppu_hblank(&mut self, time: u64) {
+        self.ppu.start_hblank(&mut self.interrupt);
+        let vcount: u16 = self.ppu.vcount();
+        if vcount < _VISIBLE_LINES {
+            self.ppu.render_line(&self.vram, &self.pram, &self.oam);
+            self.dma_trigger(DmaTiming::HBlank);
+        }
+        // ビデオキャプチャ (ライン2~161)
+        if (2..162).contains(&vcount) {
+            self.dma_trigger(DmaTiming::Special);
+        }
+        self.scheduler.schedule_at(EventType::HDraw, time + _HBLANK_CYCLES);
+    }
+
+    // HDraw開始 (次のライン, VBlank DMA)
+    fn ppu_hdraw(&mut self, time: u64) {
+        self.ppu.start_hdraw(&mut self.interrupt);
+        match self.ppu.vcount() {
+            _VISIBLE_LINES => self.dma_trigger(DmaTiming::VBlank),
+            162 => self.dma_video_capture_end(),
+            _ => {},
+        }
+        self.scheduler.schedule_at(EventType::HBlank, time + _HDRAW_CYCLES);
+    }
+
+    // 設定が変更されたタイマのオーバーフローを再登録
+    fn schedule_timers(&mut self) {
+        let reschedule: u8 = self.timers.take_reschedule();
+        for n in 0..4 {
+            if (reschedule & (1 << n)) == 0 {
+                continue;
+            }
+            self.scheduler.cancel(EventType::TimerOverflow(n));
+            if let Some(time) = self.timers.next_overflow(n as usize) {
+                self.scheduler.schedule_at(EventType::TimerOverflow(n), time);
+            }
+        }
+    }
+
+    // キー入力の更新 (フロントエンドから呼び出す)
+    pub fn set_keys(&mut self, keys: KeyState) {
+        self.keypad.set_keys(keys);
+        self.check_keypad_irq();
+    }
+
+    // KEYCNTの条件成立でキー割り込み (STOP解除要因)
+    fn check_keypad_irq(&mut self) {
+        if self.keypad.is_irq_condition() {
+            self.interrupt.request(Interrupt::Keypad);
+        }
+    }
+
+    // 即時転送を開始したDMAを登録 (許可から2サイクル後に開始)
+    fn schedule_dma(&mut self) {
+        let start: u8 = self.dma.take_start();
+        for n in 0..4 {
+            if (start & (1 << n)) != 0 {
+                self.scheduler.schedule(EventType::DmaTransfer(n), 2);
+            }
+        }
+    }
+
+    // VBlank/HBlank/ビデオキャプチャ開始時のDMA (優先度順: DMA0 > DMA3)
+    pub fn dma_trigger(&mut self, timing: DmaTiming) {
+        for n in 0..4 {
+            // Special はDMA3(ビデオキャプチャ)のみ
+            if self.dma.is_triggered(n, timing) && (timing != DmaTiming::Special || n == 3) {
+                self.run_dma(n);
+            }
+        }
+    }
+
+    // ビデオキャプチャ終了 (DMA3)
+    pub fn dma_video_capture_end(&mut self) {
+        if self.dma.is_triggered(3, DmaTiming::Special) {
+            self.dma.stop(3);
+        }
+    }
+
+    // サウンドFIFOの補充要求 (DMA1/2)
+    fn dma_fifo_refill(&mut self) {
+        let fifo: [u32; 2] = [_FIFO_A_ADDR, _FIFO_B_ADDR];
+        for (i, addr) in fifo.iter().enumerate() {
+            if !self.apu.take_fifo_request(i) {
+                continue;
+            }
+            if let Some(n) = (1..3).find(|n| self.dma.is_fifo(*n, *addr)) {
+                self.run_dma(n);
+            }
+        }
+    }
+
+    // DMA転送 (CPUは転送終了まで停止, Cycle += 2N + 2(n-1)S + 2I)
+    fn run_dma(&mut self, n: usize) {
+        if !self.dma.is_enabled(n) {
+            return;
+        }
+        let t: DmaTransfer = self.dma.transfer(n);
+        trace!("DMA{}: {:?}", n, t);
+        let width: u32 = if t.word { 4 } else { 2 };
+        let mut src: u32 = t.src;
+        let mut dst: u32 = t.dst;
+        let mut access: MemoryAccess = MemoryAccess::NonSeq;
+        for _ in 0..t.count {
+            let read_cycles: u32 = self.access_cycles(src, width, access);
+            let write_cycles: u32 = self.access_cycles(dst, width, access);
+            self.scheduler.advance((read_cycles + write_cycles) as u64);
+            unsafe {
+                if t.word {
+                    let val: u32 = self.read_word(src & !3);
+                    self.write_word(dst & !3, val);
+                } else {
+                    let val: u16 = self.read_hword(src & !1);
+                    self.write_hword(dst & !1, val);
+                }
+            }
+            src = src.wrapping_add(t.src_step as u32);
+            dst = dst.wrapping_add(t.dst_step as u32);
+            access = MemoryAccess::Seq;
+        }
+        self.scheduler.advance(2);
+        if self.dma.finish(n, src, dst) {
+            self.interrupt.request(Interrupt::dma(n as u8));
+        }
+    }
+
+    // タイマオーバーフロー (割り込み, Direct Sound, 次段タイマのカウントアップ)
+    fn timer_overflow(&mut self, n: usize, time: u64) {
+        if self.timers.overflow(n, time) {
+            self.interrupt.request(Interrupt::timer(n as u8));
+        }
+        if n < 2 {
+            self.apu.timer_overflow(n as u8);
+            self.dma_fifo_refill();
+        }
+        if let Some(next) = self.timers.next_overflow(n) {
+            self.scheduler.schedule_at(EventType::TimerOverflow(n as u8), next);
+        }
+        if n < 3 && self.timers.count_up(n + 1) {
+            self.timer_overflow(n + 1, time);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_access_cycles() {
+        let mut bus = Bus::new();
+        assert_eq!(bus.access_cycles(0x0300_0000, 4, MemoryAccess::NonSeq), 1);
+        assert_eq!(bus.access_cycles(0x0200_0000, 2, MemoryAccess::Seq), 3);
+        assert_eq!(bus.access_cycles(0x0200_0000, 4, MemoryAccess::NonSeq), 6);
+        // WS0 初期値: N=4, S=2
+        assert_eq!(bus.access_cycles(0x0800_0000, 2, MemoryAccess::NonSeq), 5);
+        assert_eq!(bus.access_cycles(0x0800_0000, 2, MemoryAccess::Seq), 3);
+        assert_eq!(bus.access_cycles(0x0800_0000, 4, MemoryAccess::NonSeq), 8);
+        // WAITCNT = 0x4317 (SRAM:8, WS0:3,1, WS1:4,4)
+        bus.set_waitcnt(0x4317);
+        assert_eq!(bus.access_cycles(0x0800_0000, 2, MemoryAccess::NonSeq), 4);
+        assert_eq!(bus.access_cycles(0x0800_0000, 2, MemoryAccess::Seq), 2);
+        assert_eq!(bus.access_cycles(0x0A00_0000, 4, MemoryAccess::Seq), 10);
+        assert_eq!(bus.access_cycles(0x0E00_0000, 1, MemoryAccess::NonSeq), 9);
+    }
+
+    #[test]
+    fn test_bios_protection() {
+        let mut bus = Bus::new();
+        assert!(bus.load_bios(&[0; 16]).is_err());
+        let image: Vec<u8> = (0.._BIOS_SIZE).map(|i| i as u8).collect();
+        bus.load_bios(&image).unwrap();
+
+        // BIOS内実行中は読み出し可能
+        bus.set_fetch_addr(0x0000_0100);
+        assert_eq!(unsafe { bus.read_word(0x0000_0010) }, 0x1312_1110);
+        assert_eq!(unsafe { bus.read_byte(0x0000_0021) }, 0x21);
+        bus.set_fetch_addr(0x0000_0008);
+        assert_eq!(unsafe { bus.read_word(0x0000_0008) }, 0x0B0A_0908);
+        // BIOS内のデータ読み出しはラッチを更新しない
+        assert_eq!(unsafe { bus.read_word(0x0000_0010) }, 0x1312_1110);
+
+        // BIOS外からは最後にフェッチした命令が読める
+        bus.set_fetch_addr(0x0800_0000);
+        assert_eq!(unsafe { bus.read_word(0x0000_0100) }, 0x0B0A_0908);
+        assert_eq!(unsafe { bus.read_hword(0x0000_0102) }, 0x0B0A);
+    }
+
+    #[test]
+    fn test_io_routing() {
+        let mut bus = Bus::new();
+        unsafe {
+            // WAITCNT は32bit書き込みでもウェイトステートに反映
+            bus.write_word(0x0400_0204, 0xFFFF_4317);
+            assert_eq!(bus.waitcnt(), 0x4317);
+            assert_eq!(bus.read_hword(0x0400_0204), 0x4317);
+            assert_eq!(bus.access_cycles(0x0800_0000, 2, MemoryAccess::NonSeq), 4);
+            // IF 確認応答 (1書き込みでクリア)
+            bus.interrupt.request(Interrupt::VBlank);
+            bus.interrupt.request(Interrupt::HBlank);
+            bus.write_hword(0x0400_0202, 0x0001);
+            assert_eq!(bus.read_hword(0x0400_0202), 0x0002);
+            // 書き込み専用レジスタ
+            bus.write_word(0x0400_0010, 0x0001_0020);
+            assert_eq!(bus.read_word(0x0400_0010), 0);
+        }
+    }
+
+    #[test]
+    fn test_timer_overflow() {
+        let mut bus = Bus::new();
+        unsafe {
+            // TM0: Reload=0xFFF0, 1/1, IRQ / TM1: Reload=0xFFFE, カウントアップ, IRQ
+            bus.write_word(0x0400_0104, 0x00C4_FFFE);
+            bus.write_word(0x0400_0100, 0x00C0_FFF0);
+            bus.write_hword(0x0400_0200, 0x0018);
+            bus.scheduler.advance(8);
+            assert_eq!(bus.read_hword(0x0400_0100), 0xFFF8);
+
+            bus.scheduler.advance(8);
+            bus.update();
+            assert_eq!(bus.interrupt.flag(), 0x0008);
+            assert_eq!(bus.read_hword(0x0400_0104), 0xFFFF);
+
+            bus.scheduler.advance(16);
+            bus.update();
+            assert_eq!(bus.interrupt.flag(), 0x0018);
+            assert_eq!(bus.read_hword(0x0400_0104), 0xFFFE);
+            assert_eq!(bus.read_hword(0x0400_0100), 0xFFF0);
+        }
+    }
+
+    #[test]
+    fn test_dma_transfer() {
+        let mut bus = Bus::new();
+        unsafe {
+            for i in 0..8u32 {
+                bus.write_hword(0x0200_0000 + i * 2, 0x1000 + i as u16);
+            }
+            // DMA3: EWRAM → IRAM 8hword, 即時, 終了割り込み
+            bus.write_word(0x0400_00D4, 0x0200_0000);
+            bus.write_word(0x0400_00D8, 0x0300_0100);
+            bus.write_word(0x0400_00DC, 0xC000_0008);
+            bus.write_hword(0x0400_0200, 0x0800);
+            // 許可から2サイクル後に開始
+            bus.scheduler.advance(2);
+            let tick: u64 = bus.scheduler.now();
+            bus.update();
+            // EWRAM(3) + IRAM(1) を8回 + 2I
+            assert_eq!(bus.scheduler.now() - tick, 4 * 8 + 2);
+            assert_eq!(bus.read_hword(0x0300_010E), 0x1007);
+            assert_eq!(bus.read_hword(0x0400_00DE), 0x4000);
+            assert_eq!(bus.interrupt.flag(), 0x0800);
+
+            // DMA1: サウンドFIFO A (Timer0 オーバーフローで補充)
+            bus.write_word(0x0400_00BC, 0x0200_0000);
+            bus.write_word(0x0400_00C0, 0x0400_00A0);
+            bus.write_word(0x0400_00C4, 0xB600_0000);
+            bus.write_hword(0x0400_0082, 0x0000);
+            bus.write_word(0x0400_0100, 0x0080_FF00);
+            bus.scheduler.advance(0x100);
+            bus.update();
+            assert_eq!(bus.apu.fifo_len(0), 16);
+            assert_eq!(bus.apu.fifo_sample(0), 0);
+            bus.scheduler.advance(0x100);
+            bus.update();
+            assert_eq!(bus.apu.fifo_sample(0), 0x00);
+            assert_eq!(bus.apu.fifo_len(0), 31);
+            bus.scheduler.advance(0x100);
+            bus.update();
+            assert_eq!(bus.apu.fifo_sample(0), 0x10);
+        }
+    }
+
+    #[test]
+    fn test_ppu_frame() {
+        let mut bus = Bus::new();
+        unsafe {
+            // VBlank割り込み + VBlank DMA (DMA0: IRAM → EWRAM 1word)
+            bus.write_hword(0x0400_0004, 0x0008);
+            bus.write_word(0x0300_0000, 0x1234_5678);
+            bus.write_word(0x0400_00B0, 0x0300_0000);
+            bus.write_word(0x0400_00B4, 0x0200_0000);
+            bus.write_word(0x0400_00B8, 0x9400_0001);
+
+            bus.scheduler.advance((_HDRAW_CYCLES + _HBLANK_CYCLES) * 160 - 1);
+            bus.update();
+            assert_eq!(bus.read_hword(0x0400_0006), 159);
+            assert_eq!(bus.read_word(0x0200_0000), 0);
+            bus.scheduler.advance(1);
+            bus.update();
+            assert_eq!(bus.read_hword(0x0400_0006), 160);
+            assert_eq!(bus.read_hword(0x0400_0004) & 1, 1);
+            assert_eq!(bus.interrupt.flag(), 0x0001);
+            assert_eq!(bus.read_word(0x0200_0000), 0x1234_5678);
+            assert!(bus.ppu.take_frame());
+
+            // 1フレーム = 280896サイクル
+            bus.scheduler.advance((_HDRAW_CYCLES + _HBLANK_CYCLES) * 228);
+            bus.update();
+            assert_eq!(bus.read_hword(0x0400_0006), 160);
+        }
+    }
+
+    #[test]
+    fn test_memory_mirror() {
+        let mut bus = Bus::new();
+        unsafe {
+            bus.write_word(0x0204_0010, 0x1234_5678);
+            assert_eq!(bus.read_word(0x0200_0010), 0x1234_5678);
+            bus.write_hword(0x03FF_FFFC, 0xABCD);
+            assert_eq!(bus.read_hword(0x0300_7FFC), 0xABCD);
+            bus.write_hword(0x0500_0402, 0x7FFF);
+            assert_eq!(bus.read_hword(0x0500_0002), 0x7FFF);
+            bus.write_hword(0x0700_0C04, 0x0200);
+            assert_eq!(bus.read_hword(0x0700_0004), 0x0200);
+            // VRAM: 0x06018000~0x0601FFFF はOBJ領域のミラー
+            bus.write_hword(0x0601_8000, 0x1111);
+            assert_eq!(bus.read_hword(0x0601_0000), 0x1111);
+            bus.write_hword(0x0602_0004, 0x2222);
+            assert_eq!(bus.read_hword(0x0600_0004), 0x2222);
+        }
+    }
+
+    #[test]
+    fn test_byte_write_quirks() {
+        let mut bus = Bus::new();
+        unsafe {
+            // Palette RAM / BG VRAM は両バイトへ書き込み
+            bus.write_byte(0x0500_0001, 0x12);
+            assert_eq!(bus.read_hword(0x0500_0000), 0x1212);
+            bus.write_byte(0x0600_0002, 0x34);
+            assert_eq!(bus.read_hword(0x0600_0002), 0x3434);
+            // OBJ VRAM / OAM は無視
+            bus.write_byte(0x0601_0000, 0x56);
+            assert_eq!(bus.read_hword(0x0601_0000), 0);
+            bus.write_byte(0x0700_0000, 0x78);
+            assert_eq!(bus.read_hword(0x0700_0000), 0);
+            // ビットマップモードは0x06013FFFまでBG領域
+            bus.write_hword(0x0400_0000, 0x0003);
+            bus.write_byte(0x0601_2001, 0x9A);
+            assert_eq!(bus.read_hword(0x0601_2000), 0x9A9A);
+            bus.write_byte(0x0601_4000, 0x9A);
+            assert_eq!(bus.read_hword(0x0601_4000), 0);
+        }
+    }
+
+    #[test]
+    fn test_unmapped_access() {
+        let mut bus = Bus::new();
+        // 未使用領域 / Game Pak RAM は読み出し0, 書き込み無視 (パニックしない)
+        for &addr in &[0x0000_4000, 0x01FF_FFFC, 0x0400_0400, 0x04FF_FFFC, 0x0E00_0000, 0x1000_0000, 0xFFFF_FFFC] {
+            unsafe {
+                bus.write_byte(addr, 0x12);
+                bus.write_hword(addr, 0x1234);
+                bus.write_word(addr, 0x1234_5678);
+                assert_eq!(bus.read_byte(addr), 0);
+                assert_eq!(bus.read_hword(addr), 0);
+                assert_eq!(bus.read_word(addr), 0);
+            }
+        }
     }
 }
@@ -0,0 +1,230 @@
+// =========================================================================
+// [Game Pak]
+// カートリッジROMの読み込みとヘッダ解析
+// =========================================================================
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// ROMサイズ上限 (32MB)
+pub const _ROM_MAX_SIZE: usize = 32 * 1024 * 1024;
+// ヘッダサイズ (0x00~0xBF)
+const _HEADER_SIZE: usize = 0xC0;
+// ヘッダ固定値 (0xB2)
+const _HEADER_FIXED_VALUE: u8 = 0x96;
+
+// 任天堂ロゴ (0x04~0x9F)
+const _NINTENDO_LOGO: [u8; 156] = [
+    0x24, 0xFF, 0xAE, 0x51, 0x69, 0x9A, 0xA2, 0x21, 0x3D, 0x84, 0x82, 0x0A,
+    0x84, 0xE4, 0x09, 0xAD, 0x11, 0x24, 0x8B, 0x98, 0xC0, 0x81, 0x7F, 0x21,
+    0xA3, 0x52, 0xBE, 0x19, 0x93, 0x09, 0xCE, 0x20, 0x10, 0x46, 0x4A, 0x4A,
+    0xF8, 0x27, 0x31, 0xEC, 0x58, 0xC7, 0xE8, 0x33, 0x82, 0xE3, 0xCE, 0xBF,
+    0x85, 0xF4, 0xDF, 0x94, 0xCE, 0x4B, 0x09, 0xC1, 0x94, 0x56, 0x8A, 0xC0,
+    0x13, 0x72, 0xA7, 0xFC, 0x9F, 0x84, 0x4D, 0x73, 0xA3, 0xCA, 0x9A, 0x61,
+    0x58, 0x97, 0xA3, 0x27, 0xFC, 0x03, 0x98, 0x76, 0x23, 0x1D, 0xC7, 0x61,
+    0x03, 0x04, 0xAE, 0x56, 0xBF, 0x38, 0x84, 0x00, 0x40, 0xA7, 0x0E, 0xFD,
+    0xFF, 0x52, 0xFE, 0x03, 0x6F, 0x95, 0x30, 0xF1, 0x97, 0xFB, 0xC0, 0x85,
+    0x60, 0xD6, 0x80, 0x25, 0xA9, 0x63, 0xBE, 0x03, 0x01, 0x4E, 0x38, 0xE2,
+    0xF9, 0xA2, 0x34, 0xFF, 0xBB, 0x3E, 0x03, 0x44, 0x78, 0x00, 0x90, 0xCB,
+    0x88, 0x11, 0x3A, 0x94, 0x65, 0xC0, 0x7C, 0x63, 0x87, 0xF0, 0x3C, 0xAF,
+    0xD6, 0x25, 0xE4, 0x8B, 0x38, 0x0A, 0xAC, 0x72, 0x21, 0xD4, 0xF8, 0x07,
+];
+
+#[derive(Debug)]
+pub enum CartridgeError {
+    Io(io::Error),                                 // ファイル読み込み失敗
+    TooSmall(usize),                               // ヘッダに満たない
+    TooLarge(usize),                               // 32MB超過
+    InvalidLogo,                                   // 任天堂ロゴ不一致
+    InvalidFixedValue(u8),                         // 0xB2 != 0x96
+    InvalidComplement { expected: u8, actual: u8 }, // ヘッダチェックサム不一致
+}
+
+impl fmt::Display for CartridgeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CartridgeError::Io(e) => write!(f, "ROM read error: {}", e),
+            CartridgeError::TooSmall(size) => write!(f, "ROM too small: {} bytes", size),
+            CartridgeError::TooLarge(size) => write!(f, "ROM too large: {} bytes (max {})", size, _ROM_MAX_SIZE),
+            CartridgeError::InvalidLogo => write!(f, "Nintendo logo mismatch"),
+            CartridgeError::InvalidFixedValue(val) => write!(f, "Invalid header fixed value: {:#04X}", val),
+            CartridgeError::InvalidComplement { expected, actual } =>
+                write!(f, "Header complement check mismatch: expected {:#04X}, got {:#04X}", expected, actual),
+        }
+    }
+}
+
+impl std::error::Error for CartridgeError {}
+
+impl From<io::Error> for CartridgeError {
+    fn from(e: io::Error) -> Self {
+        CartridgeError::Io(e)
+    }
+}
+
+// カートリッジヘッダ
+#[allow(dead_code)]
+#[derive(Debug, Default)]
+pub struct CartridgeHeader {
+    pub entry_point: u32,  // 0x00 ROMエントリポイント (B命令)
+    pub title: String,     // 0xA0 ゲームタイトル (12文字)
+    pub game_code: String, // 0xAC ゲームコード (4文字)
+    pub maker_code: String, // 0xB0 メーカーコード (2文字)
+    pub fixed_value: u8,   // 0xB2 固定値 (0x96)
+    pub unit_code: u8,     // 0xB3 メインユニットコード
+    pub device_type: u8,   // 0xB4 デバイスタイプ
+    pub version: u8,       // 0xBC ソフトバージョン
+    pub complement: u8,    // 0xBD ヘッダチェックサム
+}
+
+impl CartridgeHeader {
+    fn parse(rom: &[u8]) -> Self {
+        let ascii = |range: std::ops::Range<usize>| -> String {
+            rom[range].iter()
+                .take_while(|c| **c != 0)
+                .map(|c| *c as char)
+                .collect()
+        };
+        CartridgeHeader {
+            entry_point: u32::from_le_bytes([rom[0], rom[1], rom[2], rom[3]]),
+            title: ascii(0xA0..0xAC),
+            game_code: ascii(0xAC..0xB0),
+            maker_code: ascii(0xB0..0xB2),
+            fixed_value: rom[0xB2],
+            unit_code: rom[0xB3],
+            device_type: rom[0xB4],
+            version: rom[0xBC],
+            complement: rom[0xBD],
+        }
+    }
+}
+
+// ヘッダチェックサム: -(0xA0~0xBCの和) - 0x19
+pub fn header_complement(rom: &[u8]) -> u8 {
+    let sum: u8 = rom[0xA0..=0xBC].iter().fold(0u8, |acc, b| acc.wrapping_add(*b));
+    0u8.wrapping_sub(sum).wrapping_sub(0x19)
+}
+
+pub struct Cartridge {
+    rom: Vec<u8>,
+    pub header: CartridgeHeader,
+}
+
+#[allow(dead_code)]
+impl Cartridge {
+    // カートリッジ未挿入 (全域オープンバス)
+    pub fn empty() -> Self {
+        Cartridge {
+            rom: Vec::new(),
+            header: CartridgeHeader::default(),
+        }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, CartridgeError> {
+        let data: Vec<u8> = fs::read(path)?;
+        Cartridge::from_bytes(data)
+    }
+
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, CartridgeError> {
+        if data.len() < _HEADER_SIZE {
+            return Err(CartridgeError::TooSmall(data.len()));
+        }
+        if data.len() > _ROM_MAX_SIZE {
+            return Err(CartridgeError::TooLarge(data.len()));
+        }
+        let header: CartridgeHeader = CartridgeHeader::parse(&data);
+        Ok(Cartridge { rom: data, header })
+    }
+
+    // ヘッダ検証 (ロゴ, 固定値, チェックサム)
+    pub fn validate(&self) -> Result<(), CartridgeError> {
+        if self.rom.len() < _HEADER_SIZE {
+            return Err(CartridgeError::TooSmall(self.rom.len()));
+        }
+        if self.rom[0x04..0xA0] != _NINTENDO_LOGO[..] {
+            return Err(CartridgeError::InvalidLogo);
+        }
+        if self.header.fixed_value != _HEADER_FIXED_VALUE {
+            return Err(CartridgeError::InvalidFixedValue(self.header.fixed_value));
+        }
+        let expected: u8 = header_complement(&self.rom);
+        if self.header.complement != expected {
+            return Err(CartridgeError::InvalidComplement { expected, actual: self.header.complement });
+        }
+        Ok(())
+    }
+
+    pub fn size(&self) -> usize {
+        self.rom.len()
+    }
+
+    // ROM読み出し (WS0/WS1/WS2 のミラーは下位25bitで共通)
+    // ROM範囲外はアドレス由来のオープンバス値 ((Addr >> 1) & 0xFFFF)
+    pub fn read_hword(&self, addr: u32) -> u16 {
+        let offset: usize = (addr & 0x01FF_FFFE) as usize;
+        if offset + 1 < self.rom.len() {
+            u16::from_le_bytes([self.rom[offset], self.rom[offset + 1]])
+        } else {
+            ((offset >> 1) & 0xFFFF) as u16
+        }
+    }
+
+    pub fn read_byte(&self, addr: u32) -> u8 {
+        (self.read_hword(addr) >> ((addr & 1) * 8)) as u8
+    }
+
+    pub fn read_word(&self, addr: u32) -> u32 {
+        let addr: u32 = addr & !3;
+        (self.read_hword(addr) as u32) | ((self.read_hword(addr + 2) as u32) << 16)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_rom() -> Vec<u8> {
+        let mut rom: Vec<u8> = vec![0; 0x200];
+        rom[0x00..0x04].copy_from_slice(&0xEA00_002Eu32.to_le_bytes());
+        rom[0x04..0xA0].copy_from_slice(&_NINTENDO_LOGO);
+        rom[0xA0..0xA8].copy_from_slice(b"RSGBATST");
+        rom[0xAC..0xB0].copy_from_slice(b"ATSE");
+        rom[0xB0..0xB2].copy_from_slice(b"01");
+        rom[0xB2] = _HEADER_FIXED_VALUE;
+        rom[0xBC] = 1;
+        rom[0xBD] = header_complement(&rom);
+        rom
+    }
+
+    #[test]
+    fn test_cartridge_header() {
+        let cart = Cartridge::from_bytes(make_rom()).unwrap();
+        assert!(cart.validate().is_ok());
+        assert_eq!(cart.header.title, "RSGBATST");
+        assert_eq!(cart.header.game_code, "ATSE");
+        assert_eq!(cart.header.maker_code, "01");
+        assert_eq!(cart.header.version, 1);
+
+        let mut rom = make_rom();
+        rom[0xBD] ^= 0xFF;
+        assert!(matches!(Cartridge::from_bytes(rom).unwrap().validate(), Err(CartridgeError::InvalidComplement { .. })));
+        let mut rom = make_rom();
+        rom[0x10] = 0;
+        assert!(matches!(Cartridge::from_bytes(rom).unwrap().validate(), Err(CartridgeError::InvalidLogo)));
+        assert!(matches!(Cartridge::from_bytes(vec![0; 0x10]), Err(CartridgeError::TooSmall(0x10))));
+    }
+
+    #[test]
+    fn test_cartridge_read() {
+        let cart = Cartridge::from_bytes(make_rom()).unwrap();
+        // ミラー
+        assert_eq!(cart.read_word(0x0800_0000), 0xEA00_002E);
+        assert_eq!(cart.read_word(0x0A00_0000), 0xEA00_002E);
+        assert_eq!(cart.read_byte(0x0C00_00A1), b'S');
+        // オープンバス
+        assert_eq!(cart.read_hword(0x0800_1000), 0x0800);
+        assert_eq!(cart.read_word(0x0800_1000), 0x0801_0800);
+        assert_eq!(cart.read_byte(0x0800_1001), 0x08);
+    }
+}
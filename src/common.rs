@@ -34,6 +34,7 @@ pub const _BIT_29: u32 = 0x20000000;
 pub const _BIT_30: u32 = 0x40000000;
 pub const _BIT_31: u32 = 0x80000000;
 
+#[allow(clippy::identity_op)]
 pub const _MEM_SIZE_1K:   usize =   1 * 1024;
 pub const _MEM_SIZE_2K:   usize =   2 * 1024;
 pub const _MEM_SIZE_4K:   usize =   4 * 1024;
@@ -56,8 +57,10 @@ pub const _SCREEN_W: u8 = 240;
 pub const _SCREEN_H: u8 = 160;
 
 // =========================================================================
+#[allow(dead_code)]
 pub trait IO {
     fn read(&mut self, _addr: u32) -> u8;
     fn write(&mut self, _addr: u32, _val: u8);
-    fn update(&mut self, _tick: u32);
+    // 指定時刻(マスタクロック)まで状態を進める
+    fn update(&mut self, _now: u64);
 }
\ No newline at end of file
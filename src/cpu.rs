@@ -2,6 +2,12 @@ use common::*;
 use thumb_op::*;
 use arm_op::*;
 use bus::*;
+use bios;
+use keypad::KeyState;
+#[cfg(test)]
+use interrupt::*;
+#[cfg(test)]
+use scheduler::*;
 use bitflags::bitflags;
 
 bitflags! {
@@ -20,35 +26,203 @@ bitflags! {
         const MODE_FIQ  = 0b0001_0001;  // FIQ Mode
         const MODE_IRQ  = 0b0001_0010;  // IRQ Mode
         const MODE_SVC  = 0b0001_0011;  // Supervisor Mode
-        const MODE_ABT  = 0b0001_0101;  // Abort Mode
-        const MODE_UDF  = 0b0001_0111;  // Undefined Mode
+        const MODE_ABT  = 0b0001_0111;  // Abort Mode
+        const MODE_UDF  = 0b0001_1011;  // Undefined Mode
         const MODE_SYS  = 0b0001_1111;  // System Mode
     }
 }
 
+// モードビット Bit[4:0]
+const _MODE_MASK: u32 = 0b1_1111;
+
+// 例外の種類
+#[allow(dead_code)]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Exception {
+    Reset,              // リセット
+    Undefined,          // 未定義命令
+    SoftwareInterrupt,  // ソフトウェア割り込み(SWI)
+    PrefetchAbort,      // プリフェッチアボート
+    DataAbort,          // データアボート
+    IRQ,                // 割り込み要求
+    FIQ,                // 高速割り込み要求
+}
+
+impl Exception {
+    // 例外ベクタアドレス
+    fn vector(&self) -> u32 {
+        match self {
+            Exception::Reset             => 0x00,
+            Exception::Undefined         => 0x04,
+            Exception::SoftwareInterrupt => 0x08,
+            Exception::PrefetchAbort     => 0x0C,
+            Exception::DataAbort         => 0x10,
+            Exception::IRQ               => 0x18,
+            Exception::FIQ               => 0x1C,
+        }
+    }
+
+    // 例外処理の動作モード
+    fn mode(&self) -> PSR {
+        match self {
+            Exception::Reset             => PSR::MODE_SVC,
+            Exception::Undefined         => PSR::MODE_UDF,
+            Exception::SoftwareInterrupt => PSR::MODE_SVC,
+            Exception::PrefetchAbort     => PSR::MODE_ABT,
+            Exception::DataAbort         => PSR::MODE_ABT,
+            Exception::IRQ               => PSR::MODE_IRQ,
+            Exception::FIQ               => PSR::MODE_FIQ,
+        }
+    }
+}
+
 // ARM7TDMI レジスタ構造体
 #[allow(dead_code)]
 pub struct Register {
-    pub r: [u32; 12],     // 汎用レジスタ R0~R12
-    pub sp: u32,          // R13(SP)
-    pub lr: u32,          // R14(LR)
-    pub pc: u32,          // R15(PC)
-    pub cpsr: PSR,        // 現在ステータスレジスタ
-    pub spsr: [PSR; 5],   // 保存ステータスレジスタ(FIQ,SVC,Abort,IRQ,Undefined)
+    pub r: [u32; 13],          // 汎用レジスタ R0~R12
+    sp: u32,                   // R13(SP) (reg/set_reg経由でアクセス)
+    lr: u32,                   // R14(LR) (reg/set_reg経由でアクセス)
+    pub pc: u32,               // R15(PC)
+    pub cpsr: PSR,             // 現在ステータスレジスタ
+    pub spsr: [PSR; 5],        // 保存ステータスレジスタ(FIQ,SVC,Abort,IRQ,Undefined)
+    r8_12_usr: [u32; 5],       // バンクレジスタ R8~R12 (FIQ以外)
+    r8_12_fiq: [u32; 5],       // バンクレジスタ R8_fiq~R12_fiq
+    sp_bank: [u32; 6],         // バンクレジスタ R13 (User/System,FIQ,SVC,Abort,IRQ,Undefined)
+    lr_bank: [u32; 6],         // バンクレジスタ R14 (User/System,FIQ,SVC,Abort,IRQ,Undefined)
 }
 
 impl Register {
     pub fn new() -> Self {
         Register {
-            r: [0; 12],
+            r: [0; 13],
             sp: 0,
             lr: 0,
             pc: 0,
-            cpsr: PSR::empty(),
+            // リセット直後はSVCモード, IRQ/FIQ禁止, ARMステート
+            cpsr: PSR::MODE_SVC | PSR::I | PSR::F,
             spsr: [PSR::empty(); 5],
+            r8_12_usr: [0; 5],
+            r8_12_fiq: [0; 5],
+            sp_bank: [0; 6],
+            lr_bank: [0; 6],
+        }
+    }
+
+    // 現在の動作モード (CPSR Bit[4:0])
+    pub fn mode(&self) -> PSR {
+        PSR::from_bits_retain(self.cpsr.bits() & _MODE_MASK)
+    }
+
+    // モードに対応するバンク番号 (0:User/System, 1:FIQ, 2:SVC, 3:Abort, 4:IRQ, 5:Undefined)
+    fn bank_index(mode: PSR) -> usize {
+        match mode.bits() & _MODE_MASK {
+            0b1_0001 => 1, // FIQ
+            0b1_0011 => 2, // SVC
+            0b1_0111 => 3, // Abort
+            0b1_0010 => 4, // IRQ
+            0b1_1011 => 5, // Undefined
+            _ => 0,        // User/System
+        }
+    }
+
+    // R0~R15 の読み出し (現在モードのバンク)
+    pub fn reg(&self, n: u8) -> u32 {
+        match n {
+            0..=12 => self.r[n as usize],
+            13 => self.sp,
+            14 => self.lr,
+            15 => self.pc,
+            _ => panic!("Invalid reg() arg: {}", n),
         }
     }
 
+    // R0~R15 の書き込み (現在モードのバンク)
+    pub fn set_reg(&mut self, n: u8, val: u32) {
+        match n {
+            0..=12 => self.r[n as usize] = val,
+            13 => self.sp = val,
+            14 => self.lr = val,
+            15 => self.pc = val,
+            _ => panic!("Invalid set_reg() arg: {}", n),
+        }
+    }
+
+    // User/Systemモードのバンクの読み出し (LDM/STM S=1 のユーザバンク転送)
+    pub fn user_reg(&self, n: u8) -> u32 {
+        let is_fiq: bool = Register::bank_index(self.mode()) == 1;
+        match n {
+            8..=12 if is_fiq => self.r8_12_usr[(n - 8) as usize],
+            13 if Register::bank_index(self.mode()) != 0 => self.sp_bank[0],
+            14 if Register::bank_index(self.mode()) != 0 => self.lr_bank[0],
+            _ => self.reg(n),
+        }
+    }
+
+    // User/Systemモードのバンクへの書き込み
+    pub fn set_user_reg(&mut self, n: u8, val: u32) {
+        let is_fiq: bool = Register::bank_index(self.mode()) == 1;
+        match n {
+            8..=12 if is_fiq => self.r8_12_usr[(n - 8) as usize] = val,
+            13 if Register::bank_index(self.mode()) != 0 => self.sp_bank[0] = val,
+            14 if Register::bank_index(self.mode()) != 0 => self.lr_bank[0] = val,
+            _ => self.set_reg(n, val),
+        }
+    }
+
+    // 動作モード切り替え (バンクレジスタの入れ替えとCPSRのモードビット更新)
+    pub fn switch_mode(&mut self, new_mode: PSR) {
+        let old_bank: usize = Register::bank_index(self.mode());
+        let new_bank: usize = Register::bank_index(new_mode);
+
+        if old_bank != new_bank {
+            // R13/R14 を退避して切り替え先のバンクを復帰
+            self.sp_bank[old_bank] = self.sp;
+            self.lr_bank[old_bank] = self.lr;
+            self.sp = self.sp_bank[new_bank];
+            self.lr = self.lr_bank[new_bank];
+
+            // FIQモードの出入りは R8~R12 も入れ替える
+            if old_bank == 1 {
+                self.r8_12_fiq.copy_from_slice(&self.r[8..13]);
+                self.r[8..13].copy_from_slice(&self.r8_12_usr);
+            } else if new_bank == 1 {
+                self.r8_12_usr.copy_from_slice(&self.r[8..13]);
+                self.r[8..13].copy_from_slice(&self.r8_12_fiq);
+            }
+        }
+
+        let psr: u32 = (self.cpsr.bits() & !_MODE_MASK) | (new_mode.bits() & _MODE_MASK);
+        self.cpsr = PSR::from_bits_retain(psr);
+    }
+
+    // CPSR 書き込み (モードが変わる場合はバンクも切り替える)
+    pub fn set_cpsr(&mut self, val: PSR) {
+        self.switch_mode(val);
+        self.cpsr = val;
+    }
+
+    // 現在モードのSPSR (User/Systemモードは SPSR が無いので CPSR を返す)
+    pub fn spsr(&self) -> PSR {
+        match Register::bank_index(self.mode()) {
+            0 => self.cpsr,
+            idx => self.spsr[idx - 1],
+        }
+    }
+
+    // 現在モードのSPSR 書き込み (User/Systemモードは無視)
+    pub fn set_spsr(&mut self, val: PSR) {
+        match Register::bank_index(self.mode()) {
+            0 => {},
+            idx => self.spsr[idx - 1] = val,
+        }
+    }
+
+    // SPSR -> CPSR 復帰 (例外からの復帰)
+    pub fn restore_cpsr(&mut self) {
+        let spsr: PSR = self.spsr();
+        self.set_cpsr(spsr);
+    }
+
     #[allow(dead_code)]
     // (DEBUG) Trace Debug用　レジスタ名取得
     fn get_reg_str(idx: u8) -> String {
@@ -78,55 +252,331 @@ impl Register {
 
 #[allow(dead_code)]
 pub struct CPU {
-    bus: Bus,
+    pub bus: Bus,
     pub reg: Register,
-    pub tick: u32,
+    pipeline: [u32; 2],    // 3段パイプライン (0:デコード段, 1:フェッチ段)
+    pipeline_flush: bool,  // PC書き換えによるパイプラインフラッシュ要求
+    pub hle_bios: bool,    // BIOSイメージ無し (SWIを高レベルエミュレーション)
+    pub hle_intr_wait: bool, // HLE IntrWait 待機中 (SWI再実行時は引数を再設定しない)
 }
 
 #[allow(dead_code)]
 impl CPU {
     pub fn new() -> Self {
-        CPU {
+        let mut cpu = CPU {
             bus: Bus::new(),
             reg: Register::new(),
-            tick: 0,
+            pipeline: [0; 2],
+            // 起動時はリセットベクタからパイプラインを充填する
+            pipeline_flush: true,
+            hle_bios: false,
+            hle_intr_wait: false,
+        };
+        // BIOSイメージが読み込まれるまではHLE
+        cpu.use_hle_bios();
+        cpu
+    }
+
+    // BIOSイメージの読み込み (実BIOSでSWIを処理する)
+    pub fn load_bios(&mut self, image: &[u8]) -> std::io::Result<()> {
+        self.bus.load_bios(image)?;
+        self.hle_bios = false;
+        Ok(())
+    }
+
+    // HLE BIOSに切り替え (疑似BIOSイメージはIRQハンドラのみ)
+    pub fn use_hle_bios(&mut self) {
+        self.bus.load_bios(&bios::hle_bios_image()).unwrap();
+        self.hle_bios = true;
+    }
+
+    // SWI (HLE時はBIOSを経由せずに処理)
+    pub fn software_interrupt(&mut self, comment: u8) {
+        if self.hle_bios {
+            bios::swi(self, comment);
+        } else {
+            self.raise_exception(Exception::SoftwareInterrupt);
         }
     }
 
-    // Thumb命令(16bit)
-    fn op_thumb(&mut self) {
-        unsafe {
-            // Fetch Thumb
-            let _instruction: u16 = self.bus.read_hword(self.reg.pc);
-            // Decode Thumb
-            let (_format, _format_data) = thumb_op_decode(self,_instruction);
-            // Exec Thumb
-            thumb_op_exec(self, _format, _format_data);
+    // キー入力 (フロントエンド共通の入力API)
+    pub fn set_keys(&mut self, keys: KeyState) {
+        self.bus.set_keys(keys);
+    }
+
+    // 命令幅 (ARM:4byte, Thumb:2byte)
+    fn op_width(&self) -> u32 {
+        if self.reg.cpsr.contains(PSR::T) { 2 } else { 4 }
+    }
+
+    // フェッチ (Cycle += 1S, 分岐直後は1N)
+    fn fetch(&mut self, addr: u32, access: MemoryAccess) -> u32 {
+        self.bus.set_fetch_addr(addr);
+        if self.reg.cpsr.contains(PSR::T) {
+            self.read_hword(addr, access) as u32
+        } else {
+            self.read_word(addr, access)
         }
     }
 
+    // パイプラインの再充填 (分岐先から2命令フェッチ、PCは分岐先+2命令)
+    fn refill_pipeline(&mut self) {
+        let width: u32 = self.op_width();
+        let pc: u32 = self.reg.pc;
+        self.pipeline[0] = self.fetch(pc, MemoryAccess::NonSeq);
+        self.pipeline[1] = self.fetch(pc.wrapping_add(width), MemoryAccess::Seq);
+        self.reg.pc = pc.wrapping_add(width * 2);
+        self.pipeline_flush = false;
+    }
+
+    // 現在時刻 (マスタクロック)
+    pub fn tick(&self) -> u64 {
+        self.bus.scheduler.now()
+    }
+
+    // 内部サイクル (Cycle += nI)
+    pub fn idle(&mut self, cycles: u32) {
+        self.bus.scheduler.advance(cycles as u64);
+    }
+
+    // PC書き込み (パイプラインフラッシュ)
+    pub fn set_pc(&mut self, addr: u32) {
+        self.reg.pc = if self.reg.cpsr.contains(PSR::T) { addr & !1 } else { addr & !3 };
+        self.pipeline_flush = true;
+    }
+
+    // R0~R15 の書き込み (R15はパイプラインフラッシュ)
+    pub fn set_reg(&mut self, n: u8, val: u32) {
+        if n == 15 {
+            self.set_pc(val);
+        } else {
+            self.reg.set_reg(n, val);
+        }
+    }
+
+    // Thumb命令(16bit)
+    fn op_thumb(&mut self, _instruction: u16) {
+        // Decode Thumb
+        let (_format, _format_data) = thumb_op_decode(self,_instruction);
+        // Exec Thumb
+        thumb_op_exec(self, _format, _format_data);
+    }
+
     // ARM命令(32bit)
-    fn op_arm(&mut self) {
-        unsafe {
-            // Fetch ARM
-            let _op: u32 = ((self.bus.read_hword(self.reg.pc) as u32) << 16) |
-                            (self.bus.read_hword(self.reg.pc + 1) as u32);
-            // Decode ARM
-            arm_op_decode(self, _op);
-            // Exec ARM
-            arm_op_exec(self, _op);
+    fn op_arm(&mut self, _op: u32) {
+        // Decode ARM
+        let (_format, _format_data) = arm_op_decode(self, _op);
+        // Exec ARM (条件フィールド Bit[31:28] が成立した場合のみ)
+        if self.check_cond((_op >> 28) as u8) {
+            arm_op_exec(self, _format, _format_data);
         }
     }
 
+    // 1命令実行し、発生したイベントを処理する
     pub fn proc(&mut self) {
+        if !self.bus.is_halted() {
+            self.step();
+        } else if self.bus.power_mode() == PowerMode::Halt {
+            // HALT中は次のイベントまで早送り
+            let deadline: u64 = self.bus.scheduler.next_deadline();
+            if deadline != u64::MAX {
+                self.bus.scheduler.advance_to(deadline);
+            }
+        }
+        if self.bus.scheduler.is_pending() {
+            self.bus.update();
+        }
+    }
+
+    // STOP中か (外部割り込みで解除されていれば通常動作へ戻る)
+    pub fn is_stopped(&mut self) -> bool {
+        self.bus.is_halted() && self.bus.power_mode() == PowerMode::Stop
+    }
+
+    // 指定サイクル分実行 (命令が新たにイベントを登録した場合も含めて、期限に達したら即座に処理する)
+    // STOP中は指定サイクルに達する前に戻る
+    pub fn run(&mut self, cycles: u64) {
+        let target: u64 = self.tick() + cycles;
+        while self.tick() < target {
+            let deadline: u64 = self.bus.scheduler.next_deadline().min(target);
+            while self.tick() < deadline {
+                if self.bus.is_halted() {
+                    // STOP中はクロック停止 (外部割り込みで解除されるまで時間を進めない)
+                    if self.bus.power_mode() == PowerMode::Stop {
+                        return;
+                    }
+                    // HALT中は次のイベントまで早送り (HALT直前の命令が登録したイベントも含める)
+                    let next: u64 = self.bus.scheduler.next_deadline().min(target);
+                    self.bus.scheduler.advance_to(next);
+                    break;
+                }
+                self.step();
+                // 即時DMAやタイマ開始など、命令が登録した直近のイベントに備える
+                if self.bus.scheduler.is_pending() {
+                    break;
+                }
+            }
+            self.bus.update();
+        }
+    }
+
+    // 1命令実行
+    fn step(&mut self) {
+        if self.pipeline_flush {
+            self.refill_pipeline();
+        }
+
+        // IRQ受付 (命令の境界でIME/IE/IFが成立し、CPSR.I = 0 の場合)
+        if self.bus.interrupt.irq_line() && !self.reg.cpsr.contains(PSR::I) {
+            self.raise_exception(Exception::IRQ);
+            self.refill_pipeline();
+        }
+
+        // Fetch: 実行中はR15 = 実行中命令+8 (Thumb:+4)
+        let _op: u32 = self.pipeline[0];
+        self.pipeline[0] = self.pipeline[1];
+        self.pipeline[1] = self.fetch(self.reg.pc, MemoryAccess::Seq);
+
         // ステータのTフラグ(Bit5)でThumb/ARM命令切り替え
+        // Decode & Execute
         match self.reg.cpsr.contains(PSR::T) {
-            // Fetch & Decode & Execute
-            false => self.op_thumb(),
-            true => self.op_arm(),
+            true => self.op_thumb(_op as u16),
+            false => self.op_arm(_op),
+        }
+
+        // PCが書き換えられた場合はフラッシュして分岐先から再充填 (Cycle += 1N+1S)
+        if self.pipeline_flush {
+            self.refill_pipeline();
+        } else {
+            self.reg.pc = self.reg.pc.wrapping_add(self.op_width());
+        }
+    }
+
+    // リセット
+    pub fn reset(&mut self) {
+        self.raise_exception(Exception::Reset);
+    }
+
+    // 例外発生
+    // CPSRを例外モードのSPSRへ退避し、LRに戻りアドレスを設定してベクタへ分岐する
+    // (reg.pcはパイプラインにより 実行中命令+8 (Thumb:+4) を指している)
+    pub fn raise_exception(&mut self, kind: Exception) {
+        let width: u32 = self.op_width();
+        let pc: u32 = self.reg.pc;
+        let cpsr: PSR = self.reg.cpsr;
+
+        // 戻りアドレス (ARM/Thumb)
+        //   SWI/未定義命令  : 命令+4 / 命令+2 (次の命令)
+        //   プリフェッチアボート: 命令+4
+        //   データアボート   : 命令+8
+        //   IRQ/FIQ       : 次に実行する命令+4
+        let lr: u32 = match kind {
+            Exception::Reset => 0,
+            Exception::Undefined | Exception::SoftwareInterrupt => pc.wrapping_sub(width),
+            Exception::PrefetchAbort | Exception::IRQ | Exception::FIQ => pc.wrapping_sub(width * 2).wrapping_add(4),
+            Exception::DataAbort => pc.wrapping_sub(width * 2).wrapping_add(8),
+        };
+
+        self.reg.switch_mode(kind.mode());
+        if kind != Exception::Reset {
+            self.reg.set_spsr(cpsr);
+            self.reg.set_reg(14, lr);
+        }
+
+        // ARMステート, IRQ禁止 (Reset/FIQはFIQも禁止)
+        self.reg.cpsr.remove(PSR::T);
+        self.reg.cpsr.insert(PSR::I);
+        if kind == Exception::Reset || kind == Exception::FIQ {
+            self.reg.cpsr.insert(PSR::F);
+        }
+        self.set_pc(kind.vector()); // Cycle += 2S+1N
+    }
+
+    // 条件フィールド判定 (ARM Bit[31:28] / Thumb Format16 Cond)
+    pub fn check_cond(&self, cond: u8) -> bool {
+        let n = self.reg.cpsr.contains(PSR::N);
+        let z = self.reg.cpsr.contains(PSR::Z);
+        let c = self.reg.cpsr.contains(PSR::C);
+        let v = self.reg.cpsr.contains(PSR::V);
+        match cond {
+            0x0 => z,               // EQ
+            0x1 => !z,              // NE
+            0x2 => c,               // CS
+            0x3 => !c,              // CC
+            0x4 => n,               // MI
+            0x5 => !n,              // PL
+            0x6 => v,               // VS
+            0x7 => !v,              // VC
+            0x8 => c && !z,         // HI
+            0x9 => !c || z,         // LS
+            0xA => n == v,          // GE
+            0xB => n != v,          // LT
+            0xC => !z && (n == v),  // GT
+            0xD => z || (n != v),   // LE
+            0xE => true,            // AL
+            _ => false,             // NV (ARMv4では未定義)
         }
+    }
+
+    // バスアクセス (ハーフワード/ワードはアライメントに切り捨て、アクセスサイクルを加算)
+    pub fn read_byte(&mut self, addr: u32, access: MemoryAccess) -> u8 {
+        self.idle(self.bus.access_cycles(addr, 1, access));
+        unsafe { self.bus.read_byte(addr) }
+    }
 
-        self.bus.update(self.tick);
+    pub fn read_hword(&mut self, addr: u32, access: MemoryAccess) -> u16 {
+        self.idle(self.bus.access_cycles(addr, 2, access));
+        unsafe { self.bus.read_hword(addr & !1) }
+    }
+
+    pub fn read_word(&mut self, addr: u32, access: MemoryAccess) -> u32 {
+        self.idle(self.bus.access_cycles(addr, 4, access));
+        unsafe { self.bus.read_word(addr & !3) }
+    }
+
+    pub fn write_byte(&mut self, addr: u32, val: u8, access: MemoryAccess) {
+        self.idle(self.bus.access_cycles(addr, 1, access));
+        unsafe { self.bus.write_byte(addr, val) }
+    }
+
+    pub fn write_hword(&mut self, addr: u32, val: u16, access: MemoryAccess) {
+        self.idle(self.bus.access_cycles(addr, 2, access));
+        unsafe { self.bus.write_hword(addr & !1, val) }
+    }
+
+    pub fn write_word(&mut self, addr: u32, val: u32, access: MemoryAccess) {
+        self.idle(self.bus.access_cycles(addr, 4, access));
+        unsafe { self.bus.write_word(addr & !3, val) }
+    }
+
+    // 加算 (ADD/ADC/CMN) Rn + Op2 + Carry
+    pub fn alu_add(&mut self, a: u32, b: u32, carry: bool, set_flags: bool) -> u32 {
+        let ret64: u64 = (a as u64) + (b as u64) + (carry as u64);
+        let ret: u32 = ret64 as u32;
+        if set_flags {
+            let is_carry: bool = ret64 > 0xFFFF_FFFF;
+            let is_ovf: bool = ((!(a ^ b)) & (a ^ ret) & _BIT_31) != 0;
+            self.psr_op_update(ret, is_carry, is_ovf);
+        }
+        ret
+    }
+
+    // 減算 (SUB/SBC/RSB/RSC/CMP) Rn - Op2 - !Carry (C = 借りが無い)
+    pub fn alu_sub(&mut self, a: u32, b: u32, carry: bool, set_flags: bool) -> u32 {
+        let borrow: u64 = (!carry) as u64;
+        let ret: u32 = a.wrapping_sub(b).wrapping_sub(borrow as u32);
+        if set_flags {
+            let is_carry: bool = (a as u64) >= (b as u64) + borrow;
+            let is_ovf: bool = ((a ^ b) & (a ^ ret) & _BIT_31) != 0;
+            self.psr_op_update(ret, is_carry, is_ovf);
+        }
+        ret
+    }
+
+    // 論理演算のフラグ更新 (N,Z + シフタキャリーアウト, Vは維持)
+    pub fn psr_logic_update(&mut self, ret: u32, is_carry: bool) {
+        let is_ovf: bool = self.reg.cpsr.contains(PSR::V);
+        self.psr_op_update(ret, is_carry, is_ovf);
     }
 
     pub fn psr_op_update(&mut self, ret: u32, is_carry: bool, is_ovf: bool) {
@@ -160,3 +610,199 @@ impl CPU {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_switch_mode_banked_registers() {
+        let mut reg = Register::new();
+        reg.switch_mode(PSR::MODE_SYS);
+        for n in 0..15u8 {
+            reg.set_reg(n, n as u32);
+        }
+
+        // FIQ: R8~R14がバンク切り替え
+        reg.switch_mode(PSR::MODE_FIQ);
+        assert_eq!(reg.reg(7), 7);
+        assert_eq!(reg.reg(8), 0);
+        assert_eq!(reg.reg(13), 0);
+        assert_eq!(reg.user_reg(8), 8);
+        assert_eq!(reg.user_reg(13), 13);
+        reg.set_reg(8, 0x88);
+        reg.set_reg(13, 0xDD);
+
+        // IRQ: R13~R14のみバンク切り替え
+        reg.switch_mode(PSR::MODE_IRQ);
+        assert_eq!(reg.reg(8), 8);
+        assert_eq!(reg.reg(13), 0);
+        reg.set_spsr(PSR::MODE_SYS | PSR::C);
+
+        reg.switch_mode(PSR::MODE_FIQ);
+        assert_eq!(reg.reg(8), 0x88);
+        assert_eq!(reg.reg(13), 0xDD);
+
+        // SPSR復帰でモードとバンクが戻る
+        reg.switch_mode(PSR::MODE_IRQ);
+        reg.restore_cpsr();
+        assert_eq!(reg.mode().bits(), PSR::MODE_SYS.bits());
+        assert!(reg.cpsr.contains(PSR::C));
+        assert_eq!(reg.reg(13), 13);
+        assert_eq!(reg.reg(14), 14);
+    }
+
+    #[test]
+    fn test_raise_exception() {
+        let mut cpu = CPU::new();
+        // Thumbステート/Systemモードで 0x08000100 の SWI を実行中 (PC=命令+4)
+        cpu.reg.set_cpsr(PSR::MODE_SYS | PSR::T | PSR::Z);
+        cpu.reg.pc = 0x0800_0104;
+        cpu.raise_exception(Exception::SoftwareInterrupt);
+        assert_eq!(cpu.reg.mode().bits(), PSR::MODE_SVC.bits());
+        assert_eq!(cpu.reg.pc, 0x08);
+        assert_eq!(cpu.reg.reg(14), 0x0800_0102);
+        assert!(!cpu.reg.cpsr.contains(PSR::T));
+        assert!(cpu.reg.cpsr.contains(PSR::I));
+        assert_eq!(cpu.reg.spsr().bits(), (PSR::MODE_SYS | PSR::T | PSR::Z).bits());
+
+        // ARMステートで次に 0x08000200 を実行する時点のIRQ (PC=命令+8)
+        cpu.reg.set_cpsr(PSR::MODE_SYS);
+        cpu.reg.pc = 0x0800_0208;
+        cpu.raise_exception(Exception::IRQ);
+        assert_eq!(cpu.reg.mode().bits(), PSR::MODE_IRQ.bits());
+        assert_eq!(cpu.reg.pc, 0x18);
+        assert_eq!(cpu.reg.reg(14), 0x0800_0204);
+    }
+
+    #[test]
+    fn test_pipeline() {
+        let mut cpu = CPU::new();
+        cpu.write_word(0x0300_0000, 0xE1A0_000F, MemoryAccess::NonSeq); // MOV R0, PC
+        cpu.write_word(0x0300_0004, 0xEA00_0000, MemoryAccess::NonSeq); // B 0x0300000C
+        cpu.write_word(0x0300_0008, 0xE3A0_1001, MemoryAccess::NonSeq); // MOV R1, #1
+        cpu.write_word(0x0300_000C, 0xE3A0_2002, MemoryAccess::NonSeq); // MOV R2, #2
+        cpu.set_pc(0x0300_0000);
+
+        cpu.proc();
+        assert_eq!(cpu.reg.reg(0), 0x0300_0008);
+        let tick: u64 = cpu.tick();
+        cpu.proc();
+        assert_eq!(cpu.tick() - tick, 3); // 分岐: 2S+1N
+        cpu.proc();
+        assert_eq!(cpu.reg.reg(1), 0);
+        assert_eq!(cpu.reg.reg(2), 2);
+    }
+
+    #[test]
+    fn test_irq_delivery() {
+        let mut cpu = CPU::new();
+        cpu.write_word(0x0300_0000, 0xE3A0_1001, MemoryAccess::NonSeq); // MOV R1, #1
+        cpu.write_word(0x0300_0004, 0xE3A0_2002, MemoryAccess::NonSeq); // MOV R2, #2
+        bios::skip_boot(&mut cpu);
+        cpu.reg.cpsr.insert(PSR::I);
+        cpu.set_pc(0x0300_0000);
+        cpu.write_hword(0x0400_0200, 0x0001, MemoryAccess::NonSeq); // IE = VBlank
+        cpu.write_hword(0x0400_0208, 0x0001, MemoryAccess::NonSeq); // IME = 1
+        cpu.bus.interrupt.request(Interrupt::VBlank);
+
+        // CPSR.I = 1 の間は受け付けない
+        cpu.proc();
+        assert_eq!(cpu.reg.reg(1), 1);
+        assert_eq!(cpu.reg.mode().bits(), PSR::MODE_SYS.bits());
+
+        // 次の命令(0x03000004)の前にIRQ例外 (LR = 次の命令+4)
+        cpu.reg.cpsr.remove(PSR::I);
+        cpu.proc();
+        assert_eq!(cpu.reg.mode().bits(), PSR::MODE_IRQ.bits());
+        assert_eq!(cpu.reg.reg(14), 0x0300_0008);
+        assert_eq!(cpu.reg.user_reg(2), 0);
+        assert!(cpu.reg.cpsr.contains(PSR::I));
+    }
+
+    #[test]
+    fn test_halt() {
+        let mut cpu = CPU::new();
+        cpu.write_word(0x0300_0000, 0xE3A0_0301, MemoryAccess::NonSeq); // MOV R0, #0x04000000
+        cpu.write_word(0x0300_0004, 0xE5C0_1301, MemoryAccess::NonSeq); // STRB R1, [R0, #0x301]
+        cpu.write_word(0x0300_0008, 0xE3A0_2002, MemoryAccess::NonSeq); // MOV R2, #2
+        bios::skip_boot(&mut cpu);
+        cpu.reg.cpsr.insert(PSR::I);
+        cpu.set_pc(0x0300_0000);
+        cpu.write_hword(0x0400_0200, 0x0001, MemoryAccess::NonSeq); // IE = VBlank
+        cpu.bus.scheduler.schedule(EventType::HBlank, 1000);
+
+        // HALT中はイベント時刻まで早送りし、命令を実行しない
+        cpu.run(10);
+        assert_eq!(cpu.bus.power_mode(), PowerMode::Halt);
+        cpu.run(5000);
+        assert_eq!(cpu.tick(), cpu.bus.scheduler.now());
+        assert!(cpu.tick() >= 5010);
+        assert_eq!(cpu.reg.reg(2), 0);
+
+        // IE & IF != 0 で解除 (IME/CPSR.Iに関係なく再開)
+        cpu.bus.interrupt.request(Interrupt::VBlank);
+        cpu.proc();
+        assert_eq!(cpu.bus.power_mode(), PowerMode::Running);
+        assert_eq!(cpu.reg.reg(2), 2);
+
+        // STOPはKeypad割り込みのみで解除
+        cpu.write_byte(0x0400_0301, 0x80, MemoryAccess::NonSeq);
+        let tick: u64 = cpu.tick();
+        cpu.run(1000);
+        assert_eq!(cpu.tick(), tick);
+        assert!(cpu.is_stopped());
+        cpu.write_hword(0x0400_0200, 0x1001, MemoryAccess::NonSeq); // IE = VBlank | Keypad
+        assert!(cpu.bus.is_halted());
+        cpu.write_hword(0x0400_0132, 0x4001, MemoryAccess::NonSeq); // KEYCNT = A, IRQ
+        cpu.set_keys(KeyState::A);
+        assert!(!cpu.is_stopped());
+        assert_eq!(cpu.bus.power_mode(), PowerMode::Running);
+    }
+
+    #[test]
+    fn test_run_immediate_dma() {
+        let mut cpu = CPU::new();
+        cpu.write_word(0x0300_0000, 0xE1C0_10B0, MemoryAccess::NonSeq); // STRH R1, [R0]
+        cpu.write_word(0x0300_0004, 0xE1A0_0000, MemoryAccess::NonSeq); // MOV R0, R0
+        cpu.write_word(0x0300_0008, 0xE1A0_0000, MemoryAccess::NonSeq); // MOV R0, R0
+        cpu.write_word(0x0300_000C, 0xE593_2000, MemoryAccess::NonSeq); // LDR R2, [R3]
+        cpu.write_word(0x0300_0010, 0xEAFF_FFFE, MemoryAccess::NonSeq); // B .
+        cpu.write_word(0x0200_0000, 0xDEAD_BEEF, MemoryAccess::NonSeq);
+        bios::skip_boot(&mut cpu);
+        cpu.reg.cpsr.insert(PSR::I);
+        cpu.set_pc(0x0300_0000);
+        cpu.write_word(0x0400_00D4, 0x0200_0000, MemoryAccess::NonSeq); // DMA3SAD
+        cpu.write_word(0x0400_00D8, 0x0300_1000, MemoryAccess::NonSeq); // DMA3DAD
+        cpu.write_hword(0x0400_00DC, 0x0001, MemoryAccess::NonSeq); // DMA3CNT_L = 1
+        cpu.reg.set_reg(0, 0x0400_00DE);
+        cpu.reg.set_reg(1, 0x8400); // 有効, 即時, 32bit
+        cpu.reg.set_reg(3, 0x0300_1000);
+
+        // 即時DMAは書き込みの2サイクル後(IWRAMのNOP2命令分)に開始し、次のLDRより前に転送される
+        cpu.run(100);
+        assert_eq!(cpu.reg.reg(2), 0xDEAD_BEEF);
+    }
+
+    #[test]
+    fn test_halt_wake_by_new_event() {
+        let mut cpu = CPU::new();
+        cpu.write_word(0x0300_0000, 0xE580_1000, MemoryAccess::NonSeq); // STR R1, [R0]
+        cpu.write_word(0x0300_0004, 0xE5C5_4000, MemoryAccess::NonSeq); // STRB R4, [R5]
+        cpu.write_word(0x0300_0008, 0xE3A0_2002, MemoryAccess::NonSeq); // MOV R2, #2
+        cpu.write_word(0x0300_000C, 0xEAFF_FFFE, MemoryAccess::NonSeq); // B .
+        bios::skip_boot(&mut cpu);
+        cpu.reg.cpsr.insert(PSR::I);
+        cpu.set_pc(0x0300_0000);
+        cpu.write_hword(0x0400_0200, 0x0008, MemoryAccess::NonSeq); // IE = Timer0
+        cpu.reg.set_reg(0, 0x0400_0100);
+        cpu.reg.set_reg(1, 0x00C0_FFC0); // TM0: リロード0xFFC0, IRQ, 開始
+        cpu.reg.set_reg(4, 0);
+        cpu.reg.set_reg(5, 0x0400_0301); // HALTCNT
+
+        // HALT直前に開始したタイマのオーバーフローで解除され、残りサイクルで命令を実行する
+        cpu.run(200);
+        assert_eq!(cpu.bus.power_mode(), PowerMode::Running);
+        assert_eq!(cpu.reg.reg(2), 2);
+    }
+}
@@ -0,0 +1,269 @@
+// =========================================================================
+// [DMA Controller]
+// DMA0~DMA3 (0x040000B0~0x040000DF)
+// 転送処理はBus側で行う (ウェイトステート/各領域の読み書き規則を共通化)
+// =========================================================================
+use common::*;
+
+// 転送開始タイミング
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DmaTiming {
+    Immediate, // 即時
+    VBlank,    // VBlank開始
+    HBlank,    // HBlank開始
+    Special,   // DMA1/2:サウンドFIFO, DMA3:ビデオキャプチャ
+}
+
+const _DMACNT_DST_MASK: u16 = 0x0060; // 転送先アドレス制御 Bit[6:5]
+const _DMACNT_SRC_MASK: u16 = 0x0180; // 転送元アドレス制御 Bit[8:7]
+const _DMACNT_REPEAT: u16   = 0x0200; // リピート
+const _DMACNT_WORD: u16     = 0x0400; // 32bit転送
+const _DMACNT_IRQ: u16      = 0x4000; // 転送終了割り込み
+const _DMACNT_ENABLE: u16   = 0x8000; // 転送許可
+
+// チャネル毎のアドレス/転送数の有効ビット |SAD|DAD|CNT_L|CNT_H|
+// (DMA0は内部メモリのみ, DMA3のみGame Pakへ書き込み可能)
+const _DMA_MASK: [(u32, u32, u32, u16); 4] = [
+    (0x07FF_FFFF, 0x07FF_FFFF, 0x3FFF, 0xF7E0),
+    (0x0FFF_FFFF, 0x07FF_FFFF, 0x3FFF, 0xF7E0),
+    (0x0FFF_FFFF, 0x07FF_FFFF, 0x3FFF, 0xF7E0),
+    (0x0FFF_FFFF, 0x0FFF_FFFF, 0xFFFF, 0xFFE0),
+];
+
+// サウンドFIFOのアドレス
+pub const _FIFO_A_ADDR: u32 = 0x0400_00A0;
+pub const _FIFO_B_ADDR: u32 = 0x0400_00A4;
+
+// 1回分の転送内容
+#[derive(Debug)]
+pub struct DmaTransfer {
+    pub src: u32,
+    pub dst: u32,
+    pub count: u32,
+    pub word: bool,     // 32bit転送
+    pub src_step: i32,  // 転送毎のアドレス増分
+    pub dst_step: i32,
+}
+
+#[derive(Clone, Copy)]
+struct DmaChannel {
+    sad: u32,     // 転送元 (レジスタ値)
+    dad: u32,     // 転送先 (レジスタ値)
+    cnt_l: u16,   // 転送数 (レジスタ値)
+    cnt_h: u16,   // 制御
+    src: u32,     // 内部転送元 (転送開始時にラッチ)
+    dst: u32,     // 内部転送先
+}
+
+pub struct Dma {
+    channels: [DmaChannel; 4],
+    start: u8,    // 即時転送の開始要求 (Bit0~3)
+}
+
+#[allow(dead_code)]
+impl Dma {
+    pub fn new() -> Self {
+        Dma {
+            channels: [DmaChannel { sad: 0, dad: 0, cnt_l: 0, cnt_h: 0, src: 0, dst: 0 }; 4],
+            start: 0,
+        }
+    }
+
+    pub fn is_enabled(&self, n: usize) -> bool {
+        (self.channels[n].cnt_h & _DMACNT_ENABLE) != 0
+    }
+
+    pub fn timing(&self, n: usize) -> DmaTiming {
+        match (self.channels[n].cnt_h >> 12) & 3 {
+            0 => DmaTiming::Immediate,
+            1 => DmaTiming::VBlank,
+            2 => DmaTiming::HBlank,
+            _ => DmaTiming::Special,
+        }
+    }
+
+    // 指定タイミングで転送を開始するチャネルか
+    pub fn is_triggered(&self, n: usize, timing: DmaTiming) -> bool {
+        self.is_enabled(n) && self.timing(n) == timing
+    }
+
+    // サウンドFIFO転送のチャネルか (DMA1/2, Special, 転送先がFIFO)
+    pub fn is_fifo(&self, n: usize, fifo_addr: u32) -> bool {
+        (n == 1 || n == 2) && self.is_triggered(n, DmaTiming::Special) && self.channels[n].dad == fifo_addr
+    }
+
+    // 即時転送の開始要求 (取得後クリア)
+    pub fn take_start(&mut self) -> u8 {
+        std::mem::take(&mut self.start)
+    }
+
+    // 転送内容を取得
+    pub fn transfer(&self, n: usize) -> DmaTransfer {
+        let ch: &DmaChannel = &self.channels[n];
+        let fifo: bool = self.is_fifo(n, _FIFO_A_ADDR) || self.is_fifo(n, _FIFO_B_ADDR);
+        let word: bool = fifo || (ch.cnt_h & _DMACNT_WORD) != 0;
+        let width: i32 = if word { 4 } else { 2 };
+        let step = |mode: u16| -> i32 {
+            match mode {
+                0 | 3 => width, // 増加 (転送先の3は増加/リロード)
+                1 => -width,    // 減少
+                _ => 0,         // 固定
+            }
+        };
+        // FIFO転送は転送先固定で4word
+        let (count, dst_step) = if fifo {
+            (4, 0)
+        } else {
+            (self.word_count(n), step((ch.cnt_h & _DMACNT_DST_MASK) >> 5))
+        };
+        DmaTransfer {
+            src: ch.src,
+            dst: ch.dst,
+            count,
+            word,
+            src_step: step((ch.cnt_h & _DMACNT_SRC_MASK) >> 7),
+            dst_step,
+        }
+    }
+
+    // 転送数 (0は最大数)
+    fn word_count(&self, n: usize) -> u32 {
+        match self.channels[n].cnt_l as u32 & _DMA_MASK[n].2 {
+            0 if n == 3 => 0x10000,
+            0 => 0x4000,
+            count => count,
+        }
+    }
+
+    // 転送終了 (戻り値は割り込み要求の有無)
+    pub fn finish(&mut self, n: usize, src: u32, dst: u32) -> bool {
+        let repeat: bool = (self.channels[n].cnt_h & _DMACNT_REPEAT) != 0 && self.timing(n) != DmaTiming::Immediate;
+        let fifo: bool = self.is_fifo(n, _FIFO_A_ADDR) || self.is_fifo(n, _FIFO_B_ADDR);
+        let ch: &mut DmaChannel = &mut self.channels[n];
+        ch.src = src;
+        if !fifo {
+            ch.dst = dst;
+        }
+        if repeat {
+            // 転送先 増加/リロード
+            if (ch.cnt_h & _DMACNT_DST_MASK) == 0x0060 {
+                ch.dst = ch.dad;
+            }
+        } else {
+            ch.cnt_h &= !_DMACNT_ENABLE;
+        }
+        (ch.cnt_h & _DMACNT_IRQ) != 0
+    }
+
+    // DMA3 ビデオキャプチャ終了 (リピート中でも停止)
+    pub fn stop(&mut self, n: usize) {
+        self.channels[n].cnt_h &= !_DMACNT_ENABLE;
+    }
+
+    fn write_control(&mut self, n: usize, val: u16) {
+        let was_enabled: bool = self.is_enabled(n);
+        let ch: &mut DmaChannel = &mut self.channels[n];
+        ch.cnt_h = val & _DMA_MASK[n].3;
+        // 停止→許可で転送元/転送先をラッチ
+        if !was_enabled && (ch.cnt_h & _DMACNT_ENABLE) != 0 {
+            ch.src = ch.sad;
+            ch.dst = ch.dad;
+            if self.timing(n) == DmaTiming::Immediate {
+                self.start |= 1 << n;
+            }
+        }
+    }
+}
+
+impl IO for Dma {
+    fn read(&mut self, addr: u32) -> u8 {
+        let offset: u32 = (addr & 0x3FF) - 0xB0;
+        let n: usize = (offset / 12) as usize;
+        match offset % 12 {
+            // SAD/DAD/CNT_Lは書き込み専用
+            10 => self.channels[n].cnt_h as u8,
+            11 => (self.channels[n].cnt_h >> 8) as u8,
+            _ => 0,
+        }
+    }
+
+    fn write(&mut self, addr: u32, val: u8) {
+        let offset: u32 = (addr & 0x3FF) - 0xB0;
+        let n: usize = (offset / 12) as usize;
+        let reg: u32 = offset % 12;
+        let ch: &mut DmaChannel = &mut self.channels[n];
+        match reg {
+            0..=3 => {
+                let shift: u32 = reg * 8;
+                ch.sad = ((ch.sad & !(0xFF << shift)) | ((val as u32) << shift)) & _DMA_MASK[n].0;
+            },
+            4..=7 => {
+                let shift: u32 = (reg - 4) * 8;
+                ch.dad = ((ch.dad & !(0xFF << shift)) | ((val as u32) << shift)) & _DMA_MASK[n].1;
+            },
+            8 => ch.cnt_l = (ch.cnt_l & 0xFF00) | val as u16,
+            9 => ch.cnt_l = (ch.cnt_l & 0x00FF) | ((val as u16) << 8),
+            10 => {
+                let cnt_h: u16 = (ch.cnt_h & 0xFF00) | val as u16;
+                self.write_control(n, cnt_h);
+            },
+            _ => {
+                let cnt_h: u16 = (ch.cnt_h & 0x00FF) | ((val as u16) << 8);
+                self.write_control(n, cnt_h);
+            },
+        }
+    }
+
+    fn update(&mut self, _now: u64) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write32(dma: &mut Dma, addr: u32, val: u32) {
+        for i in 0..4 {
+            dma.write(addr + i, (val >> (i * 8)) as u8);
+        }
+    }
+
+    #[test]
+    fn test_dma_registers() {
+        let mut dma = Dma::new();
+        // DMA0: 転送元はGame Pak不可 (内部メモリのみ)
+        write32(&mut dma, 0x0400_00B0, 0x0800_0000);
+        write32(&mut dma, 0x0400_00B4, 0x0300_0000);
+        write32(&mut dma, 0x0400_00B8, 0x8400_0000);
+        assert_eq!(dma.take_start(), 0x01);
+        let t: DmaTransfer = dma.transfer(0);
+        assert_eq!(t.src, 0);
+        assert_eq!(t.count, 0x4000);
+        assert!(t.word);
+        assert_eq!(dma.read(0x0400_00BB), 0x84);
+        assert_eq!(dma.read(0x0400_00B0), 0);
+        assert!(!dma.finish(0, 0, 0));
+        assert!(!dma.is_enabled(0));
+
+        // DMA1: サウンドFIFO (リピート, 転送先固定で4word)
+        write32(&mut dma, 0x0400_00BC, 0x0200_0000);
+        write32(&mut dma, 0x0400_00C0, _FIFO_A_ADDR);
+        write32(&mut dma, 0x0400_00C4, 0xB600_0001);
+        assert_eq!(dma.take_start(), 0);
+        assert!(dma.is_fifo(1, _FIFO_A_ADDR));
+        let t: DmaTransfer = dma.transfer(1);
+        assert_eq!((t.count, t.word, t.src_step, t.dst_step), (4, true, 4, 0));
+        dma.finish(1, 0x0200_0010, 0);
+        assert!(dma.is_enabled(1));
+        assert_eq!(dma.transfer(1).src, 0x0200_0010);
+
+        // DMA3: HBlank, 転送先 増加/リロード, 転送元固定, 16bit
+        write32(&mut dma, 0x0400_00D4, 0x0800_0100);
+        write32(&mut dma, 0x0400_00D8, 0x0600_0000);
+        write32(&mut dma, 0x0400_00DC, 0xA360_0000);
+        let t: DmaTransfer = dma.transfer(3);
+        assert_eq!((t.src, t.count, t.word, t.src_step, t.dst_step), (0x0800_0100, 0x10000, false, 0, 2));
+        assert!(dma.is_triggered(3, DmaTiming::HBlank));
+        dma.finish(3, 0x0800_0100, 0x0600_0200);
+        assert_eq!(dma.transfer(3).dst, 0x0600_0000);
+    }
+}
@@ -0,0 +1,164 @@
+// =========================================================================
+// [Interrupt Controller]
+// IE(0x04000200) / IF(0x04000202) / IME(0x04000208)
+// =========================================================================
+use common::*;
+
+// 割り込み要因 (IE/IFのビット番号)
+#[allow(dead_code)]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Interrupt {
+    VBlank  = 0,
+    HBlank  = 1,
+    VCount  = 2,
+    Timer0  = 3,
+    Timer1  = 4,
+    Timer2  = 5,
+    Timer3  = 6,
+    Serial  = 7,
+    Dma0    = 8,
+    Dma1    = 9,
+    Dma2    = 10,
+    Dma3    = 11,
+    Keypad  = 12,
+    GamePak = 13,
+}
+
+#[allow(dead_code)]
+impl Interrupt {
+    pub fn timer(n: u8) -> Self {
+        match n {
+            0 => Interrupt::Timer0,
+            1 => Interrupt::Timer1,
+            2 => Interrupt::Timer2,
+            3 => Interrupt::Timer3,
+            _ => panic!("Unknown Timer: {}", n),
+        }
+    }
+
+    pub fn dma(n: u8) -> Self {
+        match n {
+            0 => Interrupt::Dma0,
+            1 => Interrupt::Dma1,
+            2 => Interrupt::Dma2,
+            3 => Interrupt::Dma3,
+            _ => panic!("Unknown DMA: {}", n),
+        }
+    }
+
+    fn mask(self) -> u16 {
+        1 << (self as u16)
+    }
+}
+
+// IE/IF の有効ビット
+const _IRQ_MASK: u16 = 0x3FFF;
+// STOP解除可能な要因 (Serial, Keypad, GamePak)
+const _STOP_WAKE_MASK: u16 = 0x3080;
+
+pub struct InterruptController {
+    ie: u16,   // 割り込み許可
+    flag: u16, // 割り込み要求 (1書き込みでクリア)
+    ime: bool, // マスター許可
+}
+
+#[allow(dead_code)]
+impl InterruptController {
+    pub fn new() -> Self {
+        InterruptController {
+            ie: 0,
+            flag: 0,
+            ime: false,
+        }
+    }
+
+    // 割り込み要求 (各デバイスから呼び出す)
+    pub fn request(&mut self, irq: Interrupt) {
+        trace!("IRQ Request: {:?}", irq);
+        self.flag |= irq.mask();
+    }
+
+    // 許可された要求があるか (IMEに関係なくHALT解除に使用)
+    pub fn is_requested(&self) -> bool {
+        (self.ie & self.flag) != 0
+    }
+
+    // STOP解除要因の要求があるか (クロック停止中も動作する外部要因のみ)
+    pub fn is_wake_from_stop(&self) -> bool {
+        (self.ie & self.flag & _STOP_WAKE_MASK) != 0
+    }
+
+    // CPUへのIRQ信号 (CPSR.Iは CPU側で判定)
+    pub fn irq_line(&self) -> bool {
+        self.ime && self.is_requested()
+    }
+
+    pub fn ie(&self) -> u16 {
+        self.ie
+    }
+
+    pub fn flag(&self) -> u16 {
+        self.flag
+    }
+
+    pub fn ime(&self) -> bool {
+        self.ime
+    }
+}
+
+impl IO for InterruptController {
+    fn read(&mut self, addr: u32) -> u8 {
+        match addr & 0x3FF {
+            0x200 => self.ie as u8,
+            0x201 => (self.ie >> 8) as u8,
+            0x202 => self.flag as u8,
+            0x203 => (self.flag >> 8) as u8,
+            0x208 => self.ime as u8,
+            _ => 0,
+        }
+    }
+
+    fn write(&mut self, addr: u32, val: u8) {
+        match addr & 0x3FF {
+            0x200 => self.ie = ((self.ie & 0xFF00) | val as u16) & _IRQ_MASK,
+            0x201 => self.ie = ((self.ie & 0x00FF) | ((val as u16) << 8)) & _IRQ_MASK,
+            // IF: 1を書き込んだビットをクリア
+            0x202 => self.flag &= !(val as u16),
+            0x203 => self.flag &= !((val as u16) << 8),
+            0x208 => self.ime = (val & 1) != 0,
+            _ => {},
+        }
+    }
+
+    fn update(&mut self, _now: u64) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interrupt_ack() {
+        let mut irq = InterruptController::new();
+        irq.request(Interrupt::VBlank);
+        irq.request(Interrupt::timer(2));
+        assert_eq!(irq.read(0x0400_0202), 0x21);
+        assert!(!irq.is_requested());
+
+        irq.write(0x0400_0200, 0x20);
+        assert!(irq.is_requested());
+        assert!(!irq.irq_line());
+        irq.write(0x0400_0208, 0x01);
+        assert!(irq.irq_line());
+
+        // 1を書き込んだビットのみクリア
+        irq.write(0x0400_0202, 0x20);
+        assert_eq!(irq.flag(), 0x0001);
+        assert!(!irq.irq_line());
+        irq.request(Interrupt::dma(3));
+        irq.write(0x0400_0203, 0x00);
+        assert_eq!(irq.flag(), 0x0801);
+        irq.write(0x0400_0203, 0x08);
+        assert_eq!(irq.flag(), 0x0001);
+    }
+}
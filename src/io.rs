@@ -0,0 +1,95 @@
+// =========================================================================
+// [I/O Register]
+// 0x04000000~0x040003FF の汎用レジスタブロック (読み出し/書き込みマスク付き)
+// 専用デバイスを持たないレジスタはここで保持する
+// =========================================================================
+use common::*;
+
+pub const _IO_SIZE: usize = _MEM_SIZE_1K;
+
+// レジスタ定義 |Offset|読み出しマスク|書き込みマスク| (16bit単位, 未定義は読み出し0/書き込み無視)
+const _IO_REGS: &[(usize, u16, u16)] = &[
+    // LCD (DISPCNT~BLDYはppu.rs)
+    // Serial (Soundはapu.rs, DMAはdma.rs, Timerはtimer.rs)
+    (0x120, 0xFFFF, 0xFFFF), // SIODATA32_L / SIOMULTI0
+    (0x122, 0xFFFF, 0xFFFF), // SIODATA32_H / SIOMULTI1
+    (0x124, 0xFFFF, 0xFFFF), // SIOMULTI2
+    (0x126, 0xFFFF, 0xFFFF), // SIOMULTI3
+    (0x128, 0x7FFF, 0x7FFF), // SIOCNT
+    (0x12A, 0xFFFF, 0xFFFF), // SIODATA8 / SIOMLT_SEND
+    // Serial (Keypadはkeypad.rs)
+    (0x134, 0xC1FF, 0xC1FF), // RCNT
+    (0x140, 0x0047, 0x0047), // JOYCNT
+    (0x150, 0xFFFF, 0xFFFF), (0x152, 0xFFFF, 0xFFFF), // JOY_RECV
+    (0x154, 0xFFFF, 0xFFFF), (0x156, 0xFFFF, 0xFFFF), // JOY_TRANS
+    (0x158, 0x003A, 0x0030), // JOYSTAT
+    // Waitstate, Power-Down (IE/IF/IME/WAITCNT/HALTCNTはBus側で処理)
+    (0x300, 0x0001, 0x0001), // POSTFLG
+];
+
+pub struct IoRegisters {
+    regs: [u8; _IO_SIZE],
+    read_mask: [u8; _IO_SIZE],
+    write_mask: [u8; _IO_SIZE],
+}
+
+#[allow(dead_code)]
+impl IoRegisters {
+    pub fn new() -> Self {
+        let mut io = IoRegisters {
+            regs: [0; _IO_SIZE],
+            read_mask: [0; _IO_SIZE],
+            write_mask: [0; _IO_SIZE],
+        };
+        for (offset, read_mask, write_mask) in _IO_REGS.iter() {
+            io.read_mask[*offset..*offset + 2].copy_from_slice(&read_mask.to_le_bytes());
+            io.write_mask[*offset..*offset + 2].copy_from_slice(&write_mask.to_le_bytes());
+        }
+        io
+    }
+
+    // マスクを無視した内部値 (デバイス側からの参照用)
+    pub fn get(&self, offset: usize) -> u16 {
+        u16::from_le_bytes([self.regs[offset & !1], self.regs[(offset & !1) + 1]])
+    }
+
+    // マスクを無視した内部値の書き込み (ステータスビット等の更新用)
+    pub fn set(&mut self, offset: usize, val: u16) {
+        self.regs[(offset & !1)..(offset & !1) + 2].copy_from_slice(&val.to_le_bytes());
+    }
+}
+
+impl IO for IoRegisters {
+    fn read(&mut self, addr: u32) -> u8 {
+        let offset: usize = (addr as usize) & (_IO_SIZE - 1);
+        self.regs[offset] & self.read_mask[offset]
+    }
+
+    fn write(&mut self, addr: u32, val: u8) {
+        let offset: usize = (addr as usize) & (_IO_SIZE - 1);
+        let mask: u8 = self.write_mask[offset];
+        self.regs[offset] = (self.regs[offset] & !mask) | (val & mask);
+    }
+
+    fn update(&mut self, _now: u64) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_io_masks() {
+        let mut io = IoRegisters::new();
+        // 書き込み可能ビットのみ反映 (JOYSTAT)
+        io.write(0x0400_0158, 0xFF);
+        assert_eq!(io.read(0x0400_0158), 0x30);
+        assert_eq!(io.get(0x158), 0x0030);
+        // 読み出し/書き込み可能ビット (RCNT)
+        io.write(0x0400_0135, 0xFF);
+        assert_eq!(io.read(0x0400_0135), 0xC1);
+        // 未定義レジスタ
+        io.write(0x0400_0300 + 0x10, 0xFF);
+        assert_eq!(io.read(0x0400_0310), 0);
+    }
+}
@@ -0,0 +1,119 @@
+// =========================================================================
+// [Keypad]
+// KEYINPUT(0x04000130) / KEYCNT(0x04000132)
+// =========================================================================
+use common::*;
+use bitflags::bitflags;
+
+bitflags! {
+    // 押下中のキー (KEYINPUTのビット配置, 1=押下)
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub struct KeyState: u16 {
+        const A      = 1 << 0;
+        const B      = 1 << 1;
+        const SELECT = 1 << 2;
+        const START  = 1 << 3;
+        const RIGHT  = 1 << 4;
+        const LEFT   = 1 << 5;
+        const UP     = 1 << 6;
+        const DOWN   = 1 << 7;
+        const R      = 1 << 8;
+        const L      = 1 << 9;
+    }
+}
+
+const _KEYCNT_IRQ: u16 = 0x4000; // キー割り込み許可
+const _KEYCNT_AND: u16 = 0x8000; // 割り込み条件 (0:OR, 1:AND)
+const _KEYCNT_MASK: u16 = 0xC3FF;
+
+pub struct Keypad {
+    keys: KeyState, // 押下中のキー
+    keycnt: u16,
+}
+
+#[allow(dead_code)]
+impl Keypad {
+    pub fn new() -> Self {
+        Keypad {
+            keys: KeyState::empty(),
+            keycnt: 0,
+        }
+    }
+
+    pub fn set_keys(&mut self, keys: KeyState) {
+        self.keys = keys;
+    }
+
+    pub fn keys(&self) -> KeyState {
+        self.keys
+    }
+
+    // KEYINPUT (0=押下)
+    pub fn keyinput(&self) -> u16 {
+        !self.keys.bits() & KeyState::all().bits()
+    }
+
+    // キー割り込み条件の成立
+    pub fn is_irq_condition(&self) -> bool {
+        if (self.keycnt & _KEYCNT_IRQ) == 0 {
+            return false;
+        }
+        let select: u16 = self.keycnt & KeyState::all().bits();
+        let pressed: u16 = self.keys.bits() & select;
+        if (self.keycnt & _KEYCNT_AND) != 0 {
+            select != 0 && pressed == select
+        } else {
+            pressed != 0
+        }
+    }
+}
+
+impl IO for Keypad {
+    fn read(&mut self, addr: u32) -> u8 {
+        match addr & 0x3FF {
+            0x130 => self.keyinput() as u8,
+            0x131 => (self.keyinput() >> 8) as u8,
+            0x132 => self.keycnt as u8,
+            0x133 => (self.keycnt >> 8) as u8,
+            _ => 0,
+        }
+    }
+
+    fn write(&mut self, addr: u32, val: u8) {
+        match addr & 0x3FF {
+            // KEYINPUTは読み出し専用
+            0x132 => self.keycnt = ((self.keycnt & 0xFF00) | val as u16) & _KEYCNT_MASK,
+            0x133 => self.keycnt = ((self.keycnt & 0x00FF) | ((val as u16) << 8)) & _KEYCNT_MASK,
+            _ => {},
+        }
+    }
+
+    fn update(&mut self, _now: u64) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keypad() {
+        let mut keypad = Keypad::new();
+        assert_eq!(keypad.read(0x0400_0130), 0xFF);
+        assert_eq!(keypad.read(0x0400_0131), 0x03);
+        keypad.set_keys(KeyState::A | KeyState::START | KeyState::L);
+        assert_eq!(keypad.keyinput(), 0x01F6);
+
+        // OR条件 (A or B)
+        keypad.write(0x0400_0132, 0x03);
+        keypad.write(0x0400_0133, 0x40);
+        assert!(keypad.is_irq_condition());
+        // AND条件 (A and B)
+        keypad.write(0x0400_0133, 0xC0);
+        assert!(!keypad.is_irq_condition());
+        keypad.set_keys(KeyState::A | KeyState::B);
+        assert!(keypad.is_irq_condition());
+        // 割り込み禁止
+        keypad.write(0x0400_0133, 0x80);
+        assert!(!keypad.is_irq_condition());
+    }
+}
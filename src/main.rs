@@ -1,16 +1,177 @@
+#![allow(clippy::upper_case_acronyms)]
+
 mod cpu;
 mod bus;
 mod common;
 mod thumb_op;
 mod arm_op;
+mod barrel_shifter;
+mod scheduler;
+mod bios;
+mod cartridge;
+mod io;
+mod interrupt;
+mod timer;
+mod apu;
+mod dma;
+mod keypad;
+mod ppu;
 
 extern crate bitflags;
+extern crate once_cell;
+extern crate bincode;
 extern crate bitvec;
 
 #[macro_use]
 extern crate log;
 extern crate env_logger;
 
+use std::env;
+use std::fs;
+use std::io::{stdin, BufRead};
+use std::process;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use cpu::CPU;
+use cartridge::Cartridge;
+use keypad::KeyState;
+
+// 1フレームのサイクル数 (228ライン x 1232サイクル)
+const _FRAME_CYCLES: u64 = 280_896;
+
 fn main() {
-    println!("Hello, World!");
+    env_logger::init();
+
+    // rsgba <ROM> [BIOS]
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 2 {
+        eprintln!("Usage: {} <ROM file> [BIOS file]", args[0]);
+        process::exit(1);
+    }
+
+    let cartridge: Cartridge = match Cartridge::load(&args[1]) {
+        Ok(cartridge) => cartridge,
+        Err(e) => {
+            eprintln!("[ERR] {}: {}", args[1], e);
+            process::exit(1);
+        },
+    };
+    // ヘッダ不正でも自作ROM等は動くので警告のみ
+    if let Err(e) = cartridge.validate() {
+        warn!("Cartridge header: {}", e);
+    }
+    info!("Title: {}, Game Code: {}, Maker Code: {}, Version: {}",
+        cartridge.header.title, cartridge.header.game_code,
+        cartridge.header.maker_code, cartridge.header.version);
+
+    let mut cpu = CPU::new();
+    cpu.bus.load_cartridge(cartridge);
+
+    // BIOS指定あり: リセットベクタから起動, 指定なし: HLEでROMから起動
+    match args.get(2) {
+        Some(path) => {
+            let image: Vec<u8> = fs::read(path).unwrap_or_else(|e| {
+                eprintln!("[ERR] {}: {}", path, e);
+                process::exit(1);
+            });
+            if let Err(e) = cpu.load_bios(&image) {
+                eprintln!("[ERR] {}: {}", path, e);
+                process::exit(1);
+            }
+        },
+        None => bios::skip_boot(&mut cpu),
+    }
+
+    let input: Receiver<KeyState> = spawn_key_input();
+    while run_frame(&mut cpu, &input) {}
+    info!("Stopped with no input source");
+}
+
+// 標準入力からのキー入力 (1行 = 押下中のキー名を空白区切り, 空行で全て離す)
+fn spawn_key_input() -> Receiver<KeyState> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        for line in stdin().lock().lines().map_while(Result::ok) {
+            if tx.send(parse_keys(&line)).is_err() {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+fn parse_keys(line: &str) -> KeyState {
+    line.split_whitespace().fold(KeyState::empty(), |keys, name| {
+        match KeyState::from_name(&name.to_uppercase()) {
+            Some(key) => keys | key,
+            None => {
+                warn!("Unknown key: {}", name);
+                keys
+            },
+        }
+    })
+}
+
+// 1フレーム実行
+// STOP中はクロックが止まるので、キー入力が届くまでブロックして解除を待つ
+// 戻り値: false = 入力が閉じられてSTOPを解除できない
+fn run_frame(cpu: &mut CPU, input: &Receiver<KeyState>) -> bool {
+    while let Ok(keys) = input.try_recv() {
+        cpu.set_keys(keys);
+    }
+    cpu.run(_FRAME_CYCLES);
+    while cpu.is_stopped() {
+        match input.recv() {
+            Ok(keys) => cpu.set_keys(keys),
+            Err(_) => return false,
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use bus::PowerMode;
+
+    #[test]
+    fn test_parse_keys() {
+        assert_eq!(parse_keys("a Start  up"), KeyState::A | KeyState::START | KeyState::UP);
+        assert_eq!(parse_keys(""), KeyState::empty());
+        assert_eq!(parse_keys("B X"), KeyState::B);
+    }
+
+    #[test]
+    fn test_run_frame_stop() {
+        let mut cpu = CPU::new();
+        bios::skip_boot(&mut cpu);
+        unsafe {
+            cpu.bus.write_word(0x0300_0000, 0xEAFF_FFFE); // B .
+            cpu.bus.write_hword(0x0400_0200, 0x1000);     // IE = Keypad
+            cpu.bus.write_hword(0x0400_0132, 0x4001);     // KEYCNT = A, IRQ
+        }
+        cpu.reg.cpsr.insert(cpu::PSR::I);
+        cpu.set_pc(0x0300_0000);
+
+        // STOP中は入力が届くまで待機し、キー割り込みで解除される
+        let (tx, rx) = mpsc::channel();
+        let sender = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            tx.send(KeyState::A).unwrap();
+            tx
+        });
+        unsafe { cpu.bus.write_byte(0x0400_0301, 0x80) };
+        assert!(run_frame(&mut cpu, &rx));
+        assert_eq!(cpu.bus.power_mode(), PowerMode::Running);
+
+        // 入力が閉じられると解除できないので終了する
+        drop(sender.join().unwrap());
+        cpu.set_keys(KeyState::empty());
+        unsafe {
+            cpu.bus.write_hword(0x0400_0202, 0x1000); // IF クリア
+            cpu.bus.write_byte(0x0400_0301, 0x80);
+        }
+        assert!(!run_frame(&mut cpu, &rx));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,1189 @@
+// =========================================================================
+// [LCD Video Controller]
+// DISPCNT(0x04000000) / DISPSTAT(0x04000004) / VCOUNT(0x04000006)
+// BGxCNT/BGxHOFS/BGxVOFS/BGxPA~PD/BGxX/BGxY (0x04000008~0x0400003F)
+// WIN0H~WINOUT(0x04000040~0x0400004B) / MOSAIC(0x0400004C) / BLDCNT~BLDY(0x04000050~0x04000055)
+// OBJ(OAM)
+// ライン単位で描画し、240x160のRGBフレームバッファへ出力する
+// =========================================================================
+use common::*;
+use interrupt::*;
+
+pub const _SCREEN_WIDTH: usize = _SCREEN_W as usize;
+pub const _SCREEN_HEIGHT: usize = _SCREEN_H as usize;
+
+// 1ライン = 1232サイクル (HDraw:960 + HBlank:272), 1フレーム = 228ライン
+pub const _HDRAW_CYCLES: u64 = 960;
+pub const _HBLANK_CYCLES: u64 = 272;
+pub const _LINES: u16 = 228;
+pub const _VISIBLE_LINES: u16 = 160;
+
+const _DISPCNT_FRAME: u16        = 0x0010; // フレーム選択 (Mode4/5)
+const _DISPCNT_HBLANK_FREE: u16  = 0x0020; // H-Blank Interval Free (HBlank中のOBJ処理停止)
+const _DISPCNT_OBJ_1D: u16       = 0x0040; // OBJタイル 1次元マッピング
+const _DISPCNT_FORCED_BLANK: u16 = 0x0080; // 強制ブランク
+const _DISPCNT_BG0: u16          = 0x0100; // BG0表示 (BG1~3は続くビット)
+const _DISPCNT_OBJ: u16          = 0x1000; // OBJ表示
+const _DISPCNT_WIN0: u16         = 0x2000; // ウィンドウ0
+const _DISPCNT_WIN1: u16         = 0x4000; // ウィンドウ1
+const _DISPCNT_OBJ_WIN: u16      = 0x8000; // OBJウィンドウ
+
+const _DISPSTAT_VBLANK: u16     = 0x0001;
+const _DISPSTAT_HBLANK: u16     = 0x0002;
+const _DISPSTAT_VCOUNT: u16     = 0x0004;
+const _DISPSTAT_VBLANK_IRQ: u16 = 0x0008;
+const _DISPSTAT_HBLANK_IRQ: u16 = 0x0010;
+const _DISPSTAT_VCOUNT_IRQ: u16 = 0x0020;
+const _DISPSTAT_WRITE_MASK: u16 = 0xFF38;
+
+const _BGCNT_MOSAIC: u16 = 0x0040; // モザイク
+const _BGCNT_8BPP: u16 = 0x0080; // 256色 (8bpp)
+const _BGCNT_WRAP: u16 = 0x2000; // 回転拡大BGの画面外折り返し
+
+// レイヤ番号 (BLDCNT/WININ/WINOUTのビット位置, BG0~3は0~3)
+const _LAYER_OBJ: usize = 4;
+const _LAYER_BACKDROP: usize = 5;
+// WININ/WINOUT Bit5: 特殊効果
+const _WIN_EFFECT: u8 = 0x20;
+
+// BLDCNT 特殊効果 Bit[7:6]
+const _BLEND_ALPHA: u16 = 1;
+const _BLEND_BRIGHTEN: u16 = 2;
+const _BLEND_DARKEN: u16 = 3;
+
+// BGタイルデータ領域 (Char Baseからこの範囲外のタイルは透明)
+const _BG_VRAM_SIZE: usize = 0x10000;
+
+// OBJタイル/パレット
+const _OBJ_VRAM_BASE: usize = 0x10000;
+const _OBJ_VRAM_SIZE: usize = 0x8000;
+const _OBJ_PRAM_BASE: usize = 0x200;
+// 1ラインのOBJ処理サイクル (H-Blank Interval Free 有効時は短くなる)
+const _OBJ_CYCLES: usize = 1210;
+const _OBJ_CYCLES_HBLANK_FREE: usize = 954;
+
+// OBJサイズ [形状][サイズ] |幅|高さ|
+const _OBJ_SIZE: [[(usize, usize); 4]; 3] = [
+    [(8, 8), (16, 16), (32, 32), (64, 64)], // 正方形
+    [(16, 8), (32, 8), (32, 16), (64, 32)], // 横長
+    [(8, 16), (8, 32), (16, 32), (32, 64)], // 縦長
+];
+
+// Mode4/5 の2枚目のフレーム
+const _FRAME_1_OFFSET: usize = 0xA000;
+// Mode5 の画面サイズ
+const _MODE5_WIDTH: usize = 160;
+const _MODE5_HEIGHT: usize = 128;
+
+// BGR555 → RGB888 (0x00RRGGBB)
+pub fn bgr555_to_rgb(color: u16) -> u32 {
+    let r: u32 = (color & 0x1F) as u32;
+    let g: u32 = ((color >> 5) & 0x1F) as u32;
+    let b: u32 = ((color >> 10) & 0x1F) as u32;
+    let expand = |c: u32| (c << 3) | (c >> 2);
+    (expand(r) << 16) | (expand(g) << 8) | expand(b)
+}
+
+fn read_hword(mem: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([mem[offset], mem[offset + 1]])
+}
+
+// 28bit符号付き (基準点 20.8固定小数点)
+fn sign_extend_28(val: u32) -> i32 {
+    ((val << 4) as i32) >> 4
+}
+
+// 16bit/32bitレジスタのバイト単位書き込み
+fn set_byte16(reg: u16, index: u32, val: u8) -> u16 {
+    (reg & !(0xFF << (index * 8))) | ((val as u16) << (index * 8))
+}
+
+fn set_byte32(reg: u32, index: u32, val: u8) -> u32 {
+    (reg & !(0xFF << (index * 8))) | ((val as u32) << (index * 8))
+}
+
+// タイル内ピクセルのカラー番号 (4bppはパレット内番号, 0は透明)
+fn tile_color_index(vram: &[u8], tile_addr: usize, tx: usize, ty: usize, is_8bpp: bool) -> usize {
+    if is_8bpp {
+        vram[tile_addr + ty * 8 + tx] as usize
+    } else {
+        ((vram[tile_addr + ty * 4 + tx / 2] >> ((tx & 1) * 4)) & 0x0F) as usize
+    }
+}
+
+// BGR555の色成分ごとの演算
+fn map_color<F: Fn(u16, u16) -> u16>(a: u16, b: u16, f: F) -> u16 {
+    (0..3).fold(0, |color, i| {
+        let shift: u16 = i * 5;
+        color | (f((a >> shift) & 0x1F, (b >> shift) & 0x1F).min(0x1F) << shift)
+    })
+}
+
+// 半透明 (係数は1/16単位, 最大16)
+fn alpha_blend(top: u16, bottom: u16, eva: u16, evb: u16) -> u16 {
+    map_color(top, bottom, |a, b| (a * eva + b * evb) >> 4)
+}
+
+// 輝度変更 (明るく: 白へ, 暗く: 黒へ)
+fn brightness(color: u16, evy: u16, brighten: bool) -> u16 {
+    if brighten {
+        map_color(color, 0, |c, _| c + (((0x1F - c) * evy) >> 4))
+    } else {
+        map_color(color, 0, |c, _| c - ((c * evy) >> 4))
+    }
+}
+
+// 1ライン分のレイヤ (Noneは透明)
+type Line = [Option<u16>; _SCREEN_WIDTH];
+
+// OBJモード (attr0 Bit[11:10])
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum ObjMode {
+    Normal,          // 通常
+    SemiTransparent, // 半透明 (BLDCNTに関係なく半透明処理)
+    Window,          // OBJウィンドウ (表示せずウィンドウ領域を形成)
+}
+
+// OBJレイヤのピクセル
+#[derive(Debug, Clone, Copy)]
+struct ObjPixel {
+    color: u16,
+    priority: u16,
+    semi_transparent: bool,
+}
+
+type ObjLine = [Option<ObjPixel>; _SCREEN_WIDTH];
+
+#[derive(Clone, Copy)]
+struct Background {
+    cnt: u16,  // BGxCNT
+    hofs: u16, // 水平スクロール
+    vofs: u16, // 垂直スクロール
+}
+
+impl Background {
+    fn priority(&self) -> u16 {
+        self.cnt & 3
+    }
+
+    fn char_base(&self) -> usize {
+        ((self.cnt as usize >> 2) & 3) * 0x4000
+    }
+
+    fn screen_base(&self) -> usize {
+        ((self.cnt as usize >> 8) & 0x1F) * 0x800
+    }
+
+    fn screen_size(&self) -> usize {
+        (self.cnt >> 14) as usize
+    }
+}
+
+// 回転拡大パラメータ (BG2/BG3)
+#[derive(Clone, Copy)]
+struct AffineBg {
+    pa: i16,    // dx
+    pb: i16,    // dmx
+    pc: i16,    // dy
+    pd: i16,    // dmy
+    x: u32,     // 基準点X (レジスタ値)
+    y: u32,     // 基準点Y (レジスタ値)
+    ref_x: i32, // 内部基準点X (ライン毎にPBを加算)
+    ref_y: i32, // 内部基準点Y (ライン毎にPDを加算)
+}
+
+impl AffineBg {
+    fn new() -> Self {
+        // BIOS起動後の初期値 (等倍)
+        AffineBg { pa: 0x100, pb: 0, pc: 0, pd: 0x100, x: 0, y: 0, ref_x: 0, ref_y: 0 }
+    }
+
+    // ライン上のx番目のピクセルに対応するテクスチャ座標 (backは垂直モザイクで遡るライン数)
+    fn texel(&self, x: usize, back: usize) -> (i32, i32) {
+        (
+            (self.ref_x - self.pb as i32 * back as i32 + self.pa as i32 * x as i32) >> 8,
+            (self.ref_y - self.pd as i32 * back as i32 + self.pc as i32 * x as i32) >> 8,
+        )
+    }
+
+    // 基準点の再読み込み (VBlank, 基準点レジスタ書き込み)
+    fn reload(&mut self) {
+        self.ref_x = sign_extend_28(self.x);
+        self.ref_y = sign_extend_28(self.y);
+    }
+
+    // 次のラインへ
+    fn advance(&mut self) {
+        self.ref_x += self.pb as i32;
+        self.ref_y += self.pd as i32;
+    }
+}
+
+pub struct Ppu {
+    dispcnt: u16,
+    greenswap: u16,
+    dispstat: u16,
+    vcount: u16,
+    bg: [Background; 4],
+    affine: [AffineBg; 2],   // BG2/BG3
+    win_h: [u16; 2],         // WIN0H/WIN1H |X1(8)|X2(8)|
+    win_v: [u16; 2],         // WIN0V/WIN1V |Y1(8)|Y2(8)|
+    winin: u16,
+    winout: u16,
+    mosaic: u16,             // MOSAIC
+    bldcnt: u16,
+    bldalpha: u16,
+    bldy: u16,
+    framebuffer: Vec<u32>,   // 240x160 RGB888
+    frame_ready: bool,       // VBlank突入で1フレーム完成
+}
+
+#[allow(dead_code)]
+impl Ppu {
+    pub fn new() -> Self {
+        Ppu {
+            dispcnt: 0,
+            greenswap: 0,
+            dispstat: 0,
+            vcount: 0,
+            bg: [Background { cnt: 0, hofs: 0, vofs: 0 }; 4],
+            affine: [AffineBg::new(); 2],
+            win_h: [0; 2],
+            win_v: [0; 2],
+            winin: 0,
+            winout: 0,
+            mosaic: 0,
+            bldcnt: 0,
+            bldalpha: 0,
+            bldy: 0,
+            framebuffer: vec![0; _SCREEN_WIDTH * _SCREEN_HEIGHT],
+            frame_ready: false,
+        }
+    }
+
+    pub fn vcount(&self) -> u16 {
+        self.vcount
+    }
+
+    pub fn dispcnt(&self) -> u16 {
+        self.dispcnt
+    }
+
+    pub fn framebuffer(&self) -> &[u32] {
+        &self.framebuffer
+    }
+
+    // 完成したフレームがあるか (取得後クリア)
+    pub fn take_frame(&mut self) -> bool {
+        std::mem::take(&mut self.frame_ready)
+    }
+
+    // HBlank開始
+    pub fn start_hblank(&mut self, irq: &mut InterruptController) {
+        self.dispstat |= _DISPSTAT_HBLANK;
+        if (self.dispstat & _DISPSTAT_HBLANK_IRQ) != 0 {
+            irq.request(Interrupt::HBlank);
+        }
+    }
+
+    // 次のラインのHDraw開始 (VCount更新, VBlank判定)
+    pub fn start_hdraw(&mut self, irq: &mut InterruptController) {
+        self.dispstat &= !_DISPSTAT_HBLANK;
+        self.vcount = (self.vcount + 1) % _LINES;
+
+        match self.vcount {
+            // 回転拡大の内部基準点はライン毎に進み、VBlankで再読み込み
+            1..=159 => self.affine.iter_mut().for_each(|a| a.advance()),
+            _VISIBLE_LINES => {
+                self.affine.iter_mut().for_each(|a| a.reload());
+                self.dispstat |= _DISPSTAT_VBLANK;
+                self.frame_ready = true;
+                if (self.dispstat & _DISPSTAT_VBLANK_IRQ) != 0 {
+                    irq.request(Interrupt::VBlank);
+                }
+            },
+            // 最終ラインではVBlankフラグが落ちる
+            227 => self.dispstat &= !_DISPSTAT_VBLANK,
+            _ => {},
+        }
+        self.check_vcount(irq);
+    }
+
+    // VCount一致判定 (DISPSTAT Bit[15:8])
+    fn check_vcount(&mut self, irq: &mut InterruptController) {
+        if self.vcount == (self.dispstat >> 8) {
+            if (self.dispstat & _DISPSTAT_VCOUNT) == 0 && (self.dispstat & _DISPSTAT_VCOUNT_IRQ) != 0 {
+                irq.request(Interrupt::VCount);
+            }
+            self.dispstat |= _DISPSTAT_VCOUNT;
+        } else {
+            self.dispstat &= !_DISPSTAT_VCOUNT;
+        }
+    }
+
+    // 現在のラインを描画
+    pub fn render_line(&mut self, vram: &[u8], pram: &[u8], oam: &[u8]) {
+        let y: usize = self.vcount as usize;
+        if y >= _SCREEN_HEIGHT {
+            return;
+        }
+        let mut line: [u32; _SCREEN_WIDTH] = [0; _SCREEN_WIDTH];
+        if (self.dispcnt & _DISPCNT_FORCED_BLANK) != 0 {
+            // 強制ブランク中は白
+            line = [0x00FF_FFFF; _SCREEN_WIDTH];
+        } else {
+            let layers: [Option<Line>; 4] = [
+                self.render_bg(0, vram, pram, y),
+                self.render_bg(1, vram, pram, y),
+                self.render_bg(2, vram, pram, y),
+                self.render_bg(3, vram, pram, y),
+            ];
+            let (obj, obj_window) = self.render_obj(vram, pram, oam, y);
+            let window: [u8; _SCREEN_WIDTH] = self.window_line(y, &obj_window);
+            // BGxCNTの優先度順 (同じ優先度はBG番号の小さい方が手前)
+            let mut order: Vec<usize> = (0..4).filter(|n| layers[*n].is_some()).collect();
+            order.sort_by_key(|n| (self.bg[*n].priority(), *n));
+
+            let backdrop: u16 = read_hword(pram, 0);
+            for (x, pixel) in line.iter_mut().enumerate() {
+                let enable: u8 = window[x];
+                let obj_pixel: Option<ObjPixel> = obj[x].filter(|_| (enable & (1 << _LAYER_OBJ)) != 0);
+                // 最前面と2番目のレイヤ |レイヤ番号|色|
+                let mut targets: [(usize, u16); 2] = [(_LAYER_BACKDROP, backdrop); 2];
+                let mut count: usize = 0;
+                let mut obj_pending: Option<ObjPixel> = obj_pixel;
+                for n in order.iter().filter(|n| (enable & (1 << **n)) != 0) {
+                    let color: u16 = match layers[*n].as_ref().and_then(|layer| layer[x]) {
+                        Some(color) => color,
+                        None => continue,
+                    };
+                    // OBJは同じ優先度のBGより手前
+                    if let Some(o) = obj_pending.filter(|o| o.priority <= self.bg[*n].priority()) {
+                        targets[count] = (_LAYER_OBJ, o.color);
+                        count += 1;
+                        obj_pending = None;
+                        if count == 2 {
+                            break;
+                        }
+                    }
+                    targets[count] = (*n, color);
+                    count += 1;
+                    if count == 2 {
+                        break;
+                    }
+                }
+                if let Some(o) = obj_pending.filter(|_| count < 2) {
+                    targets[count] = (_LAYER_OBJ, o.color);
+                }
+                let semi_transparent: bool = targets[0].0 == _LAYER_OBJ && obj_pixel.is_some_and(|o| o.semi_transparent);
+                let color: u16 = if (enable & _WIN_EFFECT) != 0 {
+                    self.blend(targets[0], targets[1], semi_transparent)
+                } else {
+                    targets[0].1
+                };
+                *pixel = bgr555_to_rgb(color);
+            }
+        }
+        self.framebuffer[y * _SCREEN_WIDTH..(y + 1) * _SCREEN_WIDTH].copy_from_slice(&line);
+    }
+
+    // 特殊効果 (top/bottom は |レイヤ番号|色|)
+    // 半透明OBJはBLDCNTの第1対象/効果の種類に関係なく半透明処理
+    fn blend(&self, top: (usize, u16), bottom: (usize, u16), semi_transparent: bool) -> u16 {
+        let first_target: bool = (self.bldcnt & (1 << top.0)) != 0;
+        let second_target: bool = (self.bldcnt & (1 << (8 + bottom.0))) != 0;
+        let eva: u16 = (self.bldalpha & 0x1F).min(16);
+        let evb: u16 = ((self.bldalpha >> 8) & 0x1F).min(16);
+        let evy: u16 = (self.bldy & 0x1F).min(16);
+        if semi_transparent && second_target {
+            return alpha_blend(top.1, bottom.1, eva, evb);
+        }
+        if !first_target {
+            return top.1;
+        }
+        match (self.bldcnt >> 6) & 3 {
+            _BLEND_ALPHA if second_target => alpha_blend(top.1, bottom.1, eva, evb),
+            _BLEND_BRIGHTEN => brightness(top.1, evy, true),
+            _BLEND_DARKEN => brightness(top.1, evy, false),
+            _ => top.1,
+        }
+    }
+
+    // ピクセル毎の表示レイヤ (WININ/WINOUTの設定値, ウィンドウ無効時は全表示)
+    // 優先度は WIN0 > WIN1 > OBJウィンドウ > ウィンドウ外
+    fn window_line(&self, y: usize, obj_window: &[bool; _SCREEN_WIDTH]) -> [u8; _SCREEN_WIDTH] {
+        if (self.dispcnt & (_DISPCNT_WIN0 | _DISPCNT_WIN1 | _DISPCNT_OBJ_WIN)) == 0 {
+            return [0x3F; _SCREEN_WIDTH];
+        }
+        // 範囲 [X1, X2) (X1 > X2 は画面端で折り返し)
+        let inside = |range: u16, pos: usize, limit: usize| -> bool {
+            let start: usize = (range >> 8) as usize;
+            let end: usize = ((range & 0xFF) as usize).min(limit);
+            if start <= end { start <= pos && pos < end } else { pos >= start || pos < end }
+        };
+        let in_win_y: [bool; 2] = [
+            (self.dispcnt & _DISPCNT_WIN0) != 0 && inside(self.win_v[0], y, _SCREEN_HEIGHT),
+            (self.dispcnt & _DISPCNT_WIN1) != 0 && inside(self.win_v[1], y, _SCREEN_HEIGHT),
+        ];
+        let mut line: [u8; _SCREEN_WIDTH] = [self.winout as u8 & 0x3F; _SCREEN_WIDTH];
+        for (x, enable) in line.iter_mut().enumerate() {
+            if in_win_y[0] && inside(self.win_h[0], x, _SCREEN_WIDTH) {
+                *enable = self.winin as u8 & 0x3F;
+            } else if in_win_y[1] && inside(self.win_h[1], x, _SCREEN_WIDTH) {
+                *enable = (self.winin >> 8) as u8 & 0x3F;
+            } else if (self.dispcnt & _DISPCNT_OBJ_WIN) != 0 && obj_window[x] {
+                *enable = (self.winout >> 8) as u8 & 0x3F;
+            }
+        }
+        line
+    }
+
+    // モザイクサイズ |水平|垂直| (BG:Bit[7:0], OBJ:Bit[15:8])
+    fn mosaic_size(&self, obj: bool) -> (usize, usize) {
+        let val: u16 = if obj { self.mosaic >> 8 } else { self.mosaic };
+        ((val & 0x0F) as usize + 1, ((val >> 4) & 0x0F) as usize + 1)
+    }
+
+    // BGの1ライン (非表示/現在のモードに存在しないBGはNone)
+    fn render_bg(&self, n: usize, vram: &[u8], pram: &[u8], y: usize) -> Option<Line> {
+        if (self.dispcnt & (_DISPCNT_BG0 << n)) == 0 {
+            return None;
+        }
+        // 垂直モザイクはブロック先頭のラインを繰り返す
+        let (mosaic_h, mosaic_v) = if (self.bg[n].cnt & _BGCNT_MOSAIC) != 0 { self.mosaic_size(false) } else { (1, 1) };
+        let back: usize = y % mosaic_v;
+        let mut line: Line = match (self.dispcnt & 7, n) {
+            (0, _) | (1, 0) | (1, 1) => self.render_text_bg(n, vram, pram, y - back),
+            (1, 2) | (2, 2) | (2, 3) => self.render_affine_bg(n, vram, pram, back),
+            (3..=5, 2) => self.render_bitmap(vram, pram, back),
+            _ => return None,
+        };
+        // 水平モザイクはブロック先頭のピクセルを繰り返す
+        if mosaic_h > 1 {
+            for x in 0.._SCREEN_WIDTH {
+                line[x] = line[x - x % mosaic_h];
+            }
+        }
+        Some(line)
+    }
+
+    // テキストBG (256x256~512x512, 32x32タイルのスクリーンブロック単位)
+    fn render_text_bg(&self, n: usize, vram: &[u8], pram: &[u8], y: usize) -> Line {
+        let bg: &Background = &self.bg[n];
+        let (width, height): (usize, usize) = match bg.screen_size() {
+            0 => (256, 256),
+            1 => (512, 256),
+            2 => (256, 512),
+            _ => (512, 512),
+        };
+        let is_8bpp: bool = (bg.cnt & _BGCNT_8BPP) != 0;
+        let py: usize = (y + bg.vofs as usize) & (height - 1);
+
+        let mut line: Line = [None; _SCREEN_WIDTH];
+        for (x, pixel) in line.iter_mut().enumerate() {
+            let px: usize = (x + bg.hofs as usize) & (width - 1);
+            // スクリーンエントリ |Palette(4)|VFlip|HFlip|Tile(10)|
+            let block: usize = (px / 256) + (py / 256) * (width / 256);
+            let entry_addr: usize = bg.screen_base() + block * 0x800 + (((py % 256) / 8) * 32 + (px % 256) / 8) * 2;
+            let entry: u16 = read_hword(vram, entry_addr);
+            let tx: usize = if (entry & 0x0400) != 0 { 7 - px % 8 } else { px % 8 };
+            let ty: usize = if (entry & 0x0800) != 0 { 7 - py % 8 } else { py % 8 };
+            let tile_size: usize = if is_8bpp { 64 } else { 32 };
+            let tile_addr: usize = bg.char_base() + (entry & 0x3FF) as usize * tile_size;
+            if tile_addr >= _BG_VRAM_SIZE {
+                continue;
+            }
+            let index: usize = tile_color_index(vram, tile_addr, tx, ty, is_8bpp);
+            if index == 0 {
+                continue;
+            }
+            let palette: usize = if is_8bpp { 0 } else { (entry >> 12) as usize * 16 };
+            *pixel = Some(read_hword(pram, (palette + index) * 2));
+        }
+        line
+    }
+
+    // 回転拡大BG (128x128~1024x1024, 256色タイル, 1byteのスクリーンエントリ)
+    fn render_affine_bg(&self, n: usize, vram: &[u8], pram: &[u8], back: usize) -> Line {
+        let bg: &Background = &self.bg[n];
+        let affine: &AffineBg = &self.affine[n - 2];
+        let size: i32 = 128 << bg.screen_size();
+        let wrap: bool = (bg.cnt & _BGCNT_WRAP) != 0;
+
+        let mut line: Line = [None; _SCREEN_WIDTH];
+        for (x, pixel) in line.iter_mut().enumerate() {
+            let (mut tex_x, mut tex_y) = affine.texel(x, back);
+            if wrap {
+                tex_x = tex_x.rem_euclid(size);
+                tex_y = tex_y.rem_euclid(size);
+            } else if tex_x < 0 || tex_y < 0 || tex_x >= size || tex_y >= size {
+                continue;
+            }
+            let (tex_x, tex_y) = (tex_x as usize, tex_y as usize);
+            let tile: usize = vram[bg.screen_base() + (tex_y / 8) * (size as usize / 8) + tex_x / 8] as usize;
+            let tile_addr: usize = bg.char_base() + tile * 64;
+            if tile_addr >= _BG_VRAM_SIZE {
+                continue;
+            }
+            let index: usize = tile_color_index(vram, tile_addr, tex_x % 8, tex_y % 8, true);
+            if index != 0 {
+                *pixel = Some(read_hword(pram, index * 2));
+            }
+        }
+        line
+    }
+
+    // ビットマップBG2 (Mode3/4/5, 回転拡大パラメータを使用)
+    fn render_bitmap(&self, vram: &[u8], pram: &[u8], back: usize) -> Line {
+        let mut line: Line = [None; _SCREEN_WIDTH];
+        for (x, pixel) in line.iter_mut().enumerate() {
+            let (tex_x, tex_y) = self.affine[0].texel(x, back);
+            *pixel = self.bitmap_pixel(vram, pram, tex_x, tex_y);
+        }
+        line
+    }
+
+    // ビットマップのピクセル (画面外/透明はNone)
+    fn bitmap_pixel(&self, vram: &[u8], pram: &[u8], x: i32, y: i32) -> Option<u16> {
+        let mode: u16 = self.dispcnt & 7;
+        let (width, height): (i32, i32) = if mode == 5 {
+            (_MODE5_WIDTH as i32, _MODE5_HEIGHT as i32)
+        } else {
+            (_SCREEN_WIDTH as i32, _SCREEN_HEIGHT as i32)
+        };
+        if x < 0 || y < 0 || x >= width || y >= height {
+            return None;
+        }
+        let (x, y) = (x as usize, y as usize);
+        let frame: usize = if (self.dispcnt & _DISPCNT_FRAME) != 0 { _FRAME_1_OFFSET } else { 0 };
+        match mode {
+            // 240x160 16bpp
+            3 => Some(read_hword(vram, (y * _SCREEN_WIDTH + x) * 2)),
+            // 240x160 8bpp (パレット0は透明), ページ切り替え
+            4 => {
+                let index: usize = vram[frame + y * _SCREEN_WIDTH + x] as usize;
+                if index == 0 { None } else { Some(read_hword(pram, index * 2)) }
+            },
+            // 160x128 16bpp, ページ切り替え
+            _ => Some(read_hword(vram, frame + (y * _MODE5_WIDTH + x) * 2)),
+        }
+    }
+
+    // OBJの1ライン (OAM番号順に処理し、ライン毎の処理サイクルを超えたOBJは表示しない)
+    // 戻り値は (OBJレイヤ, OBJウィンドウ領域)
+    fn render_obj(&self, vram: &[u8], pram: &[u8], oam: &[u8], y: usize) -> (ObjLine, [bool; _SCREEN_WIDTH]) {
+        let mut line: ObjLine = [None; _SCREEN_WIDTH];
+        let mut window: [bool; _SCREEN_WIDTH] = [false; _SCREEN_WIDTH];
+        if (self.dispcnt & _DISPCNT_OBJ) == 0 {
+            return (line, window);
+        }
+        let mut cycles: usize = if (self.dispcnt & _DISPCNT_HBLANK_FREE) != 0 { _OBJ_CYCLES_HBLANK_FREE } else { _OBJ_CYCLES };
+        let mapping_1d: bool = (self.dispcnt & _DISPCNT_OBJ_1D) != 0;
+        let bitmap_mode: bool = (self.dispcnt & 7) >= 3;
+        let (mosaic_h, mosaic_v) = self.mosaic_size(true);
+
+        for i in 0..128 {
+            // |attr0: Shape(2)|8bpp|Mosaic|Mode(2)|Double/Disable|Affine|Y(8)|
+            // |attr1: Size(2)|VFlip|HFlip|(Affine Param(5))|X(9)|
+            // |attr2: Palette(4)|Priority(2)|Tile(10)|
+            let attr0: u16 = read_hword(oam, i * 8);
+            let attr1: u16 = read_hword(oam, i * 8 + 2);
+            let attr2: u16 = read_hword(oam, i * 8 + 4);
+            let affine: bool = (attr0 & 0x0100) != 0;
+            if !affine && (attr0 & 0x0200) != 0 {
+                continue;
+            }
+            let mode: ObjMode = match (attr0 >> 10) & 3 {
+                0 => ObjMode::Normal,
+                1 => ObjMode::SemiTransparent,
+                2 => ObjMode::Window,
+                _ => continue,
+            };
+            let shape: usize = (attr0 >> 14) as usize;
+            if shape == 3 {
+                continue;
+            }
+            let (width, height) = _OBJ_SIZE[shape][(attr1 >> 14) as usize];
+            // 倍角 (回転拡大時の表示領域を2倍)
+            let double: bool = affine && (attr0 & 0x0200) != 0;
+            let (box_w, box_h) = if double { (width * 2, height * 2) } else { (width, height) };
+            // Y座標は256ラインで折り返し
+            let mut rel_y: usize = (y.wrapping_sub((attr0 & 0xFF) as usize)) & 0xFF;
+            if rel_y >= box_h {
+                continue;
+            }
+
+            // 処理サイクル (通常:幅, 回転拡大:10+表示領域の幅×2)
+            let cost: usize = if affine { 10 + box_w * 2 } else { width };
+            if cost > cycles {
+                break;
+            }
+            cycles -= cost;
+
+            // ビットマップモードではOBJタイルの前半(0~511)はBGと重なるため使用不可
+            let tile: usize = (attr2 & 0x3FF) as usize;
+            if bitmap_mode && tile < 512 {
+                continue;
+            }
+            let is_8bpp: bool = (attr0 & 0x2000) != 0;
+            let mosaic: bool = (attr0 & 0x1000) != 0;
+            if mosaic {
+                rel_y -= rel_y % mosaic_v;
+            }
+            let (pa, pb, pc, pd): (i32, i32, i32, i32) = if affine {
+                // 回転拡大パラメータ (32グループ, 各OAMエントリのattr3)
+                let group: usize = ((attr1 >> 9) & 0x1F) as usize * 32;
+                let param = |offset: usize| read_hword(oam, group + offset) as i16 as i32;
+                (param(6), param(14), param(22), param(30))
+            } else {
+                (0x100, 0, 0, 0x100)
+            };
+            let mut obj_x: i32 = (attr1 & 0x1FF) as i32;
+            if obj_x >= _SCREEN_WIDTH as i32 {
+                obj_x -= 512;
+            }
+            // タイル番号の増分 (8bppは2タイル分, 2次元マッピングは32タイル/行)
+            let tile_step: usize = if is_8bpp { 2 } else { 1 };
+            let row_stride: usize = if mapping_1d { (width / 8) * tile_step } else { 32 };
+
+            for bx in 0..box_w {
+                let x: i32 = obj_x + bx as i32;
+                if x < 0 || x >= _SCREEN_WIDTH as i32 {
+                    continue;
+                }
+                let bx: usize = if mosaic { bx - bx % mosaic_h } else { bx };
+                let (tx, ty): (usize, usize) = if affine {
+                    // 表示領域の中心を原点に変換
+                    let dx: i32 = bx as i32 - (box_w / 2) as i32;
+                    let dy: i32 = rel_y as i32 - (box_h / 2) as i32;
+                    let tx: i32 = ((pa * dx + pb * dy) >> 8) + (width / 2) as i32;
+                    let ty: i32 = ((pc * dx + pd * dy) >> 8) + (height / 2) as i32;
+                    if tx < 0 || ty < 0 || tx >= width as i32 || ty >= height as i32 {
+                        continue;
+                    }
+                    (tx as usize, ty as usize)
+                } else {
+                    let tx: usize = if (attr1 & 0x1000) != 0 { width - 1 - bx } else { bx };
+                    let ty: usize = if (attr1 & 0x2000) != 0 { height - 1 - rel_y } else { rel_y };
+                    (tx, ty)
+                };
+                let tile_num: usize = tile + (ty / 8) * row_stride + (tx / 8) * tile_step;
+                let tile_addr: usize = _OBJ_VRAM_BASE + ((tile_num * 32) & (_OBJ_VRAM_SIZE - 1));
+                if tile_addr + tile_step * 32 > vram.len() {
+                    continue;
+                }
+                let index: usize = tile_color_index(vram, tile_addr, tx % 8, ty % 8, is_8bpp);
+                if index == 0 {
+                    continue;
+                }
+                let x: usize = x as usize;
+                if mode == ObjMode::Window {
+                    window[x] = true;
+                    continue;
+                }
+                let palette: usize = if is_8bpp { 0 } else { (attr2 >> 12) as usize * 16 };
+                let priority: u16 = (attr2 >> 10) & 3;
+                // 優先度の高いOBJを残す (同じ優先度はOAM番号の小さい方)
+                if line[x].is_none_or(|p| priority < p.priority) {
+                    line[x] = Some(ObjPixel {
+                        color: read_hword(pram, _OBJ_PRAM_BASE + (palette + index) * 2),
+                        priority,
+                        semi_transparent: mode == ObjMode::SemiTransparent,
+                    });
+                }
+            }
+        }
+        (line, window)
+    }
+
+    // 回転拡大パラメータ書き込み (0x04000020~0x0400003F)
+    fn write_affine(&mut self, offset: u32, val: u8) {
+        let affine: &mut AffineBg = &mut self.affine[((offset - 0x20) / 0x10) as usize];
+        let reg: u32 = (offset - 0x20) % 0x10;
+        let set_param = |param: i16| set_byte16(param as u16, reg & 1, val) as i16;
+        match reg {
+            0x0..=0x1 => affine.pa = set_param(affine.pa),
+            0x2..=0x3 => affine.pb = set_param(affine.pb),
+            0x4..=0x5 => affine.pc = set_param(affine.pc),
+            0x6..=0x7 => affine.pd = set_param(affine.pd),
+            // 基準点の書き込みは内部基準点にも即時反映
+            0x8..=0xB => {
+                affine.x = set_byte32(affine.x, reg - 0x8, val) & 0x0FFF_FFFF;
+                affine.ref_x = sign_extend_28(affine.x);
+            },
+            _ => {
+                affine.y = set_byte32(affine.y, reg - 0xC, val) & 0x0FFF_FFFF;
+                affine.ref_y = sign_extend_28(affine.y);
+            },
+        }
+    }
+}
+
+impl IO for Ppu {
+    fn read(&mut self, addr: u32) -> u8 {
+        match addr & 0x3FF {
+            0x000 => self.dispcnt as u8,
+            0x001 => (self.dispcnt >> 8) as u8,
+            0x002 => self.greenswap as u8,
+            0x003 => (self.greenswap >> 8) as u8,
+            0x004 => self.dispstat as u8,
+            0x005 => (self.dispstat >> 8) as u8,
+            0x006 => self.vcount as u8,
+            0x008..=0x00F => (self.bg[((addr & 0x3FF) as usize - 0x008) / 2].cnt >> ((addr & 1) * 8)) as u8,
+            0x048 => self.winin as u8,
+            0x049 => (self.winin >> 8) as u8,
+            0x04A => self.winout as u8,
+            0x04B => (self.winout >> 8) as u8,
+            0x050 => self.bldcnt as u8,
+            0x051 => (self.bldcnt >> 8) as u8,
+            0x052 => self.bldalpha as u8,
+            0x053 => (self.bldalpha >> 8) as u8,
+            // スクロール/回転拡大パラメータ/ウィンドウ範囲/MOSAIC/BLDYは書き込み専用
+            _ => 0,
+        }
+    }
+
+    fn write(&mut self, addr: u32, val: u8) {
+        match addr & 0x3FF {
+            // Bit3 (CGBモード) はBIOS以外から書き込み不可
+            0x000 => self.dispcnt = (self.dispcnt & 0xFF08) | (val as u16 & 0xF7),
+            0x001 => self.dispcnt = (self.dispcnt & 0x00FF) | ((val as u16) << 8),
+            0x002 => self.greenswap = (val & 1) as u16,
+            // Bit0~2はステータス (読み出し専用)
+            0x004 => self.dispstat = (self.dispstat & !0x00FF) | (val as u16 & _DISPSTAT_WRITE_MASK),
+            0x005 => self.dispstat = (self.dispstat & 0x00FF) | ((val as u16) << 8),
+            // BG0/BG1はBit13(折り返し)無効
+            0x008..=0x00F => {
+                let n: usize = ((addr & 0x3FF) as usize - 0x008) / 2;
+                let mask: u16 = if n < 2 { 0xDFFF } else { 0xFFFF };
+                self.bg[n].cnt = set_byte16(self.bg[n].cnt, addr & 1, val) & mask;
+            },
+            0x010..=0x01F => {
+                let offset: usize = (addr & 0x3FF) as usize - 0x010;
+                let bg: &mut Background = &mut self.bg[offset / 4];
+                if (offset & 2) == 0 {
+                    bg.hofs = set_byte16(bg.hofs, addr & 1, val) & 0x01FF;
+                } else {
+                    bg.vofs = set_byte16(bg.vofs, addr & 1, val) & 0x01FF;
+                }
+            },
+            0x020..=0x03F => self.write_affine(addr & 0x3FF, val),
+            0x040..=0x047 => {
+                let index: usize = ((addr & 0x3FF) as usize - 0x040) / 2;
+                if index < 2 {
+                    self.win_h[index] = set_byte16(self.win_h[index], addr & 1, val);
+                } else {
+                    self.win_v[index - 2] = set_byte16(self.win_v[index - 2], addr & 1, val);
+                }
+            },
+            0x048..=0x049 => self.winin = set_byte16(self.winin, addr & 1, val) & 0x3F3F,
+            0x04A..=0x04B => self.winout = set_byte16(self.winout, addr & 1, val) & 0x3F3F,
+            0x04C..=0x04D => self.mosaic = set_byte16(self.mosaic, addr & 1, val),
+            0x050..=0x051 => self.bldcnt = set_byte16(self.bldcnt, addr & 1, val) & 0x3FFF,
+            0x052..=0x053 => self.bldalpha = set_byte16(self.bldalpha, addr & 1, val) & 0x1F1F,
+            0x054 => self.bldy = val as u16 & 0x1F,
+            // VCOUNTは読み出し専用
+            _ => {},
+        }
+    }
+
+    fn update(&mut self, _now: u64) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ppu_timing() {
+        let mut ppu = Ppu::new();
+        let mut irq = InterruptController::new();
+        // VBlank/HBlank/VCount(=100)割り込み
+        ppu.write(0x0400_0004, 0x38);
+        ppu.write(0x0400_0005, 100);
+
+        for _ in 0..100 {
+            ppu.start_hblank(&mut irq);
+            ppu.start_hdraw(&mut irq);
+        }
+        assert_eq!(ppu.read(0x0400_0006), 100);
+        assert_eq!(ppu.read(0x0400_0004) & 0x07, 0x04);
+        assert_eq!(irq.flag(), 0x0006);
+
+        for _ in 100..160 {
+            ppu.start_hblank(&mut irq);
+            ppu.start_hdraw(&mut irq);
+        }
+        assert_eq!(ppu.read(0x0400_0004) & 0x07, 0x01);
+        assert_eq!(irq.flag(), 0x0007);
+        assert!(ppu.take_frame());
+
+        for _ in 160..227 {
+            ppu.start_hblank(&mut irq);
+            ppu.start_hdraw(&mut irq);
+        }
+        assert_eq!(ppu.read(0x0400_0004) & 0x07, 0x00);
+        ppu.start_hblank(&mut irq);
+        ppu.start_hdraw(&mut irq);
+        assert_eq!(ppu.vcount(), 0);
+    }
+
+    #[test]
+    fn test_bitmap_modes() {
+        let mut ppu = Ppu::new();
+        let mut vram: Vec<u8> = vec![0; 96 * 1024];
+        let mut pram: Vec<u8> = vec![0; 1024];
+        let oam: Vec<u8> = vec![0; 1024];
+        pram[0..2].copy_from_slice(&0x7C00u16.to_le_bytes()); // 背景色: 青
+        pram[2..4].copy_from_slice(&0x001Fu16.to_le_bytes()); // パレット1: 赤
+
+        // Mode3
+        vram[0..2].copy_from_slice(&0x03E0u16.to_le_bytes());
+        ppu.write(0x0400_0000, 0x03);
+        ppu.write(0x0400_0001, 0x04);
+        ppu.render_line(&vram, &pram, &oam);
+        assert_eq!(ppu.framebuffer()[0], 0x0000_FF00);
+
+        // Mode4 (フレーム1, パレット0は透明)
+        vram[_FRAME_1_OFFSET + 1] = 1;
+        ppu.write(0x0400_0000, 0x14);
+        ppu.render_line(&vram, &pram, &oam);
+        assert_eq!(ppu.framebuffer()[0], 0x0000_00FF);
+        assert_eq!(ppu.framebuffer()[1], 0x00FF_0000);
+
+        // Mode5 (160x128の外側は背景色)
+        ppu.write(0x0400_0000, 0x05);
+        ppu.render_line(&vram, &pram, &oam);
+        assert_eq!(ppu.framebuffer()[0], 0x0000_FF00);
+        assert_eq!(ppu.framebuffer()[200], 0x0000_00FF);
+
+        // 強制ブランク
+        ppu.write(0x0400_0000, 0x85);
+        ppu.render_line(&vram, &pram, &oam);
+        assert_eq!(ppu.framebuffer()[0], 0x00FF_FFFF);
+    }
+
+    #[test]
+    fn test_text_bg() {
+        let mut ppu = Ppu::new();
+        let mut vram: Vec<u8> = vec![0; 96 * 1024];
+        let mut pram: Vec<u8> = vec![0; 1024];
+        let oam: Vec<u8> = vec![0; 1024];
+        pram[0x22..0x24].copy_from_slice(&0x001Fu16.to_le_bytes()); // パレット1-1: 赤
+        pram[0x04..0x06].copy_from_slice(&0x03E0u16.to_le_bytes()); // パレット0-2: 緑
+
+        // BG0: 4bpp, Char Base 0, Screen Base 0x0800 (ブロック1), 優先度1
+        // タイル1: 左上のピクセルのみカラー1
+        vram[0x20] = 0x01;
+        // (0,0) にタイル1, パレット1, 水平反転
+        vram[0x0800..0x0802].copy_from_slice(&0x1401u16.to_le_bytes());
+        ppu.write(0x0400_0008, 0x01);
+        ppu.write(0x0400_0009, 0x01);
+        ppu.write(0x0400_0000, 0x00);
+        ppu.write(0x0400_0001, 0x01);
+        ppu.render_line(&vram, &pram, &oam);
+        assert_eq!(ppu.framebuffer()[7], 0x00FF_0000);
+        assert_eq!(ppu.framebuffer()[0], 0);
+
+        // スクロール (HOFS=4)
+        ppu.write(0x0400_0010, 0x04);
+        ppu.render_line(&vram, &pram, &oam);
+        assert_eq!(ppu.framebuffer()[3], 0x00FF_0000);
+
+        // BG1: 8bpp, Screen Base 0x1000, 優先度0 (BG0より手前)
+        vram[0x4000 + 64 * 2 + 3] = 0x02;
+        vram[0x1000..0x1002].copy_from_slice(&0x0002u16.to_le_bytes());
+        ppu.write(0x0400_000A, 0x84);
+        ppu.write(0x0400_000B, 0x02);
+        ppu.write(0x0400_0001, 0x03);
+        ppu.render_line(&vram, &pram, &oam);
+        assert_eq!(ppu.framebuffer()[3], 0x0000_FF00);
+        // 優先度を入れ替え
+        ppu.write(0x0400_000A, 0x86);
+        ppu.render_line(&vram, &pram, &oam);
+        assert_eq!(ppu.framebuffer()[3], 0x00FF_0000);
+    }
+
+    #[test]
+    fn test_affine_bg() {
+        let mut ppu = Ppu::new();
+        let mut irq = InterruptController::new();
+        let mut vram: Vec<u8> = vec![0; 96 * 1024];
+        let mut pram: Vec<u8> = vec![0; 1024];
+        let oam: Vec<u8> = vec![0; 1024];
+        pram[0x02..0x04].copy_from_slice(&0x001Fu16.to_le_bytes());
+
+        // BG2 (Mode1): 128x128, Screen Base 0x0800, タイル1 = 全ピクセルカラー1
+        vram[0x40..0x80].fill(1);
+        vram[0x0800 + 16 + 1] = 1; // タイル(1,1)
+        ppu.write(0x0400_000C, 0x00);
+        ppu.write(0x0400_000D, 0x01);
+        ppu.write(0x0400_0000, 0x01);
+        ppu.write(0x0400_0001, 0x04);
+        // 2倍拡大 (PA=PD=0x80), 基準点 (8.0, 8.0)
+        ppu.write(0x0400_0020, 0x80);
+        ppu.write(0x0400_0021, 0x00);
+        ppu.write(0x0400_0026, 0x80);
+        ppu.write(0x0400_0027, 0x00);
+        ppu.write(0x0400_0029, 0x08);
+        ppu.write(0x0400_002D, 0x08);
+        ppu.render_line(&vram, &pram, &oam);
+        assert_eq!(ppu.framebuffer()[15], 0x00FF_0000);
+        assert_eq!(ppu.framebuffer()[16], 0);
+
+        // ライン毎にPDを加算 (16ライン目でタイル(1,2))
+        for _ in 0..16 {
+            ppu.start_hblank(&mut irq);
+            ppu.start_hdraw(&mut irq);
+        }
+        ppu.render_line(&vram, &pram, &oam);
+        assert_eq!(ppu.framebuffer()[16 * _SCREEN_WIDTH], 0);
+
+        // 画面外 (折り返し無効)
+        ppu.write(0x0400_002B, 0x08);
+        ppu.render_line(&vram, &pram, &oam);
+        assert_eq!(ppu.framebuffer()[16 * _SCREEN_WIDTH], 0);
+        // 折り返し有効 (基準点Y = -120.0 → 128x128で折り返して8.0)
+        ppu.write(0x0400_000D, 0x21);
+        ppu.write(0x0400_002C, 0x00);
+        ppu.write(0x0400_002D, 0x88);
+        ppu.write(0x0400_002E, 0xFF);
+        ppu.write(0x0400_002F, 0x0F);
+        ppu.render_line(&vram, &pram, &oam);
+        assert_eq!(ppu.framebuffer()[16 * _SCREEN_WIDTH], 0x00FF_0000);
+    }
+
+    // OAMエントリ設定 (attr0, attr1, attr2)
+    fn set_obj(oam: &mut [u8], n: usize, attr: [u16; 3]) {
+        for (i, a) in attr.iter().enumerate() {
+            oam[n * 8 + i * 2..n * 8 + i * 2 + 2].copy_from_slice(&a.to_le_bytes());
+        }
+    }
+
+    fn obj_test_memory() -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+        let mut vram: Vec<u8> = vec![0; 96 * 1024];
+        let mut pram: Vec<u8> = vec![0; 1024];
+        let mut oam: Vec<u8> = vec![0; 1024];
+        // 全OBJ非表示
+        for n in 0..128 {
+            set_obj(&mut oam, n, [0x0200, 0, 0]);
+        }
+        pram[0x222..0x224].copy_from_slice(&0x001Fu16.to_le_bytes()); // OBJパレット1-1: 赤
+        pram[0x224..0x226].copy_from_slice(&0x03E0u16.to_le_bytes()); // OBJパレット1-2: 緑
+        // OBJタイル1 (4bpp): 左上のみカラー1, タイル2: 全ピクセルカラー1
+        vram[0x10020] = 0x01;
+        vram[0x10040..0x10060].fill(0x11);
+        (vram, pram, oam)
+    }
+
+    #[test]
+    fn test_obj_render() {
+        let mut ppu = Ppu::new();
+        let (mut vram, mut pram, mut oam) = obj_test_memory();
+        ppu.write(0x0400_0001, 0x11); // BG0 + OBJ, Mode0
+
+        // 8x8, X=10, 水平反転, パレット1
+        set_obj(&mut oam, 0, [0x0000, 0x100A, 0x1001]);
+        ppu.render_line(&vram, &pram, &oam);
+        assert_eq!(ppu.framebuffer()[17], 0x00FF_0000);
+        assert_eq!(ppu.framebuffer()[10], 0);
+
+        // 画面左端をはみ出すX座標 (X=-7, 水平反転)
+        set_obj(&mut oam, 0, [0x0000, 0x11F9, 0x1001]);
+        ppu.render_line(&vram, &pram, &oam);
+        assert_eq!(ppu.framebuffer()[0], 0x00FF_0000);
+
+        // BG0(優先度0)の後ろ (OBJ優先度1)
+        pram[0x02..0x04].copy_from_slice(&0x7C00u16.to_le_bytes());
+        vram[0x20] = 0x01;
+        vram[0x00..0x02].copy_from_slice(&0x0001u16.to_le_bytes());
+        set_obj(&mut oam, 0, [0x0000, 0x0000, 0x1401]);
+        ppu.render_line(&vram, &pram, &oam);
+        assert_eq!(ppu.framebuffer()[0], 0x0000_00FF);
+        // 同じ優先度はOBJが手前
+        set_obj(&mut oam, 0, [0x0000, 0x0000, 0x1001]);
+        ppu.render_line(&vram, &pram, &oam);
+        assert_eq!(ppu.framebuffer()[0], 0x00FF_0000);
+
+        // OBJウィンドウは表示しない
+        set_obj(&mut oam, 0, [0x0800, 0x0000, 0x1001]);
+        ppu.render_line(&vram, &pram, &oam);
+        assert_eq!(ppu.framebuffer()[0], 0x0000_00FF);
+    }
+
+    #[test]
+    fn test_obj_mapping() {
+        let mut ppu = Ppu::new();
+        let (mut vram, pram, mut oam) = obj_test_memory();
+        // 16x16 (タイル1~), 右下のタイルの左上ピクセル
+        // 2次元: タイル1 + 32 + 1, 1次元: タイル1 + 2 + 1
+        vram[0x10000 + 34 * 32] = 0x01;
+        vram[0x10000 + 4 * 32] = 0x02;
+        set_obj(&mut oam, 0, [0x00F8, 0x4000, 0x1001]); // Y=-8
+
+        ppu.write(0x0400_0001, 0x10);
+        ppu.render_line(&vram, &pram, &oam);
+        assert_eq!(ppu.framebuffer()[8], 0x00FF_0000);
+        ppu.write(0x0400_0000, 0x40);
+        ppu.render_line(&vram, &pram, &oam);
+        assert_eq!(ppu.framebuffer()[8], 0x0000_FF00);
+    }
+
+    #[test]
+    fn test_obj_affine() {
+        let mut ppu = Ppu::new();
+        let (vram, pram, mut oam) = obj_test_memory();
+        ppu.write(0x0400_0001, 0x10);
+        // 回転拡大グループ0: 2倍拡大 (PA=PD=0x80)
+        oam[6..8].copy_from_slice(&0x0080u16.to_le_bytes());
+        oam[30..32].copy_from_slice(&0x0080u16.to_le_bytes());
+        // 8x8 タイル2, 回転拡大+倍角 (16x16の表示領域), X=0, Y=-8 (表示領域の中央のライン)
+        set_obj(&mut oam, 1, [0x03F8, 0x0000, 0x1002]);
+        ppu.render_line(&vram, &pram, &oam);
+        assert_eq!(ppu.framebuffer()[0], 0x00FF_0000);
+        assert_eq!(ppu.framebuffer()[15], 0x00FF_0000);
+        assert_eq!(ppu.framebuffer()[16], 0);
+        // 倍角なし (8x8の表示領域に中央4x4を拡大)
+        set_obj(&mut oam, 1, [0x01FC, 0x0000, 0x1002]);
+        ppu.render_line(&vram, &pram, &oam);
+        assert_eq!(ppu.framebuffer()[7], 0x00FF_0000);
+        assert_eq!(ppu.framebuffer()[8], 0);
+    }
+
+    #[test]
+    fn test_obj_cycle_budget() {
+        let mut ppu = Ppu::new();
+        let (vram, pram, mut oam) = obj_test_memory();
+        ppu.write(0x0400_0001, 0x10);
+        // 64x64 (透明) ×18 = 1152サイクル
+        for n in 0..18 {
+            set_obj(&mut oam, n, [0x0000, 0xC000, 0x0000]);
+        }
+        set_obj(&mut oam, 18, [0x0000, 0x0064, 0x1001]);
+        ppu.render_line(&vram, &pram, &oam);
+        assert_eq!(ppu.framebuffer()[100], 0x00FF_0000);
+        // H-Blank Interval Free (954サイクル)
+        ppu.write(0x0400_0000, 0x20);
+        ppu.render_line(&vram, &pram, &oam);
+        assert_eq!(ppu.framebuffer()[100], 0);
+    }
+
+    fn write16(ppu: &mut Ppu, addr: u32, val: u16) {
+        ppu.write(addr, val as u8);
+        ppu.write(addr + 1, (val >> 8) as u8);
+    }
+
+    // BG0: 全面カラー1 (赤), BG1: 全面カラー2 (緑) の2枚重ね
+    fn blend_test_memory() -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+        let (mut vram, mut pram, oam) = obj_test_memory();
+        pram[0x02..0x04].copy_from_slice(&0x001Fu16.to_le_bytes());
+        pram[0x04..0x06].copy_from_slice(&0x03E0u16.to_le_bytes());
+        vram[0x20..0x40].fill(0x11);
+        vram[0x40..0x60].fill(0x22);
+        for i in 0..32 {
+            vram[0x0800 + i * 2] = 1;
+            vram[0x1000 + i * 2] = 2;
+        }
+        (vram, pram, oam)
+    }
+
+    fn blend_test_ppu() -> Ppu {
+        let mut ppu = Ppu::new();
+        write16(&mut ppu, 0x0400_0008, 0x0100); // BG0: Screen Base 0x0800
+        write16(&mut ppu, 0x0400_000A, 0x0201); // BG1: Screen Base 0x1000, 優先度1
+        write16(&mut ppu, 0x0400_0000, 0x1300); // BG0 + BG1 + OBJ
+        ppu
+    }
+
+    #[test]
+    fn test_window() {
+        let mut ppu = blend_test_ppu();
+        let (vram, pram, mut oam) = blend_test_memory();
+        // WIN0: X=[8,16), Y=[0,8) の内側はBG1のみ, 外側はBG0+BG1
+        write16(&mut ppu, 0x0400_0040, 0x0810);
+        write16(&mut ppu, 0x0400_0044, 0x0008);
+        write16(&mut ppu, 0x0400_0048, 0x0002);
+        write16(&mut ppu, 0x0400_004A, 0x0003);
+        ppu.write(0x0400_0001, 0x33);
+        ppu.render_line(&vram, &pram, &oam);
+        assert_eq!(ppu.framebuffer()[7], 0x00FF_0000);
+        assert_eq!(ppu.framebuffer()[8], 0x0000_FF00);
+        assert_eq!(ppu.framebuffer()[16], 0x00FF_0000);
+        assert_eq!(ppu.read(0x0400_0048), 0x02);
+        assert_eq!(ppu.read(0x0400_0040), 0);
+
+        // X1 > X2 は折り返し
+        write16(&mut ppu, 0x0400_0040, 0xF004);
+        ppu.render_line(&vram, &pram, &oam);
+        assert_eq!(ppu.framebuffer()[3], 0x0000_FF00);
+        assert_eq!(ppu.framebuffer()[4], 0x00FF_0000);
+
+        // OBJウィンドウ (8x8, X=100) の内側は何も表示しない
+        set_obj(&mut oam, 0, [0x0800, 0x0064, 0x0002]);
+        ppu.write(0x0400_0001, 0x93);
+        ppu.render_line(&vram, &pram, &oam);
+        assert_eq!(ppu.framebuffer()[100], 0);
+        assert_eq!(ppu.framebuffer()[108], 0x00FF_0000);
+    }
+
+    #[test]
+    fn test_blending() {
+        let mut ppu = blend_test_ppu();
+        let (vram, pram, mut oam) = blend_test_memory();
+        // 半透明: BG0(第1対象) 8/16 + BG1(第2対象) 8/16
+        write16(&mut ppu, 0x0400_0050, 0x0241);
+        write16(&mut ppu, 0x0400_0052, 0x0808);
+        ppu.render_line(&vram, &pram, &oam);
+        assert_eq!(ppu.framebuffer()[0], bgr555_to_rgb(0x01EF));
+        // 第2対象でなければ効果なし
+        write16(&mut ppu, 0x0400_0050, 0x0441);
+        ppu.render_line(&vram, &pram, &oam);
+        assert_eq!(ppu.framebuffer()[0], 0x00FF_0000);
+
+        // 明るく (EVY=8), 暗く (EVY=8)
+        ppu.write(0x0400_0054, 0x08);
+        write16(&mut ppu, 0x0400_0050, 0x0081);
+        ppu.render_line(&vram, &pram, &oam);
+        assert_eq!(ppu.framebuffer()[0], bgr555_to_rgb(0x3DFF));
+        write16(&mut ppu, 0x0400_0050, 0x00C1);
+        ppu.render_line(&vram, &pram, &oam);
+        assert_eq!(ppu.framebuffer()[0], bgr555_to_rgb(0x0010));
+        // ウィンドウで特殊効果を無効化
+        write16(&mut ppu, 0x0400_004A, 0x001F);
+        ppu.write(0x0400_0001, 0x53);
+        ppu.render_line(&vram, &pram, &oam);
+        assert_eq!(ppu.framebuffer()[0], 0x00FF_0000);
+
+        // 半透明OBJ + BG1 (BLDCNTが暗くでも半透明処理, 第1対象の指定も不要)
+        ppu.write(0x0400_0001, 0x12);
+        write16(&mut ppu, 0x0400_0050, 0x02C0);
+        set_obj(&mut oam, 0, [0x0400, 0x0000, 0x1002]);
+        ppu.render_line(&vram, &pram, &oam);
+        assert_eq!(ppu.framebuffer()[0], bgr555_to_rgb(0x01EF));
+        // 第2対象がなければ通常のOBJ
+        write16(&mut ppu, 0x0400_0050, 0x00C0);
+        ppu.render_line(&vram, &pram, &oam);
+        assert_eq!(ppu.framebuffer()[0], 0x00FF_0000);
+    }
+
+    #[test]
+    fn test_mosaic() {
+        let mut ppu = Ppu::new();
+        let (mut vram, pram, oam) = blend_test_memory();
+        // BG0: タイル3 の各行先頭ピクセルのみカラー1
+        for row in 0..8 {
+            vram[0x60 + row * 4] = 0x01;
+        }
+        vram[0x0800..0x0802].copy_from_slice(&0x0003u16.to_le_bytes());
+        write16(&mut ppu, 0x0400_0008, 0x0140);
+        write16(&mut ppu, 0x0400_0000, 0x0100);
+        ppu.render_line(&vram, &pram, &oam);
+        assert_eq!(ppu.framebuffer()[0], 0x00FF_0000);
+        assert_eq!(ppu.framebuffer()[1], 0);
+        // 水平4ピクセル単位
+        ppu.write(0x0400_004C, 0x03);
+        ppu.render_line(&vram, &pram, &oam);
+        assert_eq!(ppu.framebuffer()[3], 0x00FF_0000);
+        assert_eq!(ppu.framebuffer()[4], 0);
+    }
+}
@@ -0,0 +1,181 @@
+// =========================================================================
+// [Scheduler]
+// イベントスケジューラ (マスタクロックと時刻順のイベントキュー)
+// =========================================================================
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+// イベントの種類
+#[allow(dead_code)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+pub enum EventType {
+    HDraw,             // PPU: ライン描画開始 (VBlank/VCountはライン番号で判定)
+    HBlank,            // PPU: HBlank開始
+    TimerOverflow(u8), // タイマ0~3 オーバーフロー
+    DmaTransfer(u8),   // DMA0~3 転送開始
+    ApuSample,         // APU: サンプル生成
+    SerialTransfer,    // シリアル転送完了
+}
+
+impl EventType {
+    // セーブステート用の数値表現 (種類, 引数)
+    fn to_raw(self) -> (u8, u8) {
+        match self {
+            EventType::HDraw            => (0, 0),
+            EventType::HBlank           => (1, 0),
+            EventType::TimerOverflow(n) => (2, n),
+            EventType::DmaTransfer(n)   => (3, n),
+            EventType::ApuSample        => (4, 0),
+            EventType::SerialTransfer   => (5, 0),
+        }
+    }
+
+    fn from_raw(kind: u8, arg: u8) -> Option<Self> {
+        match kind {
+            0 => Some(EventType::HDraw),
+            1 => Some(EventType::HBlank),
+            2 => Some(EventType::TimerOverflow(arg)),
+            3 => Some(EventType::DmaTransfer(arg)),
+            4 => Some(EventType::ApuSample),
+            5 => Some(EventType::SerialTransfer),
+            _ => None,
+        }
+    }
+}
+
+// キューの要素 |時刻|登録順|種類|
+type Event = Reverse<(u64, u64, EventType)>;
+// セーブステートの要素 |時刻|登録順|種類|引数|
+type RawEvent = (u64, u64, u8, u8);
+
+pub struct Scheduler {
+    now: u64,                 // マスタクロック (CPUサイクル)
+    seq: u64,                 // 登録順 (同時刻のイベントはFIFO)
+    events: BinaryHeap<Event>,
+}
+
+#[allow(dead_code)]
+impl Scheduler {
+    pub fn new() -> Self {
+        Scheduler {
+            now: 0,
+            seq: 0,
+            events: BinaryHeap::new(),
+        }
+    }
+
+    // 現在時刻
+    pub fn now(&self) -> u64 {
+        self.now
+    }
+
+    // 時刻を進める
+    pub fn advance(&mut self, cycles: u64) {
+        self.now += cycles;
+    }
+
+    // 時刻を指定値まで進める (HALT等の早送り用)
+    pub fn advance_to(&mut self, time: u64) {
+        if time > self.now {
+            self.now = time;
+        }
+    }
+
+    // 現在時刻からdelayサイクル後にイベント登録
+    pub fn schedule(&mut self, kind: EventType, delay: u64) {
+        let time: u64 = self.now + delay;
+        self.schedule_at(kind, time);
+    }
+
+    // 絶対時刻でイベント登録
+    pub fn schedule_at(&mut self, kind: EventType, time: u64) {
+        self.events.push(Reverse((time, self.seq, kind)));
+        self.seq += 1;
+    }
+
+    // 登録済みイベントの取り消し
+    pub fn cancel(&mut self, kind: EventType) {
+        self.events.retain(|Reverse((_, _, k))| *k != kind);
+    }
+
+    // 次のイベント時刻 (イベントが無い場合はu64::MAX)
+    pub fn next_deadline(&self) -> u64 {
+        match self.events.peek() {
+            Some(Reverse((time, _, _))) => *time,
+            None => u64::MAX,
+        }
+    }
+
+    // 処理すべきイベントがあるか
+    pub fn is_pending(&self) -> bool {
+        self.now >= self.next_deadline()
+    }
+
+    // 現在時刻までに発生したイベントを1つ取り出す (戻り値は (イベント, 発生予定時刻))
+    pub fn pop_due(&mut self) -> Option<(EventType, u64)> {
+        if !self.is_pending() {
+            return None;
+        }
+        self.events.pop().map(|Reverse((time, _, kind))| (kind, time))
+    }
+
+    // セーブステート書き出し
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut events: Vec<RawEvent> = self.events.iter()
+            .map(|Reverse((time, seq, kind))| {
+                let (k, arg) = kind.to_raw();
+                (*time, *seq, k, arg)
+            })
+            .collect();
+        events.sort();
+        bincode::serialize(&(self.now, self.seq, events)).unwrap()
+    }
+
+    // セーブステート読み込み
+    pub fn deserialize(data: &[u8]) -> Result<Self, bincode::Error> {
+        let (now, seq, events): (u64, u64, Vec<RawEvent>) = bincode::deserialize(data)?;
+        let mut heap: BinaryHeap<Event> = BinaryHeap::new();
+        for (time, s, k, arg) in events {
+            match EventType::from_raw(k, arg) {
+                Some(kind) => heap.push(Reverse((time, s, kind))),
+                None => return Err(Box::new(bincode::ErrorKind::Custom(format!("Unknown event type: {}", k)))),
+            }
+        }
+        Ok(Scheduler { now, seq, events: heap })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schedule_order() {
+        let mut sched = Scheduler::new();
+        sched.schedule(EventType::HBlank, 960);
+        sched.schedule(EventType::TimerOverflow(1), 100);
+        sched.schedule(EventType::TimerOverflow(0), 100);
+        assert_eq!(sched.next_deadline(), 100);
+        assert_eq!(sched.pop_due(), None);
+
+        sched.advance(1000);
+        assert_eq!(sched.pop_due(), Some((EventType::TimerOverflow(1), 100)));
+        assert_eq!(sched.pop_due(), Some((EventType::TimerOverflow(0), 100)));
+        assert_eq!(sched.pop_due(), Some((EventType::HBlank, 960)));
+        assert_eq!(sched.pop_due(), None);
+    }
+
+    #[test]
+    fn test_serialize() {
+        let mut sched = Scheduler::new();
+        sched.advance(50);
+        sched.schedule(EventType::HDraw, 1232);
+        sched.schedule(EventType::DmaTransfer(3), 10);
+        sched.cancel(EventType::HDraw);
+
+        let restored = Scheduler::deserialize(&sched.serialize()).unwrap();
+        assert_eq!(restored.now(), 50);
+        assert_eq!(restored.next_deadline(), 60);
+        assert_eq!(restored.events.len(), 1);
+    }
+}
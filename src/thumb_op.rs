@@ -1,7 +1,11 @@
 // use bitflags::Flags;
 use cpu::*;
+use barrel_shifter::*;
+use bus::MemoryAccess;
+use arm_op::multiply_cycles;
+use once_cell::sync::Lazy;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum ThumbFormat {
     Format01, // Format01 ... Move shifted register
     Format02, // Format02 ... Add and subtract
@@ -22,6 +26,7 @@ pub enum ThumbFormat {
     Format17, // Format17 ... Software interrupt
     Format18, // Format18 ... Unconditional branch
     Format19, // Format19 ... Long branch with link
+    Undefined, // Undefined
 }
 
 #[derive(Debug, PartialEq)]
@@ -30,7 +35,7 @@ pub enum ThumbInstruction {
     MoveShiftedRegister(u8, i8, u8, u8), // |Op|Offset5|Rs|Rd|
 
     // Format02: 加算/減算
-    AddSubtract(u8, i8, u8, u8), // |Op|Rn/Offset3|Rs|Rd|
+    AddSubtract(u8, i8, u8, u8), // |I,Op|Rn/Offset3|Rs|Rd|
 
     // Format03: レジスタ移動/比較/加算/減算（即値）
     MoveCompareAddSubtractImmediate(u8, u8, i8), // |Op|Rd|Offset8|
@@ -82,6 +87,9 @@ pub enum ThumbInstruction {
 
     // Format19: リンク付きロング分岐
     LongBranchWithLink(u8, i16), // |H|Offset|
+
+    // 未定義命令
+    Undefined(u16), // |Instruction|
 }
 
 fn decode_format01(instruction: u16) -> ThumbInstruction {
@@ -93,7 +101,7 @@ fn decode_format01(instruction: u16) -> ThumbInstruction {
 }
 
 fn decode_format02(instruction: u16) -> ThumbInstruction {
-    let op: u8 = ((instruction & 0b0000_0010_0000_0000) >> 9) as u8;
+    let op: u8 = ((instruction & 0b0000_0110_0000_0000) >> 9) as u8;
     let rn: i8 = ((instruction & 0b0000_0001_1100_0000) >> 6) as i8;
     let rs: u8 = ((instruction & 0b0000_0000_0011_1000) >> 3) as u8;
     let rd: u8 = (instruction & 0b0000_0000_0000_0111) as u8;
@@ -210,42 +218,63 @@ fn decode_format17(instruction: u16) -> ThumbInstruction {
 }
 
 fn decode_format18(instruction: u16) -> ThumbInstruction {
-    let offset11: i16 = (instruction & 0b0000_0111_1111_1111) as i16;
+    // Offset11を符号拡張
+    let offset11: i16 = (((instruction & 0b0000_0111_1111_1111) << 5) as i16) >> 5;
     ThumbInstruction::UnconditionalBranch(offset11)
 }
 
 fn decode_format19(instruction: u16) -> ThumbInstruction {
-    let h: u8      = ((instruction & 0b0000_1000_0000_0000) >> 8) as u8;
+    let h: u8      = ((instruction & 0b0000_1000_0000_0000) >> 11) as u8;
     let offset: i16 = (instruction & 0b0000_0111_1111_1111) as i16;
     ThumbInstruction::LongBranchWithLink(h, offset)
 }
 
-pub fn thumb_format_decode(instruction: u16) -> (ThumbFormat, ThumbInstruction) {
-    // Thumb命令のフォーマット判別（※ビットが立つ範囲で判定する）
-    // FIXME Format1,2、Format16,17で最後のビット範囲が被る
-    // ※Format16のcondが0x0FはFormat17なので0b1101_1110_1111_1111までに変更した（あってるかは不明）
-    let format: ThumbFormat = match instruction {
-        0b0000_0000_0000_0000..=0b0001_1111_1111_1111 => ThumbFormat::Format01,
-        0b0001_1100_0000_0000..=0b0001_1111_1111_1111 => ThumbFormat::Format02,
+// Thumb命令のフォーマット判別テーブル (上位10bitで引く)
+static THUMB_FORMAT_LUT: Lazy<[ThumbFormat; 1024]> = Lazy::new(|| {
+    let mut lut: [ThumbFormat; 1024] = [ThumbFormat::Undefined; 1024];
+    for (i, entry) in lut.iter_mut().enumerate() {
+        *entry = thumb_format_classify((i as u16) << 6);
+    }
+    lut
+});
+
+// Thumb命令のフォーマット判別（※ビットが立つ範囲で判定する、テーブル生成用）
+// ※Format16のcond=0x0Eは未定義命令, cond=0x0FはFormat17
+// ※Format07/08はBit9で分かれるので範囲を分割
+fn thumb_format_classify(instruction: u16) -> ThumbFormat {
+    match instruction {
+        0b0000_0000_0000_0000..=0b0001_0111_1111_1111 => ThumbFormat::Format01,
+        0b0001_1000_0000_0000..=0b0001_1111_1111_1111 => ThumbFormat::Format02,
         0b0010_0000_0000_0000..=0b0011_1111_1111_1111 => ThumbFormat::Format03,
         0b0100_0000_0000_0000..=0b0100_0011_1111_1111 => ThumbFormat::Format04,
         0b0100_0100_0000_0000..=0b0100_0111_1111_1111 => ThumbFormat::Format05,
         0b0100_1000_0000_0000..=0b0100_1111_1111_1111 => ThumbFormat::Format06,
-        0b0101_0000_0000_0000..=0b0101_1101_1111_1111 => ThumbFormat::Format07,
-        0b0101_0000_0000_0000..=0b0101_1111_1111_1111 => ThumbFormat::Format08,
+        0b0101_0000_0000_0000..=0b0101_0001_1111_1111 |
+        0b0101_0100_0000_0000..=0b0101_0101_1111_1111 |
+        0b0101_1000_0000_0000..=0b0101_1001_1111_1111 |
+        0b0101_1100_0000_0000..=0b0101_1101_1111_1111 => ThumbFormat::Format07,
+        0b0101_0010_0000_0000..=0b0101_0011_1111_1111 |
+        0b0101_0110_0000_0000..=0b0101_0111_1111_1111 |
+        0b0101_1010_0000_0000..=0b0101_1011_1111_1111 |
+        0b0101_1110_0000_0000..=0b0101_1111_1111_1111 => ThumbFormat::Format08,
         0b0110_0000_0000_0000..=0b0111_1111_1111_1111 => ThumbFormat::Format09,
         0b1000_0000_0000_0000..=0b1000_1111_1111_1111 => ThumbFormat::Format10,
         0b1001_0000_0000_0000..=0b1001_1111_1111_1111 => ThumbFormat::Format11,
         0b1010_0000_0000_0000..=0b1010_1111_1111_1111 => ThumbFormat::Format12,
         0b1011_0000_0000_0000..=0b1011_0000_1111_1111 => ThumbFormat::Format13,
-        0b1011_0100_0000_0000..=0b1011_1101_1111_1111 => ThumbFormat::Format14,
+        0b1011_0100_0000_0000..=0b1011_0101_1111_1111 |
+        0b1011_1100_0000_0000..=0b1011_1101_1111_1111 => ThumbFormat::Format14,
         0b1100_0000_0000_0000..=0b1100_1111_1111_1111 => ThumbFormat::Format15,
-        0b1101_0000_0000_0000..=0b1101_1110_1111_1111 => ThumbFormat::Format16,
+        0b1101_0000_0000_0000..=0b1101_1101_1111_1111 => ThumbFormat::Format16,
         0b1101_1111_0000_0000..=0b1101_1111_1111_1111 => ThumbFormat::Format17,
         0b1110_0000_0000_0000..=0b1110_0111_1111_1111 => ThumbFormat::Format18,
         0b1111_0000_0000_0000..=0b1111_1111_1111_1111 => ThumbFormat::Format19,
-        _ => panic!("Unknown Thumb instruction format"),
-    };
+        _ => ThumbFormat::Undefined,
+    }
+}
+
+pub fn thumb_format_decode(instruction: u16) -> (ThumbFormat, ThumbInstruction) {
+    let format: ThumbFormat = THUMB_FORMAT_LUT[(instruction >> 6) as usize];
 
     // Thumb命令のフォーマットに応じて命令をでコード
     match format {
@@ -268,27 +297,57 @@ pub fn thumb_format_decode(instruction: u16) -> (ThumbFormat, ThumbInstruction)
         ThumbFormat::Format17 => (format, decode_format17(instruction)),
         ThumbFormat::Format18 => (format, decode_format18(instruction)),
         ThumbFormat::Format19 => (format, decode_format19(instruction)),
+        ThumbFormat::Undefined => (format, ThumbInstruction::Undefined(instruction)),
     }
 }
 
-// ADD Rd, Rs
-fn add(_cpu: &mut CPU, rs: u8, rd: u8) {
-    let _ret: u32 = _cpu.reg.r[rd as usize].wrapping_add(_cpu.reg.r[rs as usize]);
+// ADC Rd, Rs
+fn adc(_cpu: &mut CPU, rs: u8, rd: u8) {
+    let _carry: bool = _cpu.reg.cpsr.contains(PSR::C);
+    let _ret: u32 = _cpu.alu_add(_cpu.reg.r[rd as usize], _cpu.reg.r[rs as usize], _carry, true);
     _cpu.reg.r[rd as usize] = _ret;
-    _cpu.psr_op_update(_ret, false, false);
 }
 
-// SUB Rd, Rs
-fn sub(_cpu: &mut CPU, rs: u8, rd: u8) {
-    let _ret: u32 = _cpu.reg.r[rd as usize].wrapping_sub(_cpu.reg.r[rs as usize]);
+// SBC Rd, Rs
+fn sbc(_cpu: &mut CPU, rs: u8, rd: u8) {
+    let _carry: bool = _cpu.reg.cpsr.contains(PSR::C);
+    let _ret: u32 = _cpu.alu_sub(_cpu.reg.r[rd as usize], _cpu.reg.r[rs as usize], _carry, true);
+    _cpu.reg.r[rd as usize] = _ret;
+}
+
+// NEG Rd, Rs (Rd = 0 - Rs)
+fn neg(_cpu: &mut CPU, rs: u8, rd: u8) {
+    let _ret: u32 = _cpu.alu_sub(0, _cpu.reg.r[rs as usize], true, true);
     _cpu.reg.r[rd as usize] = _ret;
-    _cpu.psr_op_update(_ret, true, false);
 }
 
 // CMP Rd, Rs
 fn cmp(_cpu: &mut CPU, rs: u8, rd: u8) {
-    let _ret: u32 = _cpu.reg.r[rd as usize].wrapping_sub(_cpu.reg.r[rs as usize]);
-    _cpu.psr_op_update(_ret, true, false);
+    _cpu.alu_sub(_cpu.reg.r[rd as usize], _cpu.reg.r[rs as usize], true, true);
+}
+
+// CMN Rd, Rs
+fn cmn(_cpu: &mut CPU, rs: u8, rd: u8) {
+    _cpu.alu_add(_cpu.reg.r[rd as usize], _cpu.reg.r[rs as usize], false, true);
+}
+
+// MUL Rd, Rs (Cは破壊されるがARMv4では変化なしとして扱う、Vは変化なし)
+fn mul(_cpu: &mut CPU, rs: u8, rd: u8) {
+    let rd_val: u32 = _cpu.reg.r[rd as usize];
+    let _ret: u32 = rd_val.wrapping_mul(_cpu.reg.r[rs as usize]);
+    _cpu.reg.r[rd as usize] = _ret;
+    let _carry: bool = _cpu.reg.cpsr.contains(PSR::C);
+    _cpu.psr_logic_update(_ret, _carry);
+    // Cycle += 1S+mI (mは乗数Rdの値で決まる)
+    _cpu.idle(multiply_cycles(rd_val, true));
+}
+
+// LSL/LSR/ASR/ROR Rd, Rs (シフト量はRsの下位8bit)
+fn shift_reg_op(_cpu: &mut CPU, shift: ShiftType, rs: u8, rd: u8) {
+    let carry: bool = _cpu.reg.cpsr.contains(PSR::C);
+    let (_ret, _carry) = shift_reg(shift, _cpu.reg.r[rd as usize], _cpu.reg.r[rs as usize], carry);
+    _cpu.reg.r[rd as usize] = _ret;
+    _cpu.psr_logic_update(_ret, _carry);
 }
 
 // fn mov(_cpu: &mut CPU, _op: ThumbInstruction)
@@ -301,7 +360,8 @@ fn and(_cpu: &mut CPU, rs: u8, rd: u8)
 {
     let _ret: u32 = _cpu.reg.r[rd as usize] & _cpu.reg.r[rs as usize];
     _cpu.reg.r[rd as usize] = _ret;
-    _cpu.psr_op_update(_ret, false, false);
+    let _carry: bool = _cpu.reg.cpsr.contains(PSR::C);
+    _cpu.psr_logic_update(_ret, _carry);
 }
 
 // EOR Rd, Rs
@@ -309,7 +369,8 @@ fn eor(_cpu: &mut CPU, rs: u8, rd: u8)
 {
     let _ret: u32 = _cpu.reg.r[rd as usize] ^ _cpu.reg.r[rs as usize];
     _cpu.reg.r[rd as usize] = _ret;
-    _cpu.psr_op_update(_ret, false, false);
+    let _carry: bool = _cpu.reg.cpsr.contains(PSR::C);
+    _cpu.psr_logic_update(_ret, _carry);
 }
 
 // ORR Rd, Rs
@@ -317,7 +378,8 @@ fn orr(_cpu: &mut CPU, rs: u8, rd: u8)
 {
     let _ret: u32 = _cpu.reg.r[rd as usize] | _cpu.reg.r[rs as usize];
     _cpu.reg.r[rd as usize] = _ret;
-    _cpu.psr_op_update(_ret, false, false);
+    let _carry: bool = _cpu.reg.cpsr.contains(PSR::C);
+    _cpu.psr_logic_update(_ret, _carry);
 }
 
 // BIC Rd, Rs
@@ -325,7 +387,8 @@ fn bic(_cpu: &mut CPU, rs: u8, rd: u8)
 {
     let _ret: u32 = _cpu.reg.r[rd as usize] & (!_cpu.reg.r[rs as usize]);
     _cpu.reg.r[rd as usize] = _ret;
-    _cpu.psr_op_update(_ret, false, false);
+    let _carry: bool = _cpu.reg.cpsr.contains(PSR::C);
+    _cpu.psr_logic_update(_ret, _carry);
 }
 
 // MVN Rd, Rs
@@ -333,19 +396,21 @@ fn mvn(_cpu: &mut CPU, rs: u8, rd: u8)
 {
     let _ret = !_cpu.reg.r[rs as usize];
     _cpu.reg.r[rd as usize] = _ret;
-    _cpu.psr_op_update(_ret, false, false);
+    let _carry: bool = _cpu.reg.cpsr.contains(PSR::C);
+    _cpu.psr_logic_update(_ret, _carry);
 }
 
 // TST Rd, Rs
 fn tst(_cpu: &mut CPU, rs: u8, rd: u8) {
     let _ret: u32 = _cpu.reg.r[rd as usize] & _cpu.reg.r[rs as usize];
-    _cpu.psr_op_update(_ret, false, false);
+    let _carry: bool = _cpu.reg.cpsr.contains(PSR::C);
+    _cpu.psr_logic_update(_ret, _carry);
 }
 
 fn beq(_cpu: &mut CPU, softset8: i8) -> bool {
     if _cpu.reg.cpsr.contains(PSR::Z) {
         let offset: i32 = softset8 as i32;
-        _cpu.reg.pc = _cpu.reg.pc.wrapping_add((offset << 1) as u32 + 2);
+        _cpu.set_pc(_cpu.reg.pc.wrapping_add((offset << 1) as u32));
         true
     } else {
         false
@@ -355,7 +420,7 @@ fn beq(_cpu: &mut CPU, softset8: i8) -> bool {
 fn bne(_cpu: &mut CPU, softset8: i8) -> bool {
     if !_cpu.reg.cpsr.contains(PSR::Z) {
         let offset: i32 = softset8 as i32;
-        _cpu.reg.pc = _cpu.reg.pc.wrapping_add((offset << 1) as u32 + 2);
+        _cpu.set_pc(_cpu.reg.pc.wrapping_add((offset << 1) as u32));
         true
     } else {
         false
@@ -365,7 +430,7 @@ fn bne(_cpu: &mut CPU, softset8: i8) -> bool {
 fn bcs(_cpu: &mut CPU, softset8: i8) -> bool {
     if _cpu.reg.cpsr.contains(PSR::C) {
         let offset: i32 = softset8 as i32;
-        _cpu.reg.pc = _cpu.reg.pc.wrapping_add((offset << 1) as u32 + 2);
+        _cpu.set_pc(_cpu.reg.pc.wrapping_add((offset << 1) as u32));
         true
     } else {
         false
@@ -375,7 +440,7 @@ fn bcs(_cpu: &mut CPU, softset8: i8) -> bool {
 fn bcc(_cpu: &mut CPU, softset8: i8) -> bool {
     if !_cpu.reg.cpsr.contains(PSR::C) {
         let offset: i32 = softset8 as i32;
-        _cpu.reg.pc = _cpu.reg.pc.wrapping_add((offset << 1) as u32 + 2);
+        _cpu.set_pc(_cpu.reg.pc.wrapping_add((offset << 1) as u32));
         true
     } else {
         false
@@ -385,7 +450,7 @@ fn bcc(_cpu: &mut CPU, softset8: i8) -> bool {
 fn bmi(_cpu: &mut CPU, softset8: i8) -> bool {
     if _cpu.reg.cpsr.contains(PSR::N) {
         let offset: i32 = softset8 as i32;
-        _cpu.reg.pc = _cpu.reg.pc.wrapping_add((offset << 1) as u32 + 2);
+        _cpu.set_pc(_cpu.reg.pc.wrapping_add((offset << 1) as u32));
         true
     } else {
         false
@@ -395,7 +460,7 @@ fn bmi(_cpu: &mut CPU, softset8: i8) -> bool {
 fn bpl(_cpu: &mut CPU, softset8: i8) -> bool {
     if !_cpu.reg.cpsr.contains(PSR::N) {
         let offset: i32 = softset8 as i32;
-        _cpu.reg.pc = _cpu.reg.pc.wrapping_add((offset << 1) as u32 + 2);
+        _cpu.set_pc(_cpu.reg.pc.wrapping_add((offset << 1) as u32));
         true
     } else {
         false
@@ -405,7 +470,7 @@ fn bpl(_cpu: &mut CPU, softset8: i8) -> bool {
 fn bvs(_cpu: &mut CPU, softset8: i8) -> bool {
     if _cpu.reg.cpsr.contains(PSR::V) {
         let offset: i32 = softset8 as i32;
-        _cpu.reg.pc = _cpu.reg.pc.wrapping_add((offset << 1) as u32 + 2);
+        _cpu.set_pc(_cpu.reg.pc.wrapping_add((offset << 1) as u32));
         true
     } else {
         false
@@ -415,7 +480,7 @@ fn bvs(_cpu: &mut CPU, softset8: i8) -> bool {
 fn bvc(_cpu: &mut CPU, softset8: i8) -> bool {
     if !_cpu.reg.cpsr.contains(PSR::V) {
         let offset: i32 = softset8 as i32;
-        _cpu.reg.pc = _cpu.reg.pc.wrapping_add((offset << 1) as u32 + 2);
+        _cpu.set_pc(_cpu.reg.pc.wrapping_add((offset << 1) as u32));
         true
     } else {
         false
@@ -425,7 +490,7 @@ fn bvc(_cpu: &mut CPU, softset8: i8) -> bool {
 fn bhi(_cpu: &mut CPU, softset8: i8) -> bool {
     if _cpu.reg.cpsr.contains(PSR::C) && !_cpu.reg.cpsr.contains(PSR::Z) {
         let offset: i32 = softset8 as i32;
-        _cpu.reg.pc = _cpu.reg.pc.wrapping_add((offset << 1) as u32 + 2);
+        _cpu.set_pc(_cpu.reg.pc.wrapping_add((offset << 1) as u32));
         true
     } else {
         false
@@ -435,7 +500,7 @@ fn bhi(_cpu: &mut CPU, softset8: i8) -> bool {
 fn bls(_cpu: &mut CPU, softset8: i8) -> bool {
     if !_cpu.reg.cpsr.contains(PSR::C) || _cpu.reg.cpsr.contains(PSR::Z) {
         let offset: i32 = softset8 as i32;
-        _cpu.reg.pc = _cpu.reg.pc.wrapping_add((offset << 1) as u32 + 2);
+        _cpu.set_pc(_cpu.reg.pc.wrapping_add((offset << 1) as u32));
         true
     } else {
         false
@@ -445,7 +510,7 @@ fn bls(_cpu: &mut CPU, softset8: i8) -> bool {
 fn bge(_cpu: &mut CPU, softset8: i8) -> bool {
     if _cpu.reg.cpsr.contains(PSR::N) == _cpu.reg.cpsr.contains(PSR::V) {
         let offset: i32 = softset8 as i32;
-        _cpu.reg.pc = _cpu.reg.pc.wrapping_add((offset << 1) as u32 + 2);
+        _cpu.set_pc(_cpu.reg.pc.wrapping_add((offset << 1) as u32));
         true
     } else {
         false
@@ -455,7 +520,7 @@ fn bge(_cpu: &mut CPU, softset8: i8) -> bool {
 fn blt(_cpu: &mut CPU, softset8: i8) -> bool {
     if _cpu.reg.cpsr.contains(PSR::N) != _cpu.reg.cpsr.contains(PSR::V) {
         let offset: i32 = softset8 as i32;
-        _cpu.reg.pc = _cpu.reg.pc.wrapping_add((offset << 1) as u32 + 2);
+        _cpu.set_pc(_cpu.reg.pc.wrapping_add((offset << 1) as u32));
         true
     } else {
         false
@@ -465,7 +530,7 @@ fn blt(_cpu: &mut CPU, softset8: i8) -> bool {
 fn bgt(_cpu: &mut CPU, softset8: i8) -> bool {
     if !_cpu.reg.cpsr.contains(PSR::Z) && (_cpu.reg.cpsr.contains(PSR::N) == _cpu.reg.cpsr.contains(PSR::V)) {
         let offset: i32 = softset8 as i32;
-        _cpu.reg.pc = _cpu.reg.pc.wrapping_add((offset << 1) as u32 + 2);
+        _cpu.set_pc(_cpu.reg.pc.wrapping_add((offset << 1) as u32));
         true
     } else {
         false
@@ -475,7 +540,7 @@ fn bgt(_cpu: &mut CPU, softset8: i8) -> bool {
 fn ble(_cpu: &mut CPU, softset8: i8) -> bool {
     if _cpu.reg.cpsr.contains(PSR::Z) || (_cpu.reg.cpsr.contains(PSR::N) != _cpu.reg.cpsr.contains(PSR::V)) {
         let offset: i32 = softset8 as i32;
-        _cpu.reg.pc = _cpu.reg.pc.wrapping_add((offset << 1) as u32 + 2);
+        _cpu.set_pc(_cpu.reg.pc.wrapping_add((offset << 1) as u32));
         true
     } else {
         false
@@ -485,43 +550,104 @@ fn ble(_cpu: &mut CPU, softset8: i8) -> bool {
 // SWI Imm8bit
 fn swi(_cpu: &mut CPU, _val: u8)
 {
-    // PCにImm8bit
-    _cpu.reg.pc = _val as u32;
-    // modeをSVCに
-    _cpu.reg.cpsr.insert(PSR::MODE_SVC);
-    // LRを+=2
-    _cpu.reg.lr += 2;
+    // SVCモードへ移行し、ベクタ(0x08)へ分岐 (HLE時はBIOS処理を直接実行)
+    _cpu.software_interrupt(_val);
 }
 
+// B Offset11
 fn b(_cpu: &mut CPU, softset11: i16) {
     let offset: i32 = softset11 as i32;
-    _cpu.reg.pc = _cpu.reg.pc.wrapping_add((offset << 1) as u32 + 2);
+    _cpu.set_pc(_cpu.reg.pc.wrapping_add((offset << 1) as u32));
 }
 
-fn bl(_cpu: &mut CPU, softset11: i16) {
-    let offset: i32 = softset11 as i32;
-    _cpu.reg.lr = _cpu.reg.pc.wrapping_add(2);
-    _cpu.reg.pc = _cpu.reg.pc.wrapping_add((offset << 1) as u32 + 2);
+// BL 前半 (H=0): LR = PC + (Offset上位 << 12)
+fn bl_hi(_cpu: &mut CPU, softset11: i16) {
+    // Offset11を符号拡張
+    let offset: i32 = ((softset11 as i32) << 21) >> 21;
+    _cpu.reg.set_reg(14, _cpu.reg.pc.wrapping_add((offset << 12) as u32));
+}
+
+// BL 後半 (H=1): PC = LR + (Offset下位 << 1), LR = 次の命令 | 1
+fn bl_lo(_cpu: &mut CPU, softset11: i16) {
+    let next: u32 = _cpu.reg.pc.wrapping_sub(2);
+    let target: u32 = _cpu.reg.reg(14).wrapping_add(((softset11 as u32) & 0x7FF) << 1);
+    _cpu.reg.set_reg(14, next | 1);
+    _cpu.set_pc(target);
+}
+
+// LDR Rd, [addr] (非アライメント時は回転)
+fn load_word_rotated(_cpu: &mut CPU, addr: u32) -> u32 {
+    let val: u32 = _cpu.read_word(addr, MemoryAccess::NonSeq);
+    val.rotate_right((addr & 3) * 8)
+}
+
+// LDRH Rd, [addr] (非アライメント時は回転)
+fn load_hword_rotated(_cpu: &mut CPU, addr: u32) -> u32 {
+    let val: u32 = _cpu.read_hword(addr, MemoryAccess::NonSeq) as u32;
+    val.rotate_right((addr & 1) * 8)
+}
+
+// ロード: Cycle += 1S+1N+1I, ストア: Cycle += 2N
+fn load_store_cycle(_cpu: &mut CPU, l: u8) {
+    if l != 0 {
+        _cpu.idle(1);
+    }
 }
 
 fn exec_op_format01(_cpu: &mut CPU, _op: ThumbInstruction) {
     if let ThumbInstruction::MoveShiftedRegister(op, offset, rs, rd) = _op {
         trace!("Format01: MoveShiftedRegister - Op: {}, Offset: {}, Rs: {}, Rd: {}", op, offset, rs, rd);
-        // TODO
+        // LSL/LSR/ASR Rd, Rs, #Offset5
+        let shift: ShiftType = match op {
+            0b00 => ShiftType::LSL,
+            0b01 => ShiftType::LSR,
+            0b10 => ShiftType::ASR,
+            _ => unreachable!(),
+        };
+        let carry: bool = _cpu.reg.cpsr.contains(PSR::C);
+        let (_ret, _carry) = shift_imm(shift, _cpu.reg.r[rs as usize], offset as u32, carry);
+        _cpu.reg.r[rd as usize] = _ret;
+        _cpu.psr_logic_update(_ret, _carry); // Cycle += 1S
     }
 }
 
 fn exec_op_format02(_cpu: &mut CPU, _op: ThumbInstruction) {
-    // TODO
     if let ThumbInstruction::AddSubtract(op, offset, rs, rd) = _op {
         trace!("Format02: AddSubtract - Op: {}, Offset: {}, Rs: {}, Rd: {}", op, offset, rs, rd);
+        let rs_val: u32 = _cpu.reg.r[rs as usize];
+        // Op Bit1: 即値フラグ(I), Bit0: ADD/SUB
+        let operand: u32 = if (op & 0b10) != 0 {
+            offset as u32
+        } else {
+            _cpu.reg.r[offset as usize]
+        };
+        let _ret: u32 = match op & 0b01 {
+            0 => _cpu.alu_add(rs_val, operand, false, true), // ADD Rd, Rs, Rn/#Offset3
+            _ => _cpu.alu_sub(rs_val, operand, true, true),  // SUB Rd, Rs, Rn/#Offset3
+        };
+        _cpu.reg.r[rd as usize] = _ret; // Cycle += 1S
     }
 }
 
 fn exec_op_format03(_cpu: &mut CPU, _op: ThumbInstruction) {
-    // TODO
     if let ThumbInstruction::MoveCompareAddSubtractImmediate(op, rd, offset) = _op {
         trace!("Format03: MoveCompareAddSubtractImmediate - Op: {}, Rd: {}, Offset: {}", op, rd, offset);
+        let rd_val: u32 = _cpu.reg.r[rd as usize];
+        let imm: u32 = offset as u8 as u32;
+        match op {
+            // MOV Rd, #Offset8
+            0b00 => {
+                let _carry: bool = _cpu.reg.cpsr.contains(PSR::C);
+                _cpu.psr_logic_update(imm, _carry);
+                _cpu.reg.r[rd as usize] = imm;
+            },
+            // CMP Rd, #Offset8
+            0b01 => { _cpu.alu_sub(rd_val, imm, true, true); },
+            // ADD Rd, #Offset8
+            0b10 => _cpu.reg.r[rd as usize] = _cpu.alu_add(rd_val, imm, false, true),
+            // SUB Rd, #Offset8
+            _ => _cpu.reg.r[rd as usize] = _cpu.alu_sub(rd_val, imm, true, true),
+        } // Cycle += 1S
     }
 }
 
@@ -531,148 +657,356 @@ fn exec_op_format04(_cpu: &mut CPU, _op: ThumbInstruction) {
         match op {
             0b0000 => and(_cpu, rs, rd),
             0b0001 => eor(_cpu, rs, rd),
-            0b0010 => orr(_cpu, rs, rd),
-            0b0011 => bic(_cpu, rs, rd),
-            0b0100 => mvn(_cpu, rs, rd),
-            0b0101 => tst(_cpu, rs, rd),
-            _ => panic!("Unknown Format04(ALU Op) Execute"),
+            0b0010 => shift_reg_op(_cpu, ShiftType::LSL, rs, rd),
+            0b0011 => shift_reg_op(_cpu, ShiftType::LSR, rs, rd),
+            0b0100 => shift_reg_op(_cpu, ShiftType::ASR, rs, rd),
+            0b0101 => adc(_cpu, rs, rd),
+            0b0110 => sbc(_cpu, rs, rd),
+            0b0111 => shift_reg_op(_cpu, ShiftType::ROR, rs, rd),
+            0b1000 => tst(_cpu, rs, rd),
+            0b1001 => neg(_cpu, rs, rd),
+            0b1010 => cmp(_cpu, rs, rd),
+            0b1011 => cmn(_cpu, rs, rd),
+            0b1100 => orr(_cpu, rs, rd),
+            0b1101 => mul(_cpu, rs, rd),
+            0b1110 => bic(_cpu, rs, rd),
+            0b1111 => mvn(_cpu, rs, rd),
+            _ => unreachable!(),
+        }
+        // Cycle += 1S (シフト: 1S+1I)
+        if let 0b0010 | 0b0011 | 0b0100 | 0b0111 = op {
+            _cpu.idle(1);
         }
-        _cpu.tick += 1; // Cycle += 1S
     }
 }
 
 fn exec_op_format05(_cpu: &mut CPU, _op: ThumbInstruction) {
-    // TODO
     if let ThumbInstruction::HighRegisterOperationsBranchExchange(op, h1, h2, rs_hs, rd_hd) = _op {
         trace!("Format05: HighRegisterOperationsBranchExchange - Op: {}, H1: {}, H2: {}, Rs/Hs: {}, Rd/Hd: {}", op, h1, h2, rs_hs, rd_hd);
+        // H1/H2でR8~R15を指定
+        let rd: u8 = rd_hd | (h1 << 3);
+        let rs: u8 = rs_hs | (h2 << 3);
+        let rs_val: u32 = _cpu.reg.reg(rs);
+        let rd_val: u32 = _cpu.reg.reg(rd);
+        match op {
+            // ADD Rd, Hs / ADD Hd, Rs / ADD Hd, Hs (フラグ変化なし)
+            0b00 => _cpu.set_reg(rd, rd_val.wrapping_add(rs_val)),
+            // CMP Rd, Hs / CMP Hd, Rs / CMP Hd, Hs
+            0b01 => { _cpu.alu_sub(rd_val, rs_val, true, true); },
+            // MOV Rd, Hs / MOV Hd, Rs / MOV Hd, Hs (フラグ変化なし)
+            0b10 => _cpu.set_reg(rd, rs_val),
+            // BX Rs / BX Hs (Bit0でThumb/ARM切り替え)
+            _ => {
+                if (rs_val & 1) != 0 {
+                    _cpu.reg.cpsr.insert(PSR::T);
+                } else {
+                    _cpu.reg.cpsr.remove(PSR::T);
+                }
+                _cpu.set_pc(rs_val);
+            },
+        } // Cycle += 1S (PC変更時: 2S+1N)
     }
 }
 
 fn exec_op_format06(_cpu: &mut CPU, _op: ThumbInstruction) {
-    // TODO
     if let ThumbInstruction::PCRelativeLoad(rd, word8) = _op {
         trace!("Format06: PCRelativeLoad - Rd: {}, Word8: {}", rd, word8);
+        // LDR Rd, [PC, #Imm] (PCのBit1は0として扱う)
+        let addr: u32 = (_cpu.reg.pc & !2).wrapping_add((word8 as u32) << 2);
+        _cpu.reg.r[rd as usize] = _cpu.read_word(addr, MemoryAccess::NonSeq);
+        _cpu.idle(1); // Cycle += 1S+1N+1I
     }
 }
 
 fn exec_op_format07(_cpu: &mut CPU, _op: ThumbInstruction) {
-    // TODO
     if let ThumbInstruction::LoadStoreWithRelativeOffset(l, b, ro, rb, rd) = _op {
         trace!("Format07: LoadStoreWithRelativeOffset - L: {}, B: {}, Ro: {}, Rb: {}, Rd: {}", l, b, ro, rb, rd);
+        let addr: u32 = _cpu.reg.r[rb as usize].wrapping_add(_cpu.reg.r[ro as usize]);
+        match (l, b) {
+            // STR Rd, [Rb, Ro]
+            (0, 0) => { let val = _cpu.reg.r[rd as usize]; _cpu.write_word(addr, val, MemoryAccess::NonSeq); },
+            // STRB Rd, [Rb, Ro]
+            (0, _) => { let val = _cpu.reg.r[rd as usize] as u8; _cpu.write_byte(addr, val, MemoryAccess::NonSeq); },
+            // LDR Rd, [Rb, Ro]
+            (_, 0) => _cpu.reg.r[rd as usize] = load_word_rotated(_cpu, addr),
+            // LDRB Rd, [Rb, Ro]
+            (_, _) => _cpu.reg.r[rd as usize] = _cpu.read_byte(addr, MemoryAccess::NonSeq) as u32,
+        }
+        load_store_cycle(_cpu, l);
     }
 }
 
 fn exec_op_format08(_cpu: &mut CPU, _op: ThumbInstruction) {
-    // TODO
     if let ThumbInstruction::LoadStoreSignExtendedByteHalfword(h, s, ro, rb, rd) = _op {
         trace!("Format08: LoadStoreSignExtendedByteHalfword - H: {}, S: {}, Ro: {}, Rb: {}, Rd: {}", h, s, ro, rb, rd);
+        let addr: u32 = _cpu.reg.r[rb as usize].wrapping_add(_cpu.reg.r[ro as usize]);
+        match (s, h) {
+            // STRH Rd, [Rb, Ro]
+            (0, 0) => {
+                let val = _cpu.reg.r[rd as usize] as u16;
+                _cpu.write_hword(addr, val, MemoryAccess::NonSeq);
+            },
+            // LDRH Rd, [Rb, Ro]
+            (0, _) => _cpu.reg.r[rd as usize] = load_hword_rotated(_cpu, addr),
+            // LDSB Rd, [Rb, Ro]
+            (_, 0) => _cpu.reg.r[rd as usize] = _cpu.read_byte(addr, MemoryAccess::NonSeq) as i8 as i32 as u32,
+            // LDSH Rd, [Rb, Ro] (非アライメント時はLDSB相当)
+            (_, _) => _cpu.reg.r[rd as usize] = if (addr & 1) != 0 {
+                _cpu.read_byte(addr, MemoryAccess::NonSeq) as i8 as i32 as u32
+            } else {
+                _cpu.read_hword(addr, MemoryAccess::NonSeq) as i16 as i32 as u32
+            },
+        }
+        load_store_cycle(_cpu, s | h);
     }
 }
 
 fn exec_op_format09(_cpu: &mut CPU, _op: ThumbInstruction) {
-    // TODO
     if let ThumbInstruction::LoadStoreWithImmediateOffset(b, l, offset5, rb, rd) = _op {
         trace!("Format09: LoadStoreWithImmediateOffset - B: {}, L: {}, Offset5: {}, Rb: {}, Rd: {}", b, l, offset5, rb, rd);
+        let base: u32 = _cpu.reg.r[rb as usize];
+        match (l, b) {
+            // STR Rd, [Rb, #Imm]
+            (0, 0) => {
+                let val = _cpu.reg.r[rd as usize];
+                _cpu.write_word(base.wrapping_add((offset5 as u32) << 2), val, MemoryAccess::NonSeq);
+            },
+            // STRB Rd, [Rb, #Imm]
+            (0, _) => {
+                let val = _cpu.reg.r[rd as usize] as u8;
+                _cpu.write_byte(base.wrapping_add(offset5 as u32), val, MemoryAccess::NonSeq);
+            },
+            // LDR Rd, [Rb, #Imm]
+            (_, 0) => _cpu.reg.r[rd as usize] = load_word_rotated(_cpu, base.wrapping_add((offset5 as u32) << 2)),
+            // LDRB Rd, [Rb, #Imm]
+            (_, _) => _cpu.reg.r[rd as usize] = _cpu.read_byte(base.wrapping_add(offset5 as u32), MemoryAccess::NonSeq) as u32,
+        }
+        load_store_cycle(_cpu, l);
     }
 }
 
 fn exec_op_format10(_cpu: &mut CPU, _op: ThumbInstruction) {
-    // TODO
     if let ThumbInstruction::LoadStoreHalfword(l, offset5, rb, rd) = _op {
         trace!("Format10: LoadStoreHalfword - L: {}, Offset5: {}, Rb: {}, Rd: {}", l, offset5, rb, rd);
+        let addr: u32 = _cpu.reg.r[rb as usize].wrapping_add((offset5 as u32) << 1);
+        if l != 0 {
+            // LDRH Rd, [Rb, #Imm]
+            _cpu.reg.r[rd as usize] = load_hword_rotated(_cpu, addr);
+        } else {
+            // STRH Rd, [Rb, #Imm]
+            let val = _cpu.reg.r[rd as usize] as u16;
+            _cpu.write_hword(addr, val, MemoryAccess::NonSeq);
+        }
+        load_store_cycle(_cpu, l);
     }
 }
 
 fn exec_op_format11(_cpu: &mut CPU, _op: ThumbInstruction) {
-    // TODO
-    if let ThumbInstruction::SPRelativeLoadStore(l, rb, word8) = _op {
-        trace!("Format11: SPRelativeLoadStore - L: {}, Rb: {}, Word8: {}", l, rb, word8);
+    if let ThumbInstruction::SPRelativeLoadStore(l, rd, word8) = _op {
+        trace!("Format11: SPRelativeLoadStore - L: {}, Rd: {}, Word8: {}", l, rd, word8);
+        let addr: u32 = _cpu.reg.reg(13).wrapping_add((word8 as u32) << 2);
+        if l != 0 {
+            // LDR Rd, [SP, #Imm]
+            _cpu.reg.r[rd as usize] = load_word_rotated(_cpu, addr);
+        } else {
+            // STR Rd, [SP, #Imm]
+            let val = _cpu.reg.r[rd as usize];
+            _cpu.write_word(addr, val, MemoryAccess::NonSeq);
+        }
+        load_store_cycle(_cpu, l);
     }
 }
 
 fn exec_op_format12(_cpu: &mut CPU, _op: ThumbInstruction) {
-    // TODO
-    if let ThumbInstruction::LoadAddress(sp, rb, word8) = _op {
-        trace!("Format12: LoadAddress - SP: {}, Rb: {}, Word8: {}", sp, rb, word8);
+    if let ThumbInstruction::LoadAddress(sp, rd, word8) = _op {
+        trace!("Format12: LoadAddress - SP: {}, Rd: {}, Word8: {}", sp, rd, word8);
+        // ADD Rd, PC/SP, #Imm (PCのBit1は0として扱う、フラグ変化なし)
+        let base: u32 = if sp != 0 { _cpu.reg.reg(13) } else { _cpu.reg.pc & !2 };
+        _cpu.reg.r[rd as usize] = base.wrapping_add((word8 as u32) << 2); // Cycle += 1S
     }
 }
 
 fn exec_op_format13(_cpu: &mut CPU, _op: ThumbInstruction) {
-    // TODO
     if let ThumbInstruction::AddOffsetToStackPointer(s, sword7) = _op {
         trace!("Format13: AddOffsetToStackPointer - S: {}, SWord7: {}", s, sword7);
+        // ADD SP, #±Imm (フラグ変化なし)
+        let offset: u32 = (sword7 as u32) << 2;
+        let sp: u32 = _cpu.reg.reg(13);
+        _cpu.reg.set_reg(13, if s != 0 {
+            sp.wrapping_sub(offset)
+        } else {
+            sp.wrapping_add(offset)
+        }); // Cycle += 1S
     }
 }
 
 fn exec_op_format14(_cpu: &mut CPU, _op: ThumbInstruction) {
-    // TODO
     if let ThumbInstruction::PushPopRegisters(l, r, rlist) = _op {
         trace!("Format14: PushPopRegisters - L: {}, R: {}, Rlist: {}", l, r, rlist);
+        // R=1: PUSHはLR、POPはPCを追加
+        let extra: u8 = if l != 0 { 15 } else { 14 };
+        let mut regs: u16 = rlist as u16;
+        if r != 0 {
+            regs |= 1 << extra;
+        }
+        // Rlistが空の場合はR15のみ転送し、SPは0x40変化する
+        let (regs, size): (u16, u32) = if regs == 0 {
+            (1 << 15, 0x40)
+        } else {
+            (regs, regs.count_ones() * 4)
+        };
+        let first: u8 = regs.trailing_zeros() as u8;
+
+        if l != 0 {
+            // POP {Rlist}{PC}
+            let mut addr: u32 = _cpu.reg.reg(13);
+            for n in 0..16u8 {
+                if (regs & (1 << n)) == 0 {
+                    continue;
+                }
+                // 先頭のみN、以降はS
+                let access: MemoryAccess = if n == first { MemoryAccess::NonSeq } else { MemoryAccess::Seq };
+                let val: u32 = _cpu.read_word(addr, access);
+                _cpu.set_reg(n, val);
+                addr = addr.wrapping_add(4);
+            }
+            _cpu.reg.set_reg(13, _cpu.reg.reg(13).wrapping_add(size));
+            _cpu.idle(1); // Cycle += nS+1N+1I (PC: +1S+1N)
+        } else {
+            // PUSH {Rlist}{LR} (最下位レジスタが最下位アドレス)
+            let mut addr: u32 = _cpu.reg.reg(13).wrapping_sub(size);
+            _cpu.reg.set_reg(13, addr);
+            for n in 0..16u8 {
+                if (regs & (1 << n)) == 0 {
+                    continue;
+                }
+                // 先頭のみN、以降はS
+                let access: MemoryAccess = if n == first { MemoryAccess::NonSeq } else { MemoryAccess::Seq };
+                let val: u32 = if n == 15 { _cpu.reg.pc.wrapping_add(2) } else { _cpu.reg.reg(n) };
+                _cpu.write_word(addr, val, access);
+                addr = addr.wrapping_add(4);
+            }
+            // Cycle += (n-1)S+2N
+        }
     }
 }
 
 fn exec_op_format15(_cpu: &mut CPU, _op: ThumbInstruction) {
-    // TODO
     if let ThumbInstruction::MultipleLoadStore(l, rb, rlist) = _op {
         trace!("Format15: MultipleLoadStore - L: {}, Rb: {}, Rlist: {}", l, rb, rlist);
+        let base: u32 = _cpu.reg.r[rb as usize];
+        // Rlistが空の場合はR15のみ転送し、Rbは0x40変化する
+        let (regs, size): (u16, u32) = if rlist == 0 {
+            (1 << 15, 0x40)
+        } else {
+            (rlist as u16, rlist.count_ones() * 4)
+        };
+        let wb_addr: u32 = base.wrapping_add(size);
+        let first: u8 = regs.trailing_zeros() as u8;
+
+        let mut addr: u32 = base;
+        if l != 0 {
+            // LDMIA Rb!, {Rlist} (Rbがリストに含まれる場合はライトバックしない)
+            _cpu.reg.r[rb as usize] = wb_addr;
+            for n in 0..16u8 {
+                if (regs & (1 << n)) == 0 {
+                    continue;
+                }
+                // 先頭のみN、以降はS
+                let access: MemoryAccess = if n == first { MemoryAccess::NonSeq } else { MemoryAccess::Seq };
+                let val: u32 = _cpu.read_word(addr, access);
+                _cpu.set_reg(n, val);
+                addr = addr.wrapping_add(4);
+            }
+            _cpu.idle(1); // Cycle += nS+1N+1I
+        } else {
+            // STMIA Rb!, {Rlist} (Rbが先頭なら旧ベース、それ以外は更新後のベースをストア)
+            for n in 0..16u8 {
+                if (regs & (1 << n)) == 0 {
+                    continue;
+                }
+                // 先頭のみN、以降はS
+                let access: MemoryAccess = if n == first { MemoryAccess::NonSeq } else { MemoryAccess::Seq };
+                let val: u32 = if n == rb && n != first {
+                    wb_addr
+                } else if n == 15 {
+                    _cpu.reg.pc.wrapping_add(2)
+                } else {
+                    _cpu.reg.reg(n)
+                };
+                _cpu.write_word(addr, val, access);
+                addr = addr.wrapping_add(4);
+            }
+            _cpu.reg.r[rb as usize] = wb_addr;
+            // Cycle += (n-1)S+2N
+        }
     }
 }
 
 fn exec_op_format16(_cpu: &mut CPU, _op: ThumbInstruction) {
-    let mut _ret = false;
     if let ThumbInstruction::ConditionalBranch(cond, softset8) = _op {
         trace!("Format16: ConditionalBranch - Cond: {}, Softset8: {}", cond, softset8);
+        // Cycle += 2S+1N (分岐しない場合: 1S)
         match cond {
-            0x00 => _ret = beq(_cpu, softset8),
-            0x01 => _ret = bne(_cpu, softset8),
-            0x02 => _ret = bcs(_cpu, softset8),
-            0x03 => _ret = bcc(_cpu, softset8),
-            0x04 => _ret = bmi(_cpu, softset8),
-            0x05 => _ret = bpl(_cpu, softset8),
-            0x06 => _ret = bvs(_cpu, softset8),
-            0x07 => _ret = bvc(_cpu, softset8),
-            0x08 => _ret = bhi(_cpu, softset8),
-            0x09 => _ret = bls(_cpu, softset8),
-            0x0A => _ret = bge(_cpu, softset8),
-            0x0B => _ret = blt(_cpu, softset8),
-            0x0C => _ret = bgt(_cpu, softset8),
-            0x0D => _ret = ble(_cpu, softset8),
-            _ => panic!("Unknown Format16(ALU Op) Execute"),
-        }
-        if _ret != false {
-            _cpu.tick += 3; // Cycle += 2S+1N
-        }else{
-            _cpu.tick += 1; // Cycle += 1S
-        }
+            0x00 => beq(_cpu, softset8),
+            0x01 => bne(_cpu, softset8),
+            0x02 => bcs(_cpu, softset8),
+            0x03 => bcc(_cpu, softset8),
+            0x04 => bmi(_cpu, softset8),
+            0x05 => bpl(_cpu, softset8),
+            0x06 => bvs(_cpu, softset8),
+            0x07 => bvc(_cpu, softset8),
+            0x08 => bhi(_cpu, softset8),
+            0x09 => bls(_cpu, softset8),
+            0x0A => bge(_cpu, softset8),
+            0x0B => blt(_cpu, softset8),
+            0x0C => bgt(_cpu, softset8),
+            0x0D => ble(_cpu, softset8),
+            // LUTはcond=0x0E/0x0Fを振り分け済み (LUTを経由せず構築された命令は未定義命令として扱う)
+            _ => {
+                _cpu.raise_exception(Exception::Undefined);
+                true
+            },
+        };
     }
 }
 
 fn exec_op_format17(_cpu: &mut CPU, _op: ThumbInstruction) {
     if let ThumbInstruction::SoftwareInterrupt(value8) = _op {
         trace!("Format17: SoftwareInterrupt - Value8: {}", value8);
-        swi(_cpu, value8);
-        _cpu.tick += 3; // Cycle += 2S+1N
+        swi(_cpu, value8); // Cycle += 2S+1N
     }
 }
 
 fn exec_op_format18(_cpu: &mut CPU, _op: ThumbInstruction) {
-    // TODO
     if let ThumbInstruction::UnconditionalBranch(offset11) = _op {
         trace!("Format18: UnconditionalBranch - Offset11: {}", offset11);
+        b(_cpu, offset11); // Cycle += 2S+1N
     }
 }
 
 fn exec_op_format19(_cpu: &mut CPU, _op: ThumbInstruction) {
-    // TODO
     if let ThumbInstruction::LongBranchWithLink(h, offset) = _op {
         trace!("Format19: LongBranchWithLink - H: {}, Offset: {}", h, offset);
+        if h == 0 {
+            bl_hi(_cpu, offset); // Cycle += 1S
+        } else {
+            bl_lo(_cpu, offset); // Cycle += 2S+1N
+        }
+    }
+}
+
+fn exec_op_undefined(_cpu: &mut CPU, _op: ThumbInstruction) {
+    if let ThumbInstruction::Undefined(instruction) = _op {
+        trace!("Undefined - Instruction: {:#06X}", instruction);
+        _cpu.raise_exception(Exception::Undefined); // Cycle += 2S+1I+1N
+        _cpu.idle(1);
     }
 }
 
 // ARM7TDMI Thumb命令 デコード
 pub fn thumb_op_decode(_cpu: &mut CPU, _instruction: u16) -> (ThumbFormat, ThumbInstruction) {
-    let _op_format = thumb_format_decode(_instruction);
-    _op_format
+    thumb_format_decode(_instruction)
 }
 
 // ARM7TDMI Thumb命令の実行
@@ -697,6 +1031,7 @@ pub fn thumb_op_exec(_cpu: &mut CPU, _format: ThumbFormat, _op_format: ThumbInst
         ThumbFormat::Format17 => exec_op_format17(_cpu, _op_format),
         ThumbFormat::Format18 => exec_op_format18(_cpu, _op_format),
         ThumbFormat::Format19 => exec_op_format19(_cpu, _op_format),
+        ThumbFormat::Undefined => exec_op_undefined(_cpu, _op_format),
     }
 }
 
@@ -706,6 +1041,115 @@ mod tests {
 
     #[test]
     fn test_thumb_format_decode() {
-        // TODO Decode Test
+        assert_eq!(thumb_format_decode(0x1C08).0, ThumbFormat::Format02); // ADD R0, R1, #0
+        assert_eq!(thumb_format_decode(0x5088).0, ThumbFormat::Format07); // STR R0, [R1, R2]
+        assert_eq!(thumb_format_decode(0x5E88).0, ThumbFormat::Format08); // LDSH R0, [R1, R2]
+        assert_eq!(thumb_format_decode(0xF802).0, ThumbFormat::Format19); // BL (H=1)
+    }
+
+    #[test]
+    fn test_thumb_format_lut() {
+        // Format13~19 周辺の境界 (未定義命令の範囲を含む)
+        let cases: [(u16, ThumbFormat); 23] = [
+            (0xB000, ThumbFormat::Format13),  // ADD SP, #0
+            (0xB0FF, ThumbFormat::Format13),  // ADD SP, #-508
+            (0xB100, ThumbFormat::Undefined),
+            (0xB3FF, ThumbFormat::Undefined),
+            (0xB400, ThumbFormat::Format14),  // PUSH {}
+            (0xB5FF, ThumbFormat::Format14),  // PUSH {R0-R7, LR}
+            (0xB600, ThumbFormat::Undefined),
+            (0xBBFF, ThumbFormat::Undefined),
+            (0xBC00, ThumbFormat::Format14),  // POP {}
+            (0xBDFF, ThumbFormat::Format14),  // POP {R0-R7, PC}
+            (0xBE00, ThumbFormat::Undefined),
+            (0xBFFF, ThumbFormat::Undefined),
+            (0xC000, ThumbFormat::Format15),  // STMIA R0!, {}
+            (0xD000, ThumbFormat::Format16),  // BEQ
+            (0xDDFF, ThumbFormat::Format16),  // BLE
+            (0xDE00, ThumbFormat::Undefined), // cond=0x0E
+            (0xDEFF, ThumbFormat::Undefined),
+            (0xDF00, ThumbFormat::Format17),  // SWI 0
+            (0xDFFF, ThumbFormat::Format17),  // SWI 255
+            (0xE000, ThumbFormat::Format18),  // B
+            (0xE7FF, ThumbFormat::Format18),
+            (0xE800, ThumbFormat::Undefined),
+            (0xEFFF, ThumbFormat::Undefined),
+        ];
+        for (instruction, format) in cases.iter() {
+            assert_eq!(thumb_format_decode(*instruction).0, *format, "{:#06X}", instruction);
+        }
+    }
+
+    #[test]
+    fn test_thumb_alu_flags() {
+        let mut cpu = CPU::new();
+        // NEG R0, R1 (0 - 0: C=1, Z=1)
+        cpu.reg.r[1] = 0;
+        exec_op_format04(&mut cpu, ThumbInstruction::ALUOperation(0b1001, 1, 0));
+        assert!(cpu.reg.cpsr.contains(PSR::Z | PSR::C));
+        // ADC R0, R1 (0xFFFFFFFF + 0 + C: 0, C=1)
+        cpu.reg.r[0] = 0xFFFF_FFFF;
+        exec_op_format04(&mut cpu, ThumbInstruction::ALUOperation(0b0101, 1, 0));
+        assert_eq!(cpu.reg.r[0], 0);
+        assert!(cpu.reg.cpsr.contains(PSR::Z | PSR::C));
+        // CMP R0, R1 (0x7FFFFFFF - 0xFFFFFFFF: V=1, C=0)
+        cpu.reg.r[0] = 0x7FFF_FFFF;
+        cpu.reg.r[1] = 0xFFFF_FFFF;
+        exec_op_format04(&mut cpu, ThumbInstruction::ALUOperation(0b1010, 1, 0));
+        assert!(cpu.reg.cpsr.contains(PSR::V | PSR::N));
+        assert!(!cpu.reg.cpsr.contains(PSR::C));
+        // LSR R0, R1 (シフト量32: 0, C=Bit31)
+        cpu.reg.r[0] = 0x8000_0000;
+        cpu.reg.r[1] = 32;
+        exec_op_format04(&mut cpu, ThumbInstruction::ALUOperation(0b0011, 1, 0));
+        assert_eq!(cpu.reg.r[0], 0);
+        assert!(cpu.reg.cpsr.contains(PSR::Z | PSR::C));
+        // MUL R0, R1 (乗数R0が8bit以内: 1I)
+        let tick: u64 = cpu.tick();
+        cpu.reg.r[0] = 3;
+        cpu.reg.r[1] = 0x1000_0000;
+        exec_op_format04(&mut cpu, ThumbInstruction::ALUOperation(0b1101, 1, 0));
+        assert_eq!(cpu.reg.r[0], 0x3000_0000);
+        assert_eq!(cpu.tick() - tick, 1);
+    }
+
+    #[test]
+    fn test_thumb_undefined() {
+        // 0x03000000 の未定義命令 → UNDモード, LR=次の命令
+        for instruction in [0xB100u32, 0xBE00, 0xDE00, 0xE800] {
+            let mut cpu = CPU::new();
+            cpu.write_word(0x0300_0000, instruction, MemoryAccess::NonSeq);
+            cpu.reg.set_cpsr(PSR::MODE_SYS | PSR::T);
+            cpu.set_pc(0x0300_0000);
+            cpu.proc();
+            assert_eq!(cpu.reg.mode().bits(), PSR::MODE_UDF.bits());
+            assert_eq!(cpu.reg.reg(14), 0x0300_0002);
+            assert!(!cpu.reg.cpsr.contains(PSR::T));
+            assert_eq!(cpu.reg.spsr().bits(), (PSR::MODE_SYS | PSR::T).bits());
+        }
+    }
+
+    #[test]
+    fn test_thumb_push_bl() {
+        let mut cpu = CPU::new();
+        cpu.write_word(0x0300_0000, 0xB501_2005, MemoryAccess::NonSeq); // MOV R0, #5 / PUSH {R0, LR}
+        cpu.write_word(0x0300_0004, 0xF802_F000, MemoryAccess::NonSeq); // BL 0x0300000C
+        cpu.write_word(0x0300_0008, 0x0000_BC02, MemoryAccess::NonSeq); // POP {R1}
+        cpu.write_word(0x0300_000C, 0x0000_2107, MemoryAccess::NonSeq); // MOV R1, #7
+        cpu.reg.set_reg(13, 0x0300_7F00);
+        cpu.reg.set_reg(14, 0x1234_5678);
+        cpu.reg.cpsr.insert(PSR::T);
+        cpu.set_pc(0x0300_0000);
+
+        cpu.proc();
+        cpu.proc();
+        assert_eq!(cpu.reg.reg(13), 0x0300_7EF8);
+        assert_eq!(cpu.read_word(0x0300_7EF8, MemoryAccess::NonSeq), 5);
+        assert_eq!(cpu.read_word(0x0300_7EFC, MemoryAccess::NonSeq), 0x1234_5678);
+        cpu.proc();
+        cpu.proc();
+        assert_eq!(cpu.reg.reg(14), 0x0300_0009);
+        cpu.proc();
+        assert_eq!(cpu.reg.r[1], 7);
     }
 }
\ No newline at end of file
@@ -0,0 +1,193 @@
+// =========================================================================
+// [Timer]
+// TM0CNT~TM3CNT (0x04000100~0x0400010F)
+// カウンタ値は開始時刻と現在時刻(マスタクロック)から都度計算する
+// =========================================================================
+use common::*;
+
+// プリスケーラ (1, 64, 256, 1024 サイクル) のシフト量
+const _PRESCALER_SHIFT: [u32; 4] = [0, 6, 8, 10];
+
+const _TMCNT_CASCADE: u16 = 0x0004; // カウントアップタイミング (前段タイマのオーバーフロー)
+const _TMCNT_IRQ: u16     = 0x0040; // オーバーフロー割り込み
+const _TMCNT_ENABLE: u16  = 0x0080; // 動作開始
+const _TMCNT_MASK: u16    = 0x00C7;
+
+#[derive(Clone, Copy)]
+struct Timer {
+    reload: u16,  // リロード値 (TMxCNT_L 書き込み)
+    counter: u16, // start時点のカウンタ値
+    control: u16, // TMxCNT_H
+    start: u64,   // カウント開始時刻
+}
+
+pub struct Timers {
+    timers: [Timer; 4],
+    now: u64,        // 最後に同期したマスタクロック
+    reschedule: u8,  // オーバーフロー時刻の再計算が必要なタイマ (Bit0~3)
+}
+
+#[allow(dead_code)]
+impl Timers {
+    pub fn new() -> Self {
+        Timers {
+            timers: [Timer { reload: 0, counter: 0, control: 0, start: 0 }; 4],
+            now: 0,
+            reschedule: 0,
+        }
+    }
+
+    fn is_enabled(&self, n: usize) -> bool {
+        (self.timers[n].control & _TMCNT_ENABLE) != 0
+    }
+
+    // カウントアップモードか (タイマ0は常に通常モード)
+    pub fn is_cascade(&self, n: usize) -> bool {
+        n != 0 && (self.timers[n].control & _TMCNT_CASCADE) != 0
+    }
+
+    fn shift(&self, n: usize) -> u32 {
+        _PRESCALER_SHIFT[(self.timers[n].control & 3) as usize]
+    }
+
+    // 現在のカウンタ値
+    pub fn counter(&self, n: usize) -> u16 {
+        let timer: &Timer = &self.timers[n];
+        if !self.is_enabled(n) || self.is_cascade(n) {
+            return timer.counter;
+        }
+        let ticks: u64 = self.now.saturating_sub(timer.start) >> self.shift(n);
+        let remain: u64 = 0x10000 - timer.counter as u64;
+        if ticks < remain {
+            timer.counter + ticks as u16
+        } else {
+            // オーバーフロー処理前に読まれた場合はリロード値から折り返す
+            let period: u64 = 0x10000 - timer.reload as u64;
+            timer.reload + ((ticks - remain) % period) as u16
+        }
+    }
+
+    // 次のオーバーフロー時刻 (停止中/カウントアップモードはNone)
+    pub fn next_overflow(&self, n: usize) -> Option<u64> {
+        if !self.is_enabled(n) || self.is_cascade(n) {
+            return None;
+        }
+        let timer: &Timer = &self.timers[n];
+        Some(timer.start + ((0x10000 - timer.counter as u64) << self.shift(n)))
+    }
+
+    // オーバーフロー (リロード値から再開), 戻り値は割り込み要求の有無
+    pub fn overflow(&mut self, n: usize, time: u64) -> bool {
+        let timer: &mut Timer = &mut self.timers[n];
+        timer.counter = timer.reload;
+        timer.start = time;
+        (timer.control & _TMCNT_IRQ) != 0
+    }
+
+    // カウントアップモードのタイマを1つ進める, 戻り値はオーバーフローの有無
+    pub fn count_up(&mut self, n: usize) -> bool {
+        if !self.is_enabled(n) || !self.is_cascade(n) {
+            return false;
+        }
+        let timer: &mut Timer = &mut self.timers[n];
+        let (counter, overflow) = timer.counter.overflowing_add(1);
+        timer.counter = counter;
+        overflow
+    }
+
+    // オーバーフロー時刻の再計算が必要なタイマ (取得後クリア)
+    pub fn take_reschedule(&mut self) -> u8 {
+        std::mem::take(&mut self.reschedule)
+    }
+
+    fn write_control(&mut self, n: usize, val: u16) {
+        let was_enabled: bool = self.is_enabled(n);
+        // 現在値を確定してから設定を切り替える
+        let counter: u16 = self.counter(n);
+        let timer: &mut Timer = &mut self.timers[n];
+        timer.counter = counter;
+        timer.start = self.now;
+        timer.control = val & _TMCNT_MASK;
+        // 停止→開始でリロード値をロード
+        if !was_enabled && (val & _TMCNT_ENABLE) != 0 {
+            timer.counter = timer.reload;
+        }
+        self.reschedule |= 1 << n;
+    }
+}
+
+impl IO for Timers {
+    fn read(&mut self, addr: u32) -> u8 {
+        let offset: usize = (addr & 0x0F) as usize;
+        let n: usize = offset >> 2;
+        match offset & 3 {
+            0 => self.counter(n) as u8,
+            1 => (self.counter(n) >> 8) as u8,
+            2 => self.timers[n].control as u8,
+            _ => 0,
+        }
+    }
+
+    fn write(&mut self, addr: u32, val: u8) {
+        let offset: usize = (addr & 0x0F) as usize;
+        let n: usize = offset >> 2;
+        match offset & 3 {
+            // リロード値 (動作中のカウンタには影響しない)
+            0 => self.timers[n].reload = (self.timers[n].reload & 0xFF00) | val as u16,
+            1 => self.timers[n].reload = (self.timers[n].reload & 0x00FF) | ((val as u16) << 8),
+            2 => self.write_control(n, val as u16),
+            _ => {},
+        }
+    }
+
+    fn update(&mut self, _now: u64) {
+        self.now = _now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timer_counter() {
+        let mut timers = Timers::new();
+        // TM0: Reload=0xFF00, 1/64
+        timers.write(0x0400_0100, 0x00);
+        timers.write(0x0400_0101, 0xFF);
+        timers.update(100);
+        timers.write(0x0400_0102, 0xC1);
+        assert_eq!(timers.take_reschedule(), 0x01);
+        assert_eq!(timers.next_overflow(0), Some(100 + (0x100 << 6)));
+
+        timers.update(100 + 64 * 0x10 + 63);
+        assert_eq!(timers.read(0x0400_0100), 0x10);
+        assert_eq!(timers.read(0x0400_0101), 0xFF);
+        // オーバーフロー未処理でも折り返した値を返す
+        timers.update(100 + 64 * 0x102);
+        assert_eq!(timers.counter(0), 0xFF02);
+
+        // 停止で値を保持
+        assert!(timers.overflow(0, 100 + 64 * 0x100));
+        timers.update(100 + 64 * 0x120);
+        timers.write(0x0400_0102, 0x01);
+        timers.update(100000);
+        assert_eq!(timers.counter(0), 0xFF20);
+        assert_eq!(timers.next_overflow(0), None);
+    }
+
+    #[test]
+    fn test_timer_cascade() {
+        let mut timers = Timers::new();
+        timers.write(0x0400_0104, 0xFE);
+        timers.write(0x0400_0105, 0xFF);
+        timers.write(0x0400_0106, 0x84);
+        assert!(timers.is_cascade(1));
+        assert_eq!(timers.next_overflow(1), None);
+        assert!(!timers.count_up(1));
+        assert!(timers.count_up(1));
+        assert_eq!(timers.counter(1), 0);
+        assert!(!timers.overflow(1, 0));
+        assert_eq!(timers.counter(1), 0xFFFE);
+    }
+}